@@ -0,0 +1,27 @@
+//! Deserialization errors.
+use std::fmt::Display;
+
+use thiserror::Error;
+
+use crate::parsers::{self, error::ReadContext};
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Parser error: {0}")]
+    Parser(#[from] parsers::error::Error),
+
+    /// An error generated by the deserialized type, for example, a missing
+    /// field. It does not contain the read context yet.
+    #[error("{0}")]
+    Custom(String),
+
+    /// A `Custom` error after adding the read context where it happened.
+    #[error("{0}; {1}")]
+    Message(String, ReadContext),
+}
+
+impl serde::de::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::Custom(msg.to_string())
+    }
+}
@@ -0,0 +1,821 @@
+//! Serde deserializer for bencoded input.
+//!
+//! It deserializes bencoded bytes directly into your own types, without
+//! converting them into JSON first. It reads the tokens from the same
+//! [`Tokenizer`] used by the [`BencodeParser`](crate::parsers::BencodeParser),
+//! so the same input is accepted and the same errors are returned.
+//!
+//! Bencode only has four types, so some serde types are mapped:
+//!
+//! - Byte strings are deserialized as strings when they are valid UTF-8, or
+//!   as bytes otherwise. Use `serde_bytes` for fields containing raw bytes.
+//! - Integers are read as `i64` or `u64`, and as `i128` or `u128` when they do
+//!   not fit into 64 bits.
+//! - Booleans are the integers `0` and `1`.
+//! - `Option` fields are always `Some` when present. Missing fields are
+//!   `None`.
+//! - Unit enum variants are strings. Other variants are dictionaries with
+//!   one field, where the key is the variant name.
+pub mod error;
+
+use core::str;
+use std::io::Read;
+
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, EnumAccess, MapAccess, SeqAccess, Unexpected,
+    VariantAccess, Visitor,
+};
+
+use crate::{
+    parsers::{
+        self,
+        error::{ReadContext, WriteContext},
+        tokenizer::{Token, Tokenizer},
+    },
+    rw::{null_writer::NullWriter, writer::Writer},
+};
+
+use error::Error;
+
+/// It deserializes an instance of type `T` from bencoded bytes.
+///
+/// The whole input must be a single bencoded value. Only line breaks are
+/// allowed after it.
+///
+/// # Errors
+///
+/// Will return an error if the input is not valid bencode, it does not match
+/// the type `T` or it contains more data after the value.
+pub fn from_slice<T: DeserializeOwned>(input: &[u8]) -> Result<T, Error> {
+    let mut deserializer = Deserializer::new(input);
+    let value = T::deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+/// It deserializes an instance of type `T` from a reader of bencoded bytes.
+///
+/// Only the first bencoded value in the input is deserialized. The input
+/// after the value is not read, so it can be used with streams.
+///
+/// # Errors
+///
+/// Will return an error if it can't read from the input, the input is not
+/// valid bencode or it does not match the type `T`.
+pub fn from_reader<R: Read, T: DeserializeOwned>(reader: R) -> Result<T, Error> {
+    let mut deserializer = Deserializer::new(reader);
+    T::deserialize(&mut deserializer)
+}
+
+/// A consumer of the [`Tokenizer`] that passes the tokens to the serde
+/// visitors.
+pub struct Deserializer<R: Read> {
+    tokenizer: Tokenizer<R>,
+
+    /// The next token, when it has been read to know what comes next.
+    peeked_token: Option<Token>,
+
+    /// Nothing is written. The writer is only used for the error context.
+    writer: NullWriter,
+}
+
+impl<R: Read> Deserializer<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            tokenizer: Tokenizer::new(reader),
+            peeked_token: None,
+            writer: NullWriter::new(),
+        }
+    }
+
+    /// It checks the input has ended after the deserialized value. Line
+    /// breaks are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if it can't read from the input or there is more
+    /// data after the value.
+    pub fn end(&mut self) -> Result<(), Error> {
+        if self.peeked_token.is_none() && !self.tokenizer.has_next_token(&self.writer)? {
+            return Ok(());
+        }
+
+        Err(parsers::error::Error::TrailingData(
+            ReadContext {
+                token_start: Some(self.tokenizer.token_start()),
+                ..self.read_context()
+            },
+            self.write_context(),
+        )
+        .into())
+    }
+
+    /// It returns the next token without consuming it.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the input is not valid bencode or it has
+    /// ended.
+    fn peek_token(&mut self) -> Result<&Token, Error> {
+        if self.peeked_token.is_none() {
+            self.peeked_token = Some(self.read_token()?);
+        }
+
+        Ok(self
+            .peeked_token
+            .as_ref()
+            .expect("the token should have been peeked"))
+    }
+
+    /// It returns the next token consuming it.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the input is not valid bencode or it has
+    /// ended.
+    fn next_token(&mut self) -> Result<Token, Error> {
+        match self.peeked_token.take() {
+            Some(token) => Ok(token),
+            None => self.read_token(),
+        }
+    }
+
+    fn read_token(&mut self) -> Result<Token, Error> {
+        match self.tokenizer.next_event(&self.writer)? {
+            Some(event) => Ok(event.token),
+            // The tokenizer only ends without errors at the top level
+            None => Err(parsers::error::Error::UnexpectedEndOfInputExpectingValue(
+                self.read_context(),
+                self.write_context(),
+            )
+            .into()),
+        }
+    }
+
+    fn visit_integer<'de, V: Visitor<'de>>(
+        &self,
+        digits: &[u8],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        // Digits only contain ASCII chars: an optional sign and digits.
+        let digits = str::from_utf8(digits).expect("integer digits should be ASCII");
+
+        if let Ok(integer) = digits.parse::<i64>() {
+            return visitor.visit_i64(integer);
+        }
+
+        if let Ok(integer) = digits.parse::<u64>() {
+            return visitor.visit_u64(integer);
+        }
+
+        if let Ok(integer) = digits.parse::<i128>() {
+            return visitor.visit_i128(integer);
+        }
+
+        if let Ok(integer) = digits.parse::<u128>() {
+            return visitor.visit_u128(integer);
+        }
+
+        Err(parsers::error::Error::IntegerOutOfRange(
+            ReadContext {
+                token_start: Some(self.tokenizer.token_start()),
+                ..self.read_context()
+            },
+            self.write_context(),
+        )
+        .into())
+    }
+
+    /// It consumes the end of the current list or dictionary. It fails if
+    /// the deserialized type did not consume all the items.
+    fn end_list_or_dict(&mut self) -> Result<(), Error> {
+        match self.next_token()? {
+            Token::End => Ok(()),
+            _ => Err(Error::Message(
+                "expected the end of the list or dictionary, found more items".to_string(),
+                self.read_context(),
+            )),
+        }
+    }
+
+    /// It adds the read context to the errors generated by the deserialized
+    /// type.
+    fn fix_context(&self, err: Error) -> Error {
+        match err {
+            Error::Custom(message) => Error::Message(message, self.read_context()),
+            err => err,
+        }
+    }
+
+    fn read_context(&self) -> ReadContext {
+        ReadContext {
+            byte: None,
+            pos: self.tokenizer.input_byte_counter(),
+            latest_bytes: self.tokenizer.captured_bytes(),
+            path: self.tokenizer.path(),
+            next_bytes: self.tokenizer.next_bytes(),
+            token_start: None,
+        }
+    }
+
+    fn write_context(&self) -> WriteContext {
+        WriteContext {
+            byte: None,
+            pos: self.writer.output_byte_counter(),
+            latest_bytes: self.writer.captured_bytes(),
+        }
+    }
+}
+
+impl<'de, R: Read> de::Deserializer<'de> for &mut Deserializer<R> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let result = match self.next_token()? {
+            Token::Integer(digits) => self.visit_integer(&digits, visitor),
+            Token::Bytes(bytes) => match String::from_utf8(bytes) {
+                Ok(string) => visitor.visit_string(string),
+                Err(err) => visitor.visit_byte_buf(err.into_bytes()),
+            },
+            Token::ListStart => visitor
+                .visit_seq(ListAccess { de: self })
+                .and_then(|value| self.end_list_or_dict().map(|()| value)),
+            Token::DictStart => visitor
+                .visit_map(DictAccess { de: self })
+                .and_then(|value| self.end_list_or_dict().map(|()| value)),
+            Token::End => Err(Error::Message(
+                "expected a value, found the end of a list or dictionary".to_string(),
+                self.read_context(),
+            )),
+        };
+
+        result.map_err(|err| self.fix_context(err))
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if !matches!(self.peek_token()?, Token::Integer(_)) {
+            return self.deserialize_any(visitor);
+        }
+
+        let Token::Integer(digits) = self.next_token()? else {
+            unreachable!("the token should be the peeked integer")
+        };
+
+        let result = match digits.as_slice() {
+            b"0" => visitor.visit_bool(false),
+            b"1" => visitor.visit_bool(true),
+            _ => Err(de::Error::invalid_value(
+                Unexpected::Other("integer other than 0 or 1"),
+                &visitor,
+            )),
+        };
+
+        result.map_err(|err| self.fix_context(err))
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_byte_buf(visitor)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if !matches!(self.peek_token()?, Token::Bytes(_)) {
+            return self.deserialize_any(visitor);
+        }
+
+        let Token::Bytes(bytes) = self.next_token()? else {
+            unreachable!("the token should be the peeked string")
+        };
+
+        visitor
+            .visit_byte_buf(bytes)
+            .map_err(|err| self.fix_context(err))
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        // Bencode has no null value. Missing fields are `None`.
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let result = match self.peek_token()? {
+            Token::Bytes(_) => visitor.visit_enum(UnitVariantAccess { de: self }),
+            Token::DictStart => {
+                self.next_token()?;
+                visitor
+                    .visit_enum(DictVariantAccess { de: self })
+                    .and_then(|value| self.end_list_or_dict().map(|()| value))
+            }
+            _ => return self.deserialize_any(visitor),
+        };
+
+        result.map_err(|err| self.fix_context(err))
+    }
+
+    serde::forward_to_deserialize_any! {
+        i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+/// Access to the items of a bencoded list.
+struct ListAccess<'a, R: Read> {
+    de: &'a mut Deserializer<R>,
+}
+
+impl<'de, R: Read> SeqAccess<'de> for ListAccess<'_, R> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        if *self.de.peek_token()? == Token::End {
+            return Ok(None);
+        }
+
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+}
+
+/// Access to the fields of a bencoded dictionary.
+///
+/// The tokenizer checks keys are strings and all keys have a value.
+struct DictAccess<'a, R: Read> {
+    de: &'a mut Deserializer<R>,
+}
+
+impl<'de, R: Read> MapAccess<'de> for DictAccess<'_, R> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        if *self.de.peek_token()? == Token::End {
+            return Ok(None);
+        }
+
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        seed.deserialize(&mut *self.de)
+    }
+}
+
+/// Access to an enum variant represented as a string.
+struct UnitVariantAccess<'a, R: Read> {
+    de: &'a mut Deserializer<R>,
+}
+
+impl<'de, R: Read> EnumAccess<'de> for UnitVariantAccess<'_, R> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), Error> {
+        let variant = seed.deserialize(&mut *self.de)?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de, R: Read> VariantAccess<'de> for UnitVariantAccess<'_, R> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, _seed: T) -> Result<T::Value, Error> {
+        Err(de::Error::invalid_type(
+            Unexpected::UnitVariant,
+            &"newtype variant",
+        ))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, _visitor: V) -> Result<V::Value, Error> {
+        Err(de::Error::invalid_type(
+            Unexpected::UnitVariant,
+            &"tuple variant",
+        ))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Error> {
+        Err(de::Error::invalid_type(
+            Unexpected::UnitVariant,
+            &"struct variant",
+        ))
+    }
+}
+
+/// Access to an enum variant represented as a dictionary with one field.
+struct DictVariantAccess<'a, R: Read> {
+    de: &'a mut Deserializer<R>,
+}
+
+impl<'de, R: Read> EnumAccess<'de> for DictVariantAccess<'_, R> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), Error> {
+        let variant = seed.deserialize(&mut *self.de)?;
+
+        Ok((variant, self))
+    }
+}
+
+impl<'de, R: Read> VariantAccess<'de> for DictVariantAccess<'_, R> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Err(de::Error::invalid_type(
+            Unexpected::Map,
+            &"unit variant as a string",
+        ))
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(&mut *self.de)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_seq(&mut *self.de, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_map(&mut *self.de, visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct File {
+        length: u64,
+        path: Vec<String>,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Info {
+        name: String,
+        #[serde(rename = "piece length")]
+        piece_length: u64,
+        #[serde(with = "serde_bytes")]
+        pieces: Vec<u8>,
+        private: Option<bool>,
+        files: Option<Vec<File>>,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Torrent {
+        announce: String,
+        info: Info,
+    }
+
+    mod it_should_deserialize {
+        use std::collections::BTreeMap;
+
+        use crate::de::{
+            from_slice,
+            tests::{File, Info, Torrent},
+        };
+
+        #[test]
+        fn an_integer() {
+            assert_eq!(from_slice::<i64>(b"i-42e").unwrap(), -42);
+        }
+
+        #[test]
+        fn an_unsigned_integer_greater_than_i64_max() {
+            assert_eq!(
+                from_slice::<u64>(format!("i{}e", u64::MAX).as_bytes()).unwrap(),
+                u64::MAX
+            );
+        }
+
+        #[test]
+        fn a_128_bit_integer_that_does_not_fit_into_64_bits() {
+            assert_eq!(
+                from_slice::<i128>(format!("i{}e", i128::MIN).as_bytes()).unwrap(),
+                i128::MIN
+            );
+            assert_eq!(
+                from_slice::<u128>(format!("i{}e", u128::MAX).as_bytes()).unwrap(),
+                u128::MAX
+            );
+        }
+
+        #[test]
+        fn a_boolean_from_the_integers_zero_and_one() {
+            assert!(!from_slice::<bool>(b"i0e").unwrap());
+            assert!(from_slice::<bool>(b"i1e").unwrap());
+        }
+
+        #[test]
+        fn a_utf8_string() {
+            assert_eq!(from_slice::<String>(b"4:spam").unwrap(), "spam");
+        }
+
+        #[test]
+        fn a_non_utf8_string_into_bytes() {
+            assert_eq!(
+                from_slice::<serde_bytes::ByteBuf>(b"2:\xFF\xFE")
+                    .unwrap()
+                    .into_vec(),
+                b"\xFF\xFE".to_vec()
+            );
+        }
+
+        #[test]
+        fn a_list() {
+            assert_eq!(
+                from_slice::<Vec<String>>(b"l5:alice3:bobe").unwrap(),
+                vec!["alice".to_string(), "bob".to_string()]
+            );
+        }
+
+        #[test]
+        fn a_tuple() {
+            assert_eq!(
+                from_slice::<(String, i64)>(b"l5:alicei42ee").unwrap(),
+                ("alice".to_string(), 42)
+            );
+        }
+
+        #[test]
+        fn a_map() {
+            let mut expected = BTreeMap::new();
+            expected.insert("bar".to_string(), 1);
+            expected.insert("foo".to_string(), 2);
+
+            assert_eq!(
+                from_slice::<BTreeMap<String, i64>>(b"d3:bari1e3:fooi2ee").unwrap(),
+                expected
+            );
+        }
+
+        #[test]
+        fn a_struct_with_nested_structs_bytes_and_optional_fields() {
+            let torrent = from_slice::<Torrent>(
+                b"d8:announce9:udp://foo4:infod5:filesld6:lengthi10e4:pathl1:a1:beee4:name3:foo12:piece lengthi16384e6:pieces2:\xFF\xFEee",
+            )
+            .unwrap();
+
+            assert_eq!(
+                torrent,
+                Torrent {
+                    announce: "udp://foo".to_string(),
+                    info: Info {
+                        name: "foo".to_string(),
+                        piece_length: 16384,
+                        pieces: b"\xFF\xFE".to_vec(),
+                        private: None,
+                        files: Some(vec![File {
+                            length: 10,
+                            path: vec!["a".to_string(), "b".to_string()]
+                        }]),
+                    }
+                }
+            );
+        }
+
+        #[test]
+        fn ignoring_unknown_fields() {
+            #[derive(Debug, PartialEq, serde::Deserialize)]
+            struct Peer {
+                port: u16,
+            }
+
+            assert_eq!(
+                from_slice::<Peer>(b"d5:extrald1:ai1eee4:porti6881ee").unwrap(),
+                Peer { port: 6881 }
+            );
+        }
+
+        #[test]
+        fn from_a_reader_only_the_first_value_of_the_input() {
+            assert_eq!(crate::de::from_reader::<_, i64>(&b"i1ei2e"[..]).unwrap(), 1);
+        }
+
+        #[test]
+        fn ignoring_line_breaks_after_the_value() {
+            assert_eq!(from_slice::<i64>(b"i1e\r\n").unwrap(), 1);
+        }
+
+        #[test]
+        fn ignoring_line_breaks_between_values() {
+            assert_eq!(from_slice::<Vec<i64>>(b"\nli1e\ni2ee").unwrap(), vec![1, 2]);
+        }
+    }
+
+    mod it_should_deserialize_enums {
+        use serde::Deserialize;
+
+        use crate::de::from_slice;
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        #[serde(rename_all = "lowercase")]
+        enum Message {
+            Ping,
+            Error(i64),
+            Point(i64, i64),
+            Announce { port: u16 },
+        }
+
+        #[test]
+        fn with_unit_variants_as_strings() {
+            assert_eq!(from_slice::<Message>(b"4:ping").unwrap(), Message::Ping);
+        }
+
+        #[test]
+        fn with_newtype_variants_as_dictionaries() {
+            assert_eq!(
+                from_slice::<Message>(b"d5:errori201ee").unwrap(),
+                Message::Error(201)
+            );
+        }
+
+        #[test]
+        fn with_tuple_variants_as_dictionaries() {
+            assert_eq!(
+                from_slice::<Message>(b"d5:pointli1ei2eee").unwrap(),
+                Message::Point(1, 2)
+            );
+        }
+
+        #[test]
+        fn with_struct_variants_as_dictionaries() {
+            assert_eq!(
+                from_slice::<Message>(b"d8:announced4:porti6881eee").unwrap(),
+                Message::Announce { port: 6881 }
+            );
+        }
+
+        #[test]
+        fn with_internally_tagged_representation() {
+            #[derive(Debug, PartialEq, Deserialize)]
+            #[serde(tag = "y")]
+            enum Krpc {
+                #[serde(rename = "q")]
+                Query { q: String },
+                #[serde(rename = "r")]
+                Response,
+            }
+
+            assert_eq!(
+                from_slice::<Krpc>(b"d1:q4:ping1:y1:qe").unwrap(),
+                Krpc::Query {
+                    q: "ping".to_string()
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn it_should_deserialize_flattened_fields() {
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        struct Response {
+            interval: i64,
+            #[serde(flatten)]
+            extra: BTreeMap<String, i64>,
+        }
+
+        let response =
+            crate::de::from_slice::<Response>(b"d8:completei5e8:intervali1800ee").unwrap();
+
+        assert_eq!(response.interval, 1800);
+        assert_eq!(response.extra.get("complete"), Some(&5));
+    }
+
+    mod it_should_fail {
+        use crate::{
+            de::{error::Error, from_slice, tests::Torrent},
+            parsers::{self, BencodeType},
+        };
+
+        #[test]
+        fn when_the_input_is_not_valid_bencode() {
+            assert!(matches!(
+                from_slice::<i64>(b"i42"),
+                Err(Error::Parser(
+                    parsers::error::Error::UnexpectedEndOfInputParsingInteger { .. }
+                ))
+            ));
+        }
+
+        #[test]
+        fn when_there_is_trailing_data_after_the_value() {
+            let result = from_slice::<i64>(b"i1e\ni2e");
+
+            let Err(Error::Parser(parsers::error::Error::TrailingData(read_context, _))) = result
+            else {
+                panic!("expected a trailing data error, got {result:?}");
+            };
+
+            assert_eq!(read_context.offset(), 4);
+        }
+
+        #[test]
+        fn when_the_input_is_empty() {
+            assert!(matches!(
+                from_slice::<i64>(b""),
+                Err(Error::Parser(
+                    parsers::error::Error::UnexpectedEndOfInputExpectingValue { .. }
+                ))
+            ));
+        }
+
+        #[test]
+        fn when_a_list_is_not_finished() {
+            assert!(matches!(
+                from_slice::<Vec<i64>>(b"li1e"),
+                Err(Error::Parser(
+                    parsers::error::Error::UnexpectedEndOfInputExpectingNextListItem { .. }
+                ))
+            ));
+        }
+
+        #[test]
+        fn when_a_dictionary_key_is_not_a_string() {
+            assert!(matches!(
+                from_slice::<Torrent>(b"di42ei43ee"),
+                Err(Error::Parser(
+                    parsers::error::Error::ExpectedStringForDictKeyGot(BencodeType::Integer, _, _)
+                ))
+            ));
+        }
+
+        #[test]
+        fn when_a_dictionary_field_does_not_have_a_value() {
+            assert!(matches!(
+                from_slice::<Torrent>(b"d8:announcee"),
+                Err(Error::Parser(
+                    parsers::error::Error::PrematureEndOfDict { .. }
+                ))
+            ));
+        }
+
+        #[test]
+        fn when_the_integer_is_out_of_range() {
+            assert!(matches!(
+                from_slice::<u128>(format!("i{}0e", u128::MAX).as_bytes()),
+                Err(Error::Parser(
+                    parsers::error::Error::IntegerOutOfRange { .. }
+                ))
+            ));
+        }
+
+        #[test]
+        fn when_the_value_does_not_match_the_type_including_the_read_context() {
+            let result = from_slice::<Vec<i64>>(b"li1e4:spame");
+
+            let Err(Error::Message(message, read_context)) = result else {
+                panic!("expected an error with context, got: {result:?}");
+            };
+
+            assert!(message.contains("invalid type"));
+            assert_eq!(read_context.pos, 10);
+        }
+
+        #[test]
+        fn when_a_required_field_is_missing() {
+            let result = from_slice::<Torrent>(b"d8:announce3:fooe");
+
+            assert!(
+                matches!(result, Err(Error::Message(ref message, _)) if message.contains("missing field `info`"))
+            );
+        }
+
+        #[test]
+        fn when_the_tuple_has_more_items_than_expected() {
+            assert!(matches!(
+                from_slice::<(i64,)>(b"li1ei2ee"),
+                Err(Error::Message { .. })
+            ));
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//! Encoder errors.
+use std::io;
+
+use thiserror::Error;
+
+use crate::{
+    parsers::error::{ReadContext, WriteContext},
+    rw,
+};
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("R/W error: {0}")]
+    Rw(#[from] rw::error::Error),
+
+    #[error("Unexpected trailing data after the first JSON value, only one value can be converted; {0}; {1}")]
+    TrailingData(ReadContext, WriteContext),
+
+    #[error("Unrecognized first byte for new JSON value; {0}; {1}")]
+    UnrecognizedFirstJsonValueByte(ReadContext, WriteContext),
+
+    #[error("JSON value has no bencode equivalent, only integers, strings, arrays and objects are allowed; {0}; {1}")]
+    JsonValueWithoutBencodeEquivalent(ReadContext, WriteContext),
+
+    // Numbers
+    #[error("Unexpected byte parsing JSON number; {0}; {1}")]
+    UnexpectedByteParsingNumber(ReadContext, WriteContext),
+
+    #[error("Unexpected end of input parsing JSON number; {0}; {1}")]
+    UnexpectedEndOfInputParsingNumber(ReadContext, WriteContext),
+
+    #[error("Only integer JSON numbers can be converted to bencode, for example `1.5` or `1e3` are not allowed; {0}; {1}")]
+    NonIntegerNumbersNotAllowed(ReadContext, WriteContext),
+
+    #[error("Negative zero `-0` can't be converted to bencode, `i-0e` is not a valid bencoded integer; {0}; {1}")]
+    NegativeZeroNotAllowed(ReadContext, WriteContext),
+
+    // Strings
+    #[error("Unexpected end of input parsing JSON string; {0}; {1}")]
+    UnexpectedEndOfInputParsingString(ReadContext, WriteContext),
+
+    #[error("Invalid escape sequence parsing JSON string; {0}; {1}")]
+    InvalidEscapeSequenceParsingString(ReadContext, WriteContext),
+
+    #[error("Unicode escape sequence with a surrogate without its pair parsing JSON string, it can't be converted to UTF-8; {0}; {1}")]
+    LoneSurrogateParsingString(ReadContext, WriteContext),
+
+    #[error("Unescaped control character parsing JSON string, control characters must be escaped, for example `\\n` or `\\u0000`; {0}; {1}")]
+    UnescapedControlCharacterParsingString(ReadContext, WriteContext),
+
+    // Arrays
+    #[error("Unexpected end of input parsing JSON array; {0}; {1}")]
+    UnexpectedEndOfInputParsingArray(ReadContext, WriteContext),
+
+    #[error("Expected `,` or `]` parsing JSON array; {0}; {1}")]
+    ExpectedCommaOrEndOfArray(ReadContext, WriteContext),
+
+    // Objects
+    #[error("Unexpected end of input parsing JSON object; {0}; {1}")]
+    UnexpectedEndOfInputParsingObject(ReadContext, WriteContext),
+
+    #[error("Expected string for JSON object field key; {0}; {1}")]
+    ExpectedStringForObjectKey(ReadContext, WriteContext),
+
+    #[error("Expected `:` after JSON object field key; {0}; {1}")]
+    ExpectedColonAfterObjectKey(ReadContext, WriteContext),
+
+    #[error("Expected `,` or `}}` parsing JSON object; {0}; {1}")]
+    ExpectedCommaOrEndOfObject(ReadContext, WriteContext),
+}
@@ -0,0 +1,1191 @@
+//! JSON to bencode converter.
+//!
+//! It reads the JSON produced by the [`BencodeParser`](crate::parsers::BencodeParser)
+//! and writes the equivalent bencoded bytes. Byte strings the parser printed
+//! as `<hex>…</hex>` are decoded back into the original raw bytes, so
+//! converting bencode to JSON and back produces exactly the same bytes.
+//!
+//! Only the default [`StringEncoding::HexWrapped`](crate::parsers::string::StringEncoding::HexWrapped)
+//! encoding is decoded. The parser also writes text that looks like that
+//! format in hexadecimal, so a literal `<hex>ab</hex>` string is written as
+//! `<hex>3c6865783e61623c2f6865783e</hex>` and it's decoded back into the
+//! same text, not into the `0xAB` byte.
+//!
+//! Only JSON values with a bencode equivalent are converted. The negative
+//! zero `-0` is rejected, because `i-0e` is not a valid bencoded integer, and
+//! so is more than one top-level value.
+use core::str;
+use std::io::{self, Read, Write as IoWrite};
+
+use crate::{
+    parsers::{
+        error::{ReadContext, WriteContext},
+        stack::{Stack, State},
+        string::{is_hex_wrapped, HEX_WRAPPED_CLOSING, HEX_WRAPPED_OPENING, STRING_CHUNK_SIZE},
+        BENCODE_BEGIN_DICT, BENCODE_BEGIN_INTEGER, BENCODE_BEGIN_LIST, BENCODE_END_INTEGER,
+        BENCODE_END_LIST_OR_DICT, BENCODE_END_STRING_LENGTH,
+    },
+    rw::{byte_reader::ByteReader, byte_writer::ByteWriter, writer::Writer},
+};
+
+use super::error::Error;
+
+pub struct JsonToBencode<R: Read> {
+    byte_reader: ByteReader<R>,
+    stack: Stack,
+}
+
+impl<R: Read> JsonToBencode<R> {
+    const JSON_ARRAY_BEGIN: u8 = b'[';
+    const JSON_ARRAY_ITEMS_SEPARATOR: u8 = b',';
+    const JSON_ARRAY_END: u8 = b']';
+
+    const JSON_OBJ_BEGIN: u8 = b'{';
+    const JSON_OBJ_FIELDS_SEPARATOR: u8 = b',';
+    const JSON_OBJ_FIELD_KEY_VALUE_SEPARATOR: u8 = b':';
+    const JSON_OBJ_END: u8 = b'}';
+
+    const JSON_STRING_DELIMITER: u8 = b'"';
+    const JSON_STRING_ESCAPE: u8 = b'\\';
+
+    pub fn new(reader: R) -> Self {
+        JsonToBencode {
+            byte_reader: ByteReader::new(reader),
+            stack: Stack::default(),
+        }
+    }
+
+    /// It parses a JSON value read from input and writes the corresponding
+    /// bencoded bytes to the output.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if:
+    ///
+    /// - It can't read from the input or write to the output.
+    /// - The input is not valid JSON.
+    /// - The input contains JSON values without a bencode equivalent, like
+    ///   floats, booleans, `null` or `-0`.
+    /// - The input contains more than one top-level value.
+    pub fn write_bytes<W: IoWrite>(&mut self, writer: W) -> Result<(), Error> {
+        let mut writer = ByteWriter::new(writer);
+        self.convert(&mut writer)
+    }
+
+    /// It parses a JSON value read from input and writes the corresponding
+    /// bencoded value to the output.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if:
+    ///
+    /// - It can't read from the input or write to the output.
+    /// - The input is not valid JSON or it can't be represented in bencode.
+    fn convert<W: Writer>(&mut self, writer: &mut W) -> Result<(), Error> {
+        let mut value_begun = false;
+
+        while let Some(peeked_byte) = self.peek_non_whitespace_byte()? {
+            match self.stack.peek() {
+                State::Initial => {
+                    if value_begun {
+                        return Err(Error::TrailingData(
+                            self.read_context(Some(peeked_byte)),
+                            Self::write_context(Some(peeked_byte), writer),
+                        ));
+                    }
+                    value_begun = true;
+                    self.begin_json_value(peeked_byte, writer)?;
+                }
+                State::ExpectingFirstListItemOrEnd => {
+                    if peeked_byte == Self::JSON_ARRAY_END {
+                        self.end_list_or_dict(writer)?;
+                    } else {
+                        self.stack.swap_top(State::ExpectingNextListItem);
+                        self.stack.begin_list_item();
+                        self.begin_json_value(peeked_byte, writer)?;
+                    }
+                }
+                State::ExpectingNextListItem => match peeked_byte {
+                    Self::JSON_ARRAY_ITEMS_SEPARATOR => {
+                        self.read_byte(writer)?;
+                        self.stack.begin_list_item();
+                        let next_byte = self.expect_non_whitespace_byte(writer)?;
+                        self.begin_json_value(next_byte, writer)?;
+                    }
+                    Self::JSON_ARRAY_END => {
+                        self.end_list_or_dict(writer)?;
+                    }
+                    _ => {
+                        return Err(Error::ExpectedCommaOrEndOfArray(
+                            self.read_context(Some(peeked_byte)),
+                            Self::write_context(Some(peeked_byte), writer),
+                        ));
+                    }
+                },
+                State::ExpectingFirstDictFieldOrEnd => match peeked_byte {
+                    Self::JSON_OBJ_END => {
+                        self.end_list_or_dict(writer)?;
+                    }
+                    Self::JSON_STRING_DELIMITER => {
+                        self.convert_key(writer)?;
+                    }
+                    _ => {
+                        return Err(Error::ExpectedStringForObjectKey(
+                            self.read_context(Some(peeked_byte)),
+                            Self::write_context(Some(peeked_byte), writer),
+                        ));
+                    }
+                },
+                State::ExpectingDictFieldValue => {
+                    if peeked_byte != Self::JSON_OBJ_FIELD_KEY_VALUE_SEPARATOR {
+                        return Err(Error::ExpectedColonAfterObjectKey(
+                            self.read_context(Some(peeked_byte)),
+                            Self::write_context(Some(peeked_byte), writer),
+                        ));
+                    }
+
+                    self.read_byte(writer)?;
+                    let next_byte = self.expect_non_whitespace_byte(writer)?;
+                    self.stack.swap_top(State::ExpectingDictFieldKeyOrEnd);
+                    self.begin_json_value(next_byte, writer)?;
+                }
+                State::ExpectingDictFieldKeyOrEnd => match peeked_byte {
+                    Self::JSON_OBJ_FIELDS_SEPARATOR => {
+                        self.read_byte(writer)?;
+                        let next_byte = self.expect_non_whitespace_byte(writer)?;
+                        if next_byte != Self::JSON_STRING_DELIMITER {
+                            return Err(Error::ExpectedStringForObjectKey(
+                                // The path to the object, not to the previous key
+                                ReadContext {
+                                    path: self.stack.next_path(),
+                                    ..self.read_context(Some(next_byte))
+                                },
+                                Self::write_context(Some(next_byte), writer),
+                            ));
+                        }
+                        self.convert_key(writer)?;
+                    }
+                    Self::JSON_OBJ_END => {
+                        self.end_list_or_dict(writer)?;
+                    }
+                    _ => {
+                        return Err(Error::ExpectedCommaOrEndOfObject(
+                            self.read_context(Some(peeked_byte)),
+                            Self::write_context(Some(peeked_byte), writer),
+                        ));
+                    }
+                },
+            }
+        }
+
+        match self.stack.peek() {
+            State::Initial => Ok(()),
+            _ => Err(self.unexpected_end_of_input(writer)),
+        }
+    }
+
+    /// It converts the JSON value starting with the peeked byte.
+    ///
+    /// Integers and strings are converted completely. For arrays and objects
+    /// it only writes the beginning of the bencoded list or dictionary and
+    /// pushes the new state into the stack.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the value is not valid or it has no bencode
+    /// equivalent.
+    fn begin_json_value<W: Writer>(
+        &mut self,
+        peeked_byte: u8,
+        writer: &mut W,
+    ) -> Result<(), Error> {
+        match peeked_byte {
+            Self::JSON_ARRAY_BEGIN => {
+                self.read_byte(writer)?;
+                writer.write_byte(BENCODE_BEGIN_LIST)?;
+                self.stack
+                    .push(State::ExpectingFirstListItemOrEnd)
+                    .expect("the stack has no maximum depth");
+            }
+            Self::JSON_OBJ_BEGIN => {
+                self.read_byte(writer)?;
+                writer.write_byte(BENCODE_BEGIN_DICT)?;
+                self.stack
+                    .push(State::ExpectingFirstDictFieldOrEnd)
+                    .expect("the stack has no maximum depth");
+            }
+            Self::JSON_STRING_DELIMITER => {
+                self.convert_string(writer)?;
+            }
+            b'-' | b'0'..=b'9' => {
+                self.convert_integer(writer)?;
+            }
+            b't' | b'f' | b'n' => {
+                // true, false and null
+                return Err(Error::JsonValueWithoutBencodeEquivalent(
+                    self.read_context(Some(peeked_byte)),
+                    Self::write_context(Some(peeked_byte), writer),
+                ));
+            }
+            _ => {
+                return Err(Error::UnrecognizedFirstJsonValueByte(
+                    self.read_context(Some(peeked_byte)),
+                    Self::write_context(Some(peeked_byte), writer),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// It consumes the end of an array or object and writes the bencoded end
+    /// of list or dictionary.
+    fn end_list_or_dict<W: Writer>(&mut self, writer: &mut W) -> Result<(), Error> {
+        self.read_byte(writer)?;
+        writer.write_byte(BENCODE_END_LIST_OR_DICT)?;
+        self.stack.pop();
+        Ok(())
+    }
+
+    /// It converts a JSON integer into a bencoded integer.
+    ///
+    /// The JSON integer grammar (no leading zeros, optional minus sign) is
+    /// the same as the bencoded one, so digits are copied as they are. The
+    /// only exception is the negative zero, which is not allowed in bencode.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the number is not a valid JSON integer or it's
+    /// `-0`.
+    fn convert_integer<W: Writer>(&mut self, writer: &mut W) -> Result<(), Error> {
+        let mut digits = vec![];
+
+        let mut byte = self.read_number_byte(writer)?;
+
+        if byte == b'-' {
+            digits.push(byte);
+            byte = self.read_number_byte(writer)?;
+        }
+
+        if !byte.is_ascii_digit() {
+            return Err(Error::UnexpectedByteParsingNumber(
+                self.read_context(Some(byte)),
+                Self::write_context(Some(byte), writer),
+            ));
+        }
+
+        digits.push(byte);
+
+        let first_digit_is_zero = byte == b'0';
+
+        while let Some(peeked_byte) = self.peek_byte()? {
+            if peeked_byte.is_ascii_digit() {
+                if first_digit_is_zero {
+                    return Err(Error::UnexpectedByteParsingNumber(
+                        self.read_context(Some(peeked_byte)),
+                        Self::write_context(Some(peeked_byte), writer),
+                    ));
+                }
+                digits.push(self.read_byte(writer)?);
+            } else if matches!(peeked_byte, b'.' | b'e' | b'E') {
+                return Err(Error::NonIntegerNumbersNotAllowed(
+                    self.read_context(Some(peeked_byte)),
+                    Self::write_context(Some(peeked_byte), writer),
+                ));
+            } else {
+                break;
+            }
+        }
+
+        if digits == b"-0" {
+            return Err(Error::NegativeZeroNotAllowed(
+                self.read_context(Some(b'0')),
+                Self::write_context(Some(b'0'), writer),
+            ));
+        }
+
+        writer.write_byte(BENCODE_BEGIN_INTEGER)?;
+        for digit in digits {
+            writer.write_byte(digit)?;
+        }
+        writer.write_byte(BENCODE_END_INTEGER)?;
+
+        Ok(())
+    }
+
+    /// It converts a JSON object key into a bencoded string, and keeps it
+    /// for the path of the field value.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the key is not a valid JSON string.
+    fn convert_key<W: Writer>(&mut self, writer: &mut W) -> Result<(), Error> {
+        let key = self.convert_string(writer)?;
+        self.stack.set_last_key(key);
+        self.stack.swap_top(State::ExpectingDictFieldValue);
+        Ok(())
+    }
+
+    /// It converts a JSON string (value or object key) into a bencoded
+    /// string. It returns the bytes of the bencoded string.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the string is not a valid JSON string.
+    fn convert_string<W: Writer>(&mut self, writer: &mut W) -> Result<Vec<u8>, Error> {
+        let bytes = Self::decode_hex(self.parse_string(writer)?);
+
+        writer.write_str(&bytes.len().to_string())?;
+        writer.write_byte(BENCODE_END_STRING_LENGTH)?;
+        writer.write_bytes(&bytes)?;
+
+        Ok(bytes)
+    }
+
+    /// It parses a JSON string returning its unescaped UTF-8 bytes.
+    fn parse_string<W: Writer>(&mut self, writer: &W) -> Result<Vec<u8>, Error> {
+        // Discard the opening quote
+        self.read_byte(writer)?;
+
+        let mut bytes = vec![];
+
+        loop {
+            let byte = self.read_string_byte(writer)?;
+
+            match byte {
+                Self::JSON_STRING_DELIMITER => return Ok(bytes),
+                Self::JSON_STRING_ESCAPE => {
+                    let escaped_byte = self.read_string_byte(writer)?;
+
+                    match escaped_byte {
+                        b'"' | b'\\' | b'/' => bytes.push(escaped_byte),
+                        b'b' => bytes.push(0x08),
+                        b'f' => bytes.push(0x0C),
+                        b'n' => bytes.push(b'\n'),
+                        b'r' => bytes.push(b'\r'),
+                        b't' => bytes.push(b'\t'),
+                        b'u' => {
+                            let c = self.parse_unicode_escape(writer)?;
+                            let mut buf = [0; 4];
+                            bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                        }
+                        _ => {
+                            return Err(Error::InvalidEscapeSequenceParsingString(
+                                self.read_context(Some(escaped_byte)),
+                                Self::write_context(Some(escaped_byte), writer),
+                            ));
+                        }
+                    }
+                }
+                // RFC 8259 requires escaping U+0000 to U+001F
+                0x00..=0x1F => {
+                    return Err(Error::UnescapedControlCharacterParsingString(
+                        self.read_context(Some(byte)),
+                        Self::write_context(Some(byte), writer),
+                    ));
+                }
+                _ => bytes.push(byte),
+            }
+        }
+    }
+
+    /// It parses the `XXXX` part of a `\uXXXX` escape sequence, including
+    /// the second escape sequence when the first one is a high surrogate.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the escape sequence is not valid or it's a
+    /// surrogate without its pair, which can't be represented in UTF-8.
+    fn parse_unicode_escape<W: Writer>(&mut self, writer: &W) -> Result<char, Error> {
+        let high = self.parse_hex_code_unit(writer)?;
+
+        if (0xDC00..=0xDFFF).contains(&high) {
+            return Err(self.lone_surrogate(writer));
+        }
+
+        let code_point = if (0xD800..=0xDBFF).contains(&high) {
+            // The pair must follow immediately: `\uD83D\uDE00`
+            for expected_byte in [Self::JSON_STRING_ESCAPE, b'u'] {
+                if self.peek_byte()? != Some(expected_byte) {
+                    return Err(self.lone_surrogate(writer));
+                }
+                self.read_byte(writer)?;
+            }
+
+            let low = self.parse_hex_code_unit(writer)?;
+
+            if !(0xDC00..=0xDFFF).contains(&low) {
+                return Err(self.lone_surrogate(writer));
+            }
+
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+
+        Ok(char::from_u32(code_point).expect("a code point that is not a surrogate is a char"))
+    }
+
+    /// It builds the error for a `\uXXXX` escape sequence with a surrogate
+    /// that is not part of a pair.
+    fn lone_surrogate<W: Writer>(&self, writer: &W) -> Error {
+        Error::LoneSurrogateParsingString(
+            self.read_context(None),
+            Self::write_context(None, writer),
+        )
+    }
+
+    /// It parses four hexadecimal digits.
+    fn parse_hex_code_unit<W: Writer>(&mut self, writer: &W) -> Result<u32, Error> {
+        let mut code_unit = 0;
+
+        for _i in 1..=4 {
+            let byte = self.read_string_byte(writer)?;
+
+            match (byte as char).to_digit(16) {
+                Some(digit) => code_unit = code_unit * 16 + digit,
+                None => {
+                    return Err(Error::InvalidEscapeSequenceParsingString(
+                        self.read_context(Some(byte)),
+                        Self::write_context(Some(byte), writer),
+                    ));
+                }
+            }
+        }
+
+        Ok(code_unit)
+    }
+
+    /// It decodes strings in the format `<hex>fffe</hex>` back into the raw
+    /// bytes.
+    ///
+    /// The parser only uses that format for strings that are not valid UTF-8
+    /// and for text that looks like that format. If the decoded bytes are
+    /// valid UTF-8 and they do not look like that format, the original
+    /// bencoded string must have been the literal text, so it's kept as it
+    /// is.
+    ///
+    /// Strings whose first invalid UTF-8 byte is after the first chunk are
+    /// written by the parser as the valid text followed by the rest in the
+    /// same format: `<valid text><hex>fffe</hex>`. So are valid UTF-8 strings
+    /// longer than a chunk that end like that format. They are also decoded.
+    fn decode_hex(bytes: Vec<u8>) -> Vec<u8> {
+        let Some(rest) = bytes.strip_suffix(HEX_WRAPPED_CLOSING) else {
+            return bytes;
+        };
+
+        let Some(prefix_len) = rest
+            .windows(HEX_WRAPPED_OPENING.len())
+            .rposition(|window| window == HEX_WRAPPED_OPENING)
+        else {
+            return bytes;
+        };
+
+        let (text, hex) = (
+            &rest[..prefix_len],
+            &rest[prefix_len + HEX_WRAPPED_OPENING.len()..],
+        );
+
+        if !hex
+            .iter()
+            .all(|byte| matches!(byte, b'0'..=b'9' | b'a'..=b'f'))
+        {
+            return bytes;
+        }
+
+        let Ok(decoded) = hex::decode(hex) else {
+            return bytes;
+        };
+
+        // Text that looks like the hex format, or a text longer than a chunk
+        // that ends like it, written in hex by the parser
+        if is_hex_wrapped(&decoded)
+            && (text.is_empty() || text.len() + decoded.len() >= STRING_CHUNK_SIZE)
+        {
+            let mut original = text.to_vec();
+            original.extend_from_slice(&decoded);
+            return original;
+        }
+
+        if !text.is_empty() && text.len() < STRING_CHUNK_SIZE {
+            return bytes;
+        }
+
+        let mut original = text.to_vec();
+        original.extend_from_slice(&decoded);
+
+        match str::from_utf8(&original) {
+            // The text must end where the first invalid byte is
+            Err(err) if text.is_empty() || err.valid_up_to() == text.len() => original,
+            _ => bytes,
+        }
+    }
+
+    /// It reads the next byte from the input.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if it can't read from the input or the input
+    /// has ended.
+    fn read_byte<W: Writer>(&mut self, writer: &W) -> Result<u8, Error> {
+        match self.byte_reader.read_byte() {
+            Ok(byte) => Ok(byte),
+            Err(err) => {
+                if err.kind() == io::ErrorKind::UnexpectedEof {
+                    return Err(self.unexpected_end_of_input(writer));
+                }
+                Err(err.into())
+            }
+        }
+    }
+
+    /// It reads the next byte of a JSON number.
+    fn read_number_byte<W: Writer>(&mut self, writer: &W) -> Result<u8, Error> {
+        match self.byte_reader.read_byte() {
+            Ok(byte) => Ok(byte),
+            Err(err) => {
+                if err.kind() == io::ErrorKind::UnexpectedEof {
+                    return Err(Error::UnexpectedEndOfInputParsingNumber(
+                        self.read_context(None),
+                        Self::write_context(None, writer),
+                    ));
+                }
+                Err(err.into())
+            }
+        }
+    }
+
+    /// It reads the next byte of a JSON string.
+    fn read_string_byte<W: Writer>(&mut self, writer: &W) -> Result<u8, Error> {
+        match self.byte_reader.read_byte() {
+            Ok(byte) => Ok(byte),
+            Err(err) => {
+                if err.kind() == io::ErrorKind::UnexpectedEof {
+                    return Err(Error::UnexpectedEndOfInputParsingString(
+                        self.read_context(None),
+                        Self::write_context(None, writer),
+                    ));
+                }
+                Err(err.into())
+            }
+        }
+    }
+
+    /// It peeks the next byte from the input without consuming it. It returns
+    /// `None` if the input has ended.
+    fn peek_byte(&mut self) -> Result<Option<u8>, Error> {
+        match self.byte_reader.peek_byte() {
+            Ok(byte) => Ok(Some(byte)),
+            Err(err) => {
+                if err.kind() == io::ErrorKind::UnexpectedEof {
+                    return Ok(None);
+                }
+                Err(err.into())
+            }
+        }
+    }
+
+    /// It peeks the next byte that is not JSON whitespace, consuming the
+    /// whitespace before it. It returns `None` if the input has ended.
+    fn peek_non_whitespace_byte(&mut self) -> Result<Option<u8>, Error> {
+        while let Some(byte) = self.peek_byte()? {
+            if !matches!(byte, b' ' | b'\t' | b'\n' | b'\r') {
+                return Ok(Some(byte));
+            }
+            let _byte = self.byte_reader.read_byte()?;
+        }
+
+        Ok(None)
+    }
+
+    /// Like `peek_non_whitespace_byte` but the input can't end because we
+    /// are in the middle of an array or object.
+    fn expect_non_whitespace_byte<W: Writer>(&mut self, writer: &W) -> Result<u8, Error> {
+        match self.peek_non_whitespace_byte()? {
+            Some(byte) => Ok(byte),
+            None => Err(self.unexpected_end_of_input(writer)),
+        }
+    }
+
+    /// It builds the error for an input that ends before the current array
+    /// or object is finished.
+    fn unexpected_end_of_input<W: Writer>(&self, writer: &W) -> Error {
+        match self.stack.peek() {
+            State::Initial | State::ExpectingFirstListItemOrEnd | State::ExpectingNextListItem => {
+                Error::UnexpectedEndOfInputParsingArray(
+                    self.read_context(None),
+                    Self::write_context(None, writer),
+                )
+            }
+            State::ExpectingFirstDictFieldOrEnd
+            | State::ExpectingDictFieldValue
+            | State::ExpectingDictFieldKeyOrEnd => Error::UnexpectedEndOfInputParsingObject(
+                self.read_context(None),
+                Self::write_context(None, writer),
+            ),
+        }
+    }
+
+    fn read_context(&self, byte: Option<u8>) -> ReadContext {
+        ReadContext {
+            byte,
+            pos: self.byte_reader.input_byte_counter(),
+            latest_bytes: self.byte_reader.captured_bytes(),
+            path: self.stack.path(),
+            next_bytes: self.byte_reader.next_bytes(),
+            token_start: None,
+        }
+    }
+
+    fn write_context<W: Writer>(byte: Option<u8>, writer: &W) -> WriteContext {
+        WriteContext {
+            byte,
+            pos: writer.output_byte_counter(),
+            latest_bytes: writer.captured_bytes(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::try_json_to_bencode;
+
+    fn json_to_bencode_unchecked(input_buffer: &[u8]) -> Vec<u8> {
+        try_json_to_bencode(input_buffer).expect("JSON to bencode conversion failed")
+    }
+
+    mod it_should_allow_writing {
+        use crate::encoders::json::JsonToBencode;
+
+        #[test]
+        fn to_any_type_implementing_io_write_trait() {
+            let mut output = Vec::new();
+
+            let mut converter = JsonToBencode::new(&b"0"[..]);
+
+            converter
+                .write_bytes(&mut output)
+                .expect("JSON to bencode conversion failed");
+
+            assert_eq!(output, b"i0e".to_vec());
+        }
+    }
+
+    #[test]
+    fn it_should_allow_an_empty_input() {
+        assert_eq!(json_to_bencode_unchecked(b""), b"".to_vec());
+    }
+
+    #[test]
+    fn it_should_ignore_whitespace_between_tokens() {
+        assert_eq!(
+            json_to_bencode_unchecked(b" { \"foo\" :\n[ 1 ,\t2 ]\r\n} "),
+            b"d3:fooli1ei2eee".to_vec()
+        );
+    }
+
+    mod integers {
+        use crate::encoders::json::tests::json_to_bencode_unchecked;
+
+        #[test]
+        fn zero() {
+            assert_eq!(json_to_bencode_unchecked(b"0"), b"i0e".to_vec());
+        }
+
+        #[test]
+        fn positive_integer() {
+            assert_eq!(json_to_bencode_unchecked(b"42"), b"i42e".to_vec());
+        }
+
+        #[test]
+        fn negative_integer() {
+            assert_eq!(json_to_bencode_unchecked(b"-42"), b"i-42e".to_vec());
+        }
+
+        #[test]
+        fn integer_greater_than_i64_max() {
+            let big_integer = i64::MAX.to_string() + "1";
+
+            assert_eq!(
+                json_to_bencode_unchecked(big_integer.as_bytes()),
+                format!("i{big_integer}e").into_bytes()
+            );
+        }
+    }
+
+    mod strings {
+        use crate::encoders::json::tests::json_to_bencode_unchecked;
+
+        #[test]
+        fn empty_string() {
+            assert_eq!(json_to_bencode_unchecked(br#""""#), b"0:".to_vec());
+        }
+
+        #[test]
+        fn utf8() {
+            assert_eq!(json_to_bencode_unchecked(br#""spam""#), b"4:spam".to_vec());
+        }
+
+        #[test]
+        fn unicode_characters() {
+            assert_eq!(
+                json_to_bencode_unchecked(r#""ñandú""#.as_bytes()),
+                "7:ñandú".as_bytes().to_vec()
+            );
+        }
+
+        #[test]
+        fn non_utf8_bytes_in_hex_format() {
+            assert_eq!(
+                json_to_bencode_unchecked(br#""<hex>fffefdfc</hex>""#),
+                b"4:\xFF\xFE\xFD\xFC".to_vec()
+            );
+        }
+
+        #[test]
+        fn text_in_hex_format_that_is_valid_utf8_once_decoded() {
+            // The parser would have printed "spam" instead of the hex format
+            assert_eq!(
+                json_to_bencode_unchecked(br#""<hex>7370616d</hex>""#),
+                b"19:<hex>7370616d</hex>".to_vec()
+            );
+        }
+
+        #[test]
+        fn text_that_looks_like_the_hex_format_written_in_hex_format() {
+            // The parser writes the text `<hex>ab</hex>` in hex format
+            assert_eq!(
+                json_to_bencode_unchecked(br#""<hex>3c6865783e61623c2f6865783e</hex>""#),
+                b"13:<hex>ab</hex>".to_vec()
+            );
+        }
+
+        #[test]
+        fn text_in_hex_format_with_invalid_hex_digits() {
+            assert_eq!(
+                json_to_bencode_unchecked(br#""<hex>FFFE</hex>""#),
+                b"15:<hex>FFFE</hex>".to_vec()
+            );
+        }
+
+        #[test]
+        fn big_text_followed_by_non_utf8_bytes_in_hex_format() {
+            use crate::{parsers::string::STRING_CHUNK_SIZE, try_bencode_to_json};
+
+            let mut bencoded = format!("{}:", STRING_CHUNK_SIZE + 2).into_bytes();
+            bencoded.extend_from_slice(&b"a".repeat(STRING_CHUNK_SIZE));
+            bencoded.extend_from_slice(b"\xFF\xFE");
+
+            let json = try_bencode_to_json(&bencoded).unwrap();
+
+            assert!(json.ends_with("a<hex>fffe</hex>\""));
+            assert_eq!(json_to_bencode_unchecked(json.as_bytes()), bencoded);
+        }
+
+        #[test]
+        fn short_text_followed_by_non_utf8_bytes_in_hex_format() {
+            // The parser would have printed the whole string in hex format
+            assert_eq!(
+                json_to_bencode_unchecked(br#""a<hex>fffe</hex>""#),
+                b"16:a<hex>fffe</hex>".to_vec()
+            );
+        }
+
+        mod with_escaped_chars {
+            use crate::encoders::json::tests::json_to_bencode_unchecked;
+
+            #[test]
+            fn double_quote_and_backslash() {
+                assert_eq!(
+                    json_to_bencode_unchecked(br#""\"\\\/""#),
+                    b"3:\"\\/".to_vec()
+                );
+            }
+
+            #[test]
+            fn control_characters() {
+                assert_eq!(
+                    json_to_bencode_unchecked(br#""\b\f\n\r\t""#),
+                    b"5:\x08\x0C\n\r\t".to_vec()
+                );
+            }
+
+            #[test]
+            fn unicode_escape() {
+                assert_eq!(
+                    json_to_bencode_unchecked(br#""\u00f1\u0000""#),
+                    b"3:\xC3\xB1\x00".to_vec()
+                );
+            }
+
+            #[test]
+            fn unicode_escape_with_surrogate_pair() {
+                assert_eq!(
+                    json_to_bencode_unchecked(br#""\ud83d\ude00""#),
+                    "4:😀".as_bytes().to_vec()
+                );
+            }
+        }
+    }
+
+    mod lists {
+        use crate::encoders::json::tests::json_to_bencode_unchecked;
+
+        #[test]
+        fn empty_list() {
+            assert_eq!(json_to_bencode_unchecked(b"[]"), b"le".to_vec());
+        }
+
+        #[test]
+        fn nested_empty_lists() {
+            assert_eq!(json_to_bencode_unchecked(b"[[[]]]"), b"llleee".to_vec());
+        }
+
+        #[test]
+        fn with_items_of_different_types() {
+            assert_eq!(
+                json_to_bencode_unchecked(br#"[42,"alice",[],{}]"#),
+                b"li42e5:aliceledee".to_vec()
+            );
+        }
+    }
+
+    mod dictionaries {
+        use crate::encoders::json::tests::json_to_bencode_unchecked;
+
+        #[test]
+        fn empty_dictionary() {
+            assert_eq!(json_to_bencode_unchecked(b"{}"), b"de".to_vec());
+        }
+
+        #[test]
+        fn with_fields_of_different_types() {
+            assert_eq!(
+                json_to_bencode_unchecked(br#"{"bar":"spam","foo":42,"baz":[1],"qux":{}}"#),
+                b"d3:bar4:spam3:fooi42e3:bazli1ee3:quxdee".to_vec()
+            );
+        }
+
+        #[test]
+        fn keeping_the_original_field_order() {
+            assert_eq!(
+                json_to_bencode_unchecked(br#"{"b":1,"a":2}"#),
+                b"d1:bi1e1:ai2ee".to_vec()
+            );
+        }
+
+        #[test]
+        fn with_a_non_utf8_key() {
+            assert_eq!(
+                json_to_bencode_unchecked(br#"{"<hex>fffe</hex>":"bar"}"#),
+                b"d2:\xFF\xFE3:bare".to_vec()
+            );
+        }
+    }
+
+    mod it_should_produce_the_original_bencode_after_a_round_trip {
+        use crate::{encoders::json::tests::json_to_bencode_unchecked, try_bencode_to_json};
+
+        fn round_trip(bencoded_value: &[u8]) -> Vec<u8> {
+            let json = try_bencode_to_json(bencoded_value).unwrap();
+            json_to_bencode_unchecked(json.as_bytes())
+        }
+
+        #[test]
+        fn with_a_dictionary_containing_all_types() {
+            let bencoded_value = b"d4:listli-1e0:e3:numi42e3:str4:spam6:nestedd3:\xFF\xFE\xFDleee";
+
+            assert_eq!(round_trip(bencoded_value), bencoded_value.to_vec());
+        }
+
+        #[test]
+        fn with_strings_containing_json_special_chars() {
+            let bencoded_value = b"l1:\"1:\\1:\n1:\x00e";
+
+            assert_eq!(round_trip(bencoded_value), bencoded_value.to_vec());
+        }
+
+        #[test]
+        fn with_strings_that_look_like_the_hex_format() {
+            let bencoded_value = b"d13:<hex>ab</hex>13:<hex>ab</hex>e";
+
+            assert_eq!(round_trip(bencoded_value), bencoded_value.to_vec());
+        }
+
+        #[test]
+        fn with_strings_longer_than_a_chunk_that_end_like_the_hex_format() {
+            use crate::parsers::string::STRING_CHUNK_SIZE;
+
+            for (text_len, end) in [
+                (70_000 - 13, "<hex>ff</hex>"),
+                (STRING_CHUNK_SIZE - 5, "<hex>abcd</hex>"),
+                (STRING_CHUNK_SIZE, "<hex>ab</hex>.<hex></hex>"),
+            ] {
+                let string = format!("{}{end}", "a".repeat(text_len));
+                let bencoded_value = format!("{}:{string}", string.len()).into_bytes();
+
+                assert_eq!(round_trip(&bencoded_value), bencoded_value);
+            }
+        }
+
+        #[test]
+        fn with_short_strings_that_end_like_the_escaped_hex_format() {
+            let bencoded_value = b"38:a<hex>3c6865783e61623c2f6865783e</hex>";
+
+            assert_eq!(round_trip(bencoded_value), bencoded_value.to_vec());
+        }
+    }
+
+    mod it_should_fail {
+        use crate::{encoders::error::Error, try_json_to_bencode};
+
+        #[test]
+        fn when_it_cannot_recognized_the_fist_byte_of_a_new_json_value() {
+            let result = try_json_to_bencode(b"a");
+
+            assert!(matches!(
+                result,
+                Err(Error::UnrecognizedFirstJsonValueByte { .. })
+            ));
+        }
+
+        #[test]
+        fn when_there_is_more_than_one_top_level_value() {
+            for json in [&b"1 2"[..], b"[1][2]", b"{} \"spam\""] {
+                let result = try_json_to_bencode(json);
+
+                assert!(matches!(result, Err(Error::TrailingData { .. })));
+            }
+        }
+
+        #[test]
+        fn when_the_value_has_no_bencode_equivalent() {
+            for json in [&b"true"[..], b"false", b"null"] {
+                let result = try_json_to_bencode(json);
+
+                assert!(matches!(
+                    result,
+                    Err(Error::JsonValueWithoutBencodeEquivalent { .. })
+                ));
+            }
+        }
+
+        mod parsing_numbers {
+            use crate::{encoders::error::Error, try_json_to_bencode};
+
+            #[test]
+            fn when_the_number_is_a_float() {
+                for json in [&b"1.5"[..], b"1e3", b"1E3"] {
+                    let result = try_json_to_bencode(json);
+
+                    assert!(matches!(
+                        result,
+                        Err(Error::NonIntegerNumbersNotAllowed { .. })
+                    ));
+                }
+            }
+
+            #[test]
+            fn when_it_is_a_negative_zero() {
+                for json in [&b"-0"[..], b"[-0]"] {
+                    let result = try_json_to_bencode(json);
+
+                    assert!(matches!(result, Err(Error::NegativeZeroNotAllowed { .. })));
+                }
+            }
+
+            #[test]
+            fn when_it_has_leading_zeros() {
+                let result = try_json_to_bencode(b"01");
+
+                assert!(matches!(
+                    result,
+                    Err(Error::UnexpectedByteParsingNumber { .. })
+                ));
+            }
+
+            #[test]
+            fn when_it_has_a_sign_without_digits() {
+                let result = try_json_to_bencode(b"-a");
+
+                assert!(matches!(
+                    result,
+                    Err(Error::UnexpectedByteParsingNumber { .. })
+                ));
+            }
+
+            #[test]
+            fn when_the_input_ends_after_the_sign() {
+                let result = try_json_to_bencode(b"-");
+
+                assert!(matches!(
+                    result,
+                    Err(Error::UnexpectedEndOfInputParsingNumber { .. })
+                ));
+            }
+        }
+
+        mod parsing_strings {
+            use crate::{encoders::error::Error, try_json_to_bencode};
+
+            #[test]
+            fn when_the_input_ends_before_the_closing_quote() {
+                let result = try_json_to_bencode(br#""spam"#);
+
+                assert!(matches!(
+                    result,
+                    Err(Error::UnexpectedEndOfInputParsingString { .. })
+                ));
+            }
+
+            #[test]
+            fn when_it_contains_an_invalid_escape_sequence() {
+                for json in [&br#""\a""#[..], br#""\u00zz""#, br#""\ud83d\u00zz""#] {
+                    let result = try_json_to_bencode(json);
+
+                    assert!(matches!(
+                        result,
+                        Err(Error::InvalidEscapeSequenceParsingString { .. })
+                    ));
+                }
+            }
+
+            #[test]
+            fn when_it_contains_a_surrogate_without_its_pair() {
+                for json in [
+                    &br#""\ud83d""#[..],
+                    br#""\ud83dxx""#,
+                    br#""\ud83d\u0041""#,
+                    br#""\ude00""#,
+                ] {
+                    let result = try_json_to_bencode(json);
+
+                    assert!(matches!(
+                        result,
+                        Err(Error::LoneSurrogateParsingString { .. })
+                    ));
+                }
+            }
+
+            #[test]
+            fn when_it_contains_an_unescaped_control_character() {
+                for json in [&b"\"a\nb\""[..], b"\"\t\"", b"\"\x00\"", b"{\"a\x1F\":1}"] {
+                    let result = try_json_to_bencode(json);
+
+                    assert!(matches!(
+                        result,
+                        Err(Error::UnescapedControlCharacterParsingString { .. })
+                    ));
+                }
+            }
+        }
+
+        mod parsing_arrays {
+            use crate::{encoders::error::Error, try_json_to_bencode};
+
+            #[test]
+            fn when_the_array_does_not_have_the_closing_byte() {
+                for json in [&b"["[..], b"[1", b"[1,"] {
+                    let result = try_json_to_bencode(json);
+
+                    assert!(matches!(
+                        result,
+                        Err(Error::UnexpectedEndOfInputParsingArray { .. })
+                    ));
+                }
+            }
+
+            #[test]
+            fn when_the_items_are_not_separated_by_commas() {
+                let result = try_json_to_bencode(b"[1 2]");
+
+                assert!(matches!(
+                    result,
+                    Err(Error::ExpectedCommaOrEndOfArray { .. })
+                ));
+            }
+
+            #[test]
+            fn when_it_has_a_trailing_comma() {
+                let result = try_json_to_bencode(b"[1,]");
+
+                let Err(Error::UnrecognizedFirstJsonValueByte(read_context, _)) = result else {
+                    panic!("expected an unrecognized first byte error, got {result:?}");
+                };
+
+                assert_eq!(read_context.path, "[1]");
+            }
+        }
+
+        mod parsing_objects {
+            use crate::{encoders::error::Error, try_json_to_bencode};
+
+            #[test]
+            fn when_the_object_does_not_have_the_closing_byte() {
+                for json in [&b"{"[..], br#"{"foo""#, br#"{"foo":1"#, br#"{"foo":1,"#] {
+                    let result = try_json_to_bencode(json);
+
+                    assert!(matches!(
+                        result,
+                        Err(Error::UnexpectedEndOfInputParsingObject { .. })
+                    ));
+                }
+            }
+
+            #[test]
+            fn when_the_key_is_not_a_string() {
+                for json in [&b"{1:2}"[..], br#"{"foo":1,2:3}"#] {
+                    let result = try_json_to_bencode(json);
+
+                    assert!(matches!(
+                        result,
+                        Err(Error::ExpectedStringForObjectKey { .. })
+                    ));
+                }
+            }
+
+            #[test]
+            fn when_the_key_is_not_followed_by_a_colon() {
+                let result = try_json_to_bencode(br#"{"foo" 1}"#);
+
+                assert!(matches!(
+                    result,
+                    Err(Error::ExpectedColonAfterObjectKey { .. })
+                ));
+            }
+
+            #[test]
+            fn including_the_path_to_the_value() {
+                let result =
+                    try_json_to_bencode(br#"{"info":{"files":[{"length":1},{"length":1.5}]}}"#);
+
+                let Err(Error::NonIntegerNumbersNotAllowed(read_context, _)) = result else {
+                    panic!("expected a non integer number error, got {result:?}");
+                };
+
+                assert_eq!(read_context.path, "info.files[1].length");
+            }
+
+            #[test]
+            fn including_the_path_to_the_object_when_the_key_is_not_a_string() {
+                let result = try_json_to_bencode(br#"{"a":{"b":1,2:3}}"#);
+
+                let Err(Error::ExpectedStringForObjectKey(read_context, _)) = result else {
+                    panic!("expected a key error, got {result:?}");
+                };
+
+                assert_eq!(read_context.path, "a");
+            }
+
+            #[test]
+            fn when_the_fields_are_not_separated_by_commas() {
+                let result = try_json_to_bencode(br#"{"foo":1 "bar":2}"#);
+
+                assert!(matches!(
+                    result,
+                    Err(Error::ExpectedCommaOrEndOfObject { .. })
+                ));
+            }
+        }
+    }
+}
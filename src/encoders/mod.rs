@@ -0,0 +1,3 @@
+//! Encoders, converters from other formats into bencode.
+pub mod error;
+pub mod json;
@@ -3,11 +3,25 @@
 //! There are high-level functions for common purposes that call the lower level
 //! parser. You can use the low-lever parser if the high-level wrappers are not
 //! suitable for your needs.
+//!
+//! The reverse conversion, from the JSON generated by the parser back into
+//! bencoded bytes, is also available.
+
+// Errors include the read and write contexts, with the latest bytes and the
+// path to the value. They are big, but they only happen once per conversion.
+#![allow(clippy::result_large_err)]
+
+use encoders::json::JsonToBencode;
 use parsers::{error::Error, BencodeParser};
 
+pub mod de;
+pub mod encoders;
 pub mod parsers;
 pub mod rw;
+pub mod ser;
 mod test;
+pub mod torrent;
+pub mod value;
 
 /// It converts bencoded bytes into a JSON string.
 ///
@@ -25,6 +39,23 @@ pub fn try_bencode_to_json(input_buffer: &[u8]) -> Result<String, Error> {
     }
 }
 
+/// It converts the JSON generated by [`try_bencode_to_json`] back into bencoded
+/// bytes.
+///
+/// # Errors
+///
+/// Will return an error if the conversion fails.
+pub fn try_json_to_bencode(input_buffer: &[u8]) -> Result<Vec<u8>, encoders::error::Error> {
+    let mut output = Vec::new();
+
+    let mut converter = JsonToBencode::new(input_buffer);
+
+    match converter.write_bytes(&mut output) {
+        Ok(()) => Ok(output),
+        Err(err) => Err(err),
+    }
+}
+
 /// Helper to convert a string into a bencoded string.
 #[must_use]
 pub fn to_bencode(value: &str) -> Vec<u8> {
@@ -53,6 +84,24 @@ mod tests {
         }
     }
 
+    mod converting_json_to_bencode {
+        use crate::try_json_to_bencode;
+
+        #[test]
+        fn when_it_succeeds() {
+            let result = try_json_to_bencode(br#"{"spam":"eggs"}"#).unwrap();
+
+            assert_eq!(result, b"d4:spam4:eggse");
+        }
+
+        #[test]
+        fn when_it_fails() {
+            let result = try_json_to_bencode(b"invalid JSON value");
+
+            assert!(result.is_err());
+        }
+    }
+
     mod converting_string_to_bencode {
         use crate::to_bencode;
 
@@ -13,17 +13,104 @@
 //! ```text
 //! cargo run -- -i ./tests/fixtures/sample.bencode -o output.json
 //! ```
-use clap::{Arg, Command};
+use clap::{Arg, ArgAction, ArgMatches, Command};
 use std::fs::File;
 use std::io::{self, Read, Write};
-use torrust_bencode2json::parsers::BencodeParser;
+use torrust_bencode2json::{
+    parsers::{
+        config::ParserConfig,
+        error::Error,
+        integer::{IntegerRange, OutOfRangeIntegers},
+        mode::OutputMode,
+        string::StringEncoding,
+        BencodeParser, MultiValueFormat, OutputFormat,
+    },
+    rw::pretty_writer::{Indent, PrettyOptions},
+};
 
 fn main() {
     run();
 }
 
 fn run() {
-    let matches = Command::new("torrust-bencode2json")
+    let matches = command().get_matches();
+
+    let error_output = ErrorOutput::from_matches(&matches);
+
+    // Handle input stream (file or stdin)
+    let input: Box<dyn Read> = if let Some(input_path) = matches.get_one::<String>("input") {
+        match File::open(input_path) {
+            Ok(file) => Box::new(file),
+            Err(e) => exit_with_error(&Error::Io(e), error_output),
+        }
+    } else {
+        Box::new(io::stdin())
+    };
+
+    // Handle output stream (file or stdout)
+    let mut output: Box<dyn Write> = if let Some(output_path) = matches.get_one::<String>("output")
+    {
+        match File::create(output_path) {
+            Ok(file) => Box::new(file),
+            Err(e) => exit_with_error(&Error::Io(e), error_output),
+        }
+    } else {
+        Box::new(io::stdout())
+    };
+
+    let parser = BencodeParser::with_config(input, parser_config(&matches));
+
+    let mut parser = configure_parser(parser, &matches);
+
+    if let Err(e) = parser.write_bytes(&mut output) {
+        exit_with_error(&e, error_output);
+    }
+}
+
+/// How errors are written to stderr.
+#[derive(Clone, Copy)]
+enum ErrorOutput {
+    /// The message with a hex dump of the input around the failing byte.
+    Diagnostic,
+
+    /// The message with the read and write contexts in one line.
+    Plain,
+
+    /// A JSON report.
+    Json,
+}
+
+impl ErrorOutput {
+    fn from_matches(matches: &ArgMatches) -> Self {
+        if matches
+            .get_one::<String>("error-format")
+            .is_some_and(|format| format == "json")
+        {
+            Self::Json
+        } else if matches.get_flag("plain-errors") {
+            Self::Plain
+        } else {
+            Self::Diagnostic
+        }
+    }
+}
+
+/// It prints the error on stderr and exits with an error code.
+fn exit_with_error(error: &Error, output: ErrorOutput) -> ! {
+    match output {
+        ErrorOutput::Diagnostic => eprintln!("{}", error.diagnostic()),
+        ErrorOutput::Plain => eprintln!("Error: {error}"),
+        ErrorOutput::Json => match serde_json::to_string(error) {
+            Ok(report) => eprintln!("{report}"),
+            Err(e) => eprintln!("Error: {error} (JSON report failed: {e})"),
+        },
+    }
+
+    std::process::exit(1);
+}
+
+fn command() -> Command {
+    Command::new("torrust-bencode2json")
         .version("0.1.0")
         .author("Torrust Organization")
         .about("Converts Bencode to JSON")
@@ -41,37 +128,248 @@ fn run() {
                 .default_value(None)
                 .help("Optional output file (defaults to stdout)"),
         )
-        .get_matches();
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("lenient")
+                .help("Reject dictionaries with unsorted or duplicate keys, as required by BEP 3"),
+        )
+        .arg(
+            Arg::new("lenient")
+                .long("lenient")
+                .action(ArgAction::SetTrue)
+                .help("Accept non-canonical values found in legacy files, like `i-0e` or `03:abc`"),
+        )
+        .args(limit_args())
+        .arg(
+            Arg::new("string-encoding")
+                .long("string-encoding")
+                .value_parser([
+                    "hex-wrapped",
+                    "hex",
+                    "base64",
+                    "base64url",
+                    "bytes-object",
+                    "latin1",
+                ])
+                .default_value("hex-wrapped")
+                .help("Encoding for strings that are not valid UTF-8"),
+        )
+        .arg(
+            Arg::new("mode")
+                .long("mode")
+                .value_parser(["generic", "torrent", "tracker-response", "scrape", "krpc"])
+                .default_value("generic")
+                .help("Write the fields of well-known BitTorrent data in a readable way"),
+        )
+        .arg(
+            Arg::new("multi")
+                .long("multi")
+                .value_parser(["single", "ndjson", "array"])
+                .default_value("single")
+                .help("Output for inputs with more than one bencoded value"),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_parser(["json", "yaml", "cbor", "msgpack"])
+                .default_value("json")
+                .help("Output format"),
+        )
+        .arg(
+            Arg::new("pretty")
+                .long("pretty")
+                .action(ArgAction::SetTrue)
+                .help("Pretty-print the JSON with line breaks and indentation"),
+        )
+        .arg(
+            Arg::new("indent")
+                .long("indent")
+                .value_name("N|tab")
+                .value_parser(parse_indent)
+                .help("Indent the pretty-printed JSON with N spaces or a tab (implies --pretty)"),
+        )
+        .arg(
+            Arg::new("big-integers")
+                .long("big-integers")
+                .value_parser(["passthrough", "string", "fail"])
+                .default_value("passthrough")
+                .help("What to do with integers out of the --integer-range"),
+        )
+        .arg(
+            Arg::new("integer-range")
+                .long("integer-range")
+                .value_parser(["i64", "json-safe"])
+                .default_value("i64")
+                .help("Range of the integers written as numbers, json-safe is ±(2^53 - 1)"),
+        )
+        .args(error_args())
+}
 
-    // Handle input stream (file or stdin)
-    let input: Box<dyn Read> = if let Some(input_path) = matches.get_one::<String>("input") {
-        match File::open(input_path) {
-            Ok(file) => Box::new(file),
-            Err(e) => {
-                eprintln!("Error: {e}");
-                std::process::exit(1);
-            }
-        }
-    } else {
-        Box::new(io::stdin())
+/// The arguments to select how errors are written to stderr.
+fn error_args() -> [Arg; 2] {
+    [
+        Arg::new("error-format")
+            .long("error-format")
+            .value_parser(["text", "json"])
+            .default_value("text")
+            .help("Format of the errors written to stderr"),
+        Arg::new("plain-errors")
+            .long("plain-errors")
+            .action(ArgAction::SetTrue)
+            .help("Write text errors in one line, without the hex dump of the input"),
+    ]
+}
+
+/// The arguments to limit the resources used by the parser.
+fn limit_args() -> [Arg; 5] {
+    [
+        Arg::new("max-input-bytes")
+            .long("max-input-bytes")
+            .value_name("N")
+            .value_parser(clap::value_parser!(u64))
+            .help("Fail if the input is longer than N bytes"),
+        Arg::new("max-depth")
+            .long("max-depth")
+            .value_name("N")
+            .value_parser(clap::value_parser!(usize))
+            .help("Fail if there are more than N nested lists and dictionaries"),
+        Arg::new("max-string-len")
+            .long("max-string-len")
+            .value_name("N")
+            .value_parser(clap::value_parser!(usize))
+            .help("Fail if a string is longer than N bytes"),
+        Arg::new("max-integer-digits")
+            .long("max-integer-digits")
+            .value_name("N")
+            .value_parser(clap::value_parser!(usize))
+            .help("Fail if an integer has more than N digits"),
+        Arg::new("max-tokens")
+            .long("max-tokens")
+            .value_name("N")
+            .value_parser(clap::value_parser!(u64))
+            .help("Fail if there are more than N integers, strings, lists and dictionaries"),
+    ]
+}
+
+/// It parses the value of the `--indent` argument: a number of spaces or
+/// `tab`.
+fn parse_indent(value: &str) -> Result<Indent, String> {
+    if value == "tab" {
+        return Ok(Indent::Tabs(1));
+    }
+
+    value
+        .parse()
+        .map(Indent::Spaces)
+        .map_err(|_| format!("expected a number of spaces or `tab`, found `{value}`"))
+}
+
+/// It builds the parser config with the limits and modes selected in the
+/// command line arguments.
+fn parser_config(matches: &ArgMatches) -> ParserConfig {
+    let mut config = ParserConfig::default();
+
+    if matches.get_flag("strict") {
+        config = config.with_strict_mode();
+    }
+
+    if matches.get_flag("lenient") {
+        config = config.with_lenient_mode();
+    }
+
+    if let Some(max_input_bytes) = matches.get_one::<u64>("max-input-bytes") {
+        config = config.with_max_input_bytes(*max_input_bytes);
+    }
+
+    if let Some(max_depth) = matches.get_one::<usize>("max-depth") {
+        config = config.with_max_depth(*max_depth);
+    }
+
+    if let Some(max_string_len) = matches.get_one::<usize>("max-string-len") {
+        config = config.with_max_string_len(*max_string_len);
+    }
+
+    if let Some(max_integer_digits) = matches.get_one::<usize>("max-integer-digits") {
+        config = config.with_max_integer_digits(*max_integer_digits);
+    }
+
+    if let Some(max_tokens) = matches.get_one::<u64>("max-tokens") {
+        config = config.with_max_tokens(*max_tokens);
+    }
+
+    config
+}
+
+/// It sets the parser options selected in the command line arguments.
+fn configure_parser<R: Read>(
+    mut parser: BencodeParser<R>,
+    matches: &ArgMatches,
+) -> BencodeParser<R> {
+    let string_encoding = match matches
+        .get_one::<String>("string-encoding")
+        .map(String::as_str)
+    {
+        Some("hex") => StringEncoding::Hex,
+        Some("base64") => StringEncoding::Base64,
+        Some("base64url") => StringEncoding::Base64Url,
+        Some("bytes-object") => StringEncoding::BytesObject,
+        Some("latin1") => StringEncoding::Latin1,
+        _ => StringEncoding::HexWrapped,
     };
 
-    // Handle output stream (file or stdout)
-    let mut output: Box<dyn Write> = if let Some(output_path) = matches.get_one::<String>("output")
+    parser = parser.with_string_encoding(string_encoding);
+
+    let output_mode = match matches.get_one::<String>("mode").map(String::as_str) {
+        Some("torrent") => OutputMode::Torrent,
+        Some("tracker-response") => OutputMode::TrackerResponse,
+        Some("scrape") => OutputMode::Scrape,
+        Some("krpc") => OutputMode::Krpc,
+        _ => OutputMode::Generic,
+    };
+
+    parser = parser.with_output_mode(output_mode);
+
+    let multi_value_format = match matches.get_one::<String>("multi").map(String::as_str) {
+        Some("ndjson") => MultiValueFormat::Ndjson,
+        Some("array") => MultiValueFormat::Array,
+        _ => MultiValueFormat::Single,
+    };
+
+    parser = parser.with_multi_value_format(multi_value_format);
+
+    let output_format = match matches.get_one::<String>("format").map(String::as_str) {
+        Some("yaml") => OutputFormat::Yaml,
+        Some("cbor") => OutputFormat::Cbor,
+        Some("msgpack") => OutputFormat::MessagePack,
+        _ => OutputFormat::Json,
+    };
+
+    parser = parser.with_output_format(output_format);
+
+    let integer_range = match matches
+        .get_one::<String>("integer-range")
+        .map(String::as_str)
     {
-        match File::create(output_path) {
-            Ok(file) => Box::new(file),
-            Err(e) => {
-                eprintln!("Error: {e}");
-                std::process::exit(1);
-            }
-        }
-    } else {
-        Box::new(io::stdout())
+        Some("json-safe") => IntegerRange::JsonSafe,
+        _ => IntegerRange::I64,
     };
 
-    if let Err(e) = BencodeParser::new(input).write_bytes(&mut output) {
-        eprintln!("Error: {e}");
-        std::process::exit(1);
+    let out_of_range_integers = match matches
+        .get_one::<String>("big-integers")
+        .map(String::as_str)
+    {
+        Some("string") => OutOfRangeIntegers::String(integer_range),
+        Some("fail") => OutOfRangeIntegers::Fail(integer_range),
+        _ => OutOfRangeIntegers::Passthrough,
+    };
+
+    parser = parser.with_out_of_range_integers(out_of_range_integers);
+
+    match matches.get_one::<Indent>("indent") {
+        Some(indent) => parser.with_pretty_print(PrettyOptions::default().with_indent(*indent)),
+        None if matches.get_flag("pretty") => parser.with_pretty_print(PrettyOptions::default()),
+        None => parser,
     }
 }
@@ -0,0 +1,603 @@
+//! Binary output formats for the [`BencodeParser`](super::BencodeParser):
+//! [CBOR](https://www.rfc-editor.org/rfc/rfc8949.html) and
+//! [MessagePack](https://github.com/msgpack/msgpack/blob/master/spec.md).
+//!
+//! Bencoded values are mapped to native types:
+//!
+//! - Integers are written with the smallest integer encoding. Integers that do
+//!   not fit into 64 bits are written as bignums in CBOR, and as strings with
+//!   their decimal digits in `MessagePack`, which does not have bignums.
+//! - Strings are written as byte strings (`bin` in `MessagePack`), without
+//!   encoding them.
+//! - Lists are written as arrays.
+//! - Dictionaries are written as maps. Their keys are written as text strings
+//!   when they are valid UTF-8, and as byte strings otherwise.
+//!
+//! CBOR is written while the input is read, using indefinite-length arrays
+//! and maps. `MessagePack` requires the number of items before the items, and
+//! the input can only be read once, so every list and dictionary is kept in
+//! memory until it ends. The `MessagePack` encoding of a bencoded value is
+//! about as long as the value, so the memory used is bounded by the input
+//! size. For untrusted input, limit it with
+//! [`ParserConfig::with_max_input_bytes`](super::config::ParserConfig::with_max_input_bytes).
+use crate::rw::writer::Writer;
+
+use super::error::Error;
+
+// CBOR major types
+const CBOR_UNSIGNED_INTEGER: u8 = 0;
+const CBOR_NEGATIVE_INTEGER: u8 = 1;
+const CBOR_BYTE_STRING: u8 = 2;
+const CBOR_TEXT_STRING: u8 = 3;
+const CBOR_TAG: u8 = 6;
+
+const CBOR_INDEFINITE_ARRAY: u8 = 0x9f;
+const CBOR_INDEFINITE_MAP: u8 = 0xbf;
+const CBOR_BREAK: u8 = 0xff;
+
+const CBOR_POSITIVE_BIGNUM_TAG: u64 = 2;
+const CBOR_NEGATIVE_BIGNUM_TAG: u64 = 3;
+
+/// A binary output format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BinaryFormat {
+    Cbor,
+    MessagePack,
+}
+
+/// A `MessagePack` list or dictionary that has not ended yet.
+#[derive(Debug, Default)]
+struct PendingContainer {
+    is_dict: bool,
+
+    /// The number of values, including the keys of dictionaries.
+    items: u64,
+
+    /// The items already encoded.
+    buffer: Vec<u8>,
+}
+
+/// It writes the bencoded tokens in a binary format.
+#[derive(Debug)]
+pub(crate) struct BinaryEmitter {
+    format: BinaryFormat,
+
+    /// The `MessagePack` lists and dictionaries not ended yet.
+    pending: Vec<PendingContainer>,
+}
+
+impl BinaryEmitter {
+    pub(crate) fn new(format: BinaryFormat) -> Self {
+        Self {
+            format,
+            pending: Vec::new(),
+        }
+    }
+
+    /// It counts a new value or key in the current list or dictionary.
+    pub(crate) fn begin_item(&mut self) {
+        if let Some(container) = self.pending.last_mut() {
+            container.items += 1;
+        }
+    }
+
+    /// It writes an integer from its decimal digits, with an optional minus
+    /// sign.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if it can't write to the output.
+    pub(crate) fn write_integer<W: Writer>(
+        &mut self,
+        digits: &[u8],
+        writer: &mut W,
+    ) -> Result<(), Error> {
+        let (is_negative, magnitude) = match digits.split_first() {
+            Some((b'-', magnitude)) => (true, magnitude),
+            _ => (false, digits),
+        };
+
+        let magnitude = decimal_to_big_endian(magnitude);
+
+        let bytes = match self.format {
+            BinaryFormat::Cbor => cbor_integer(is_negative, &magnitude),
+            BinaryFormat::MessagePack => {
+                let Some(bytes) = msgpack_integer(is_negative, &magnitude) else {
+                    // `MessagePack` does not have bignums
+                    return self.write_text(digits, writer);
+                };
+                bytes
+            }
+        };
+
+        self.write(&bytes, writer)
+    }
+
+    /// It writes the beginning of a byte string. Its bytes must be written
+    /// with [`BinaryEmitter::write_raw`].
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if it can't write to the output, or if the
+    /// string is too long for `MessagePack`.
+    pub(crate) fn begin_bytes<W: Writer>(
+        &mut self,
+        length: usize,
+        writer: &mut W,
+    ) -> Result<(), Error> {
+        let head = match self.format {
+            BinaryFormat::Cbor => Some(cbor_head(CBOR_BYTE_STRING, length as u64)),
+            BinaryFormat::MessagePack => msgpack_bin_head(length),
+        };
+
+        let head = head.ok_or_else(|| too_long("binary string", length as u64))?;
+
+        self.write(&head, writer)
+    }
+
+    /// It writes a whole byte string.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if it can't write to the output, or if the
+    /// string is too long for `MessagePack`.
+    pub(crate) fn write_bytes<W: Writer>(
+        &mut self,
+        bytes: &[u8],
+        writer: &mut W,
+    ) -> Result<(), Error> {
+        self.begin_bytes(bytes.len(), writer)?;
+        self.write_raw(bytes, writer)
+    }
+
+    /// It writes a dictionary key: a text string if it's valid UTF-8, or a
+    /// byte string otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if it can't write to the output, or if the
+    /// key is too long for `MessagePack`.
+    pub(crate) fn write_key<W: Writer>(&mut self, key: &[u8], writer: &mut W) -> Result<(), Error> {
+        if std::str::from_utf8(key).is_err() {
+            return self.write_bytes(key, writer);
+        }
+
+        self.write_text(key, writer)
+    }
+
+    /// It writes a text string. The bytes must be valid UTF-8.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if it can't write to the output, or if the
+    /// string is too long for `MessagePack`.
+    pub(crate) fn write_text<W: Writer>(
+        &mut self,
+        text: &[u8],
+        writer: &mut W,
+    ) -> Result<(), Error> {
+        let head = match self.format {
+            BinaryFormat::Cbor => Some(cbor_head(CBOR_TEXT_STRING, text.len() as u64)),
+            BinaryFormat::MessagePack => msgpack_string_head(text.len()),
+        };
+
+        let head = head.ok_or_else(|| too_long("string", text.len() as u64))?;
+
+        self.write(&head, writer)?;
+        self.write_raw(text, writer)
+    }
+
+    /// It writes bytes of the string begun with
+    /// [`BinaryEmitter::begin_bytes`].
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if it can't write to the output.
+    pub(crate) fn write_raw<W: Writer>(
+        &mut self,
+        bytes: &[u8],
+        writer: &mut W,
+    ) -> Result<(), Error> {
+        self.write(bytes, writer)
+    }
+
+    /// It writes the beginning of a list or dictionary.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if it can't write to the output.
+    pub(crate) fn begin_list_or_dict<W: Writer>(
+        &mut self,
+        is_dict: bool,
+        writer: &mut W,
+    ) -> Result<(), Error> {
+        match self.format {
+            BinaryFormat::Cbor => {
+                let head = if is_dict {
+                    CBOR_INDEFINITE_MAP
+                } else {
+                    CBOR_INDEFINITE_ARRAY
+                };
+                self.write(&[head], writer)
+            }
+            BinaryFormat::MessagePack => {
+                self.pending.push(PendingContainer {
+                    is_dict,
+                    ..PendingContainer::default()
+                });
+                Ok(())
+            }
+        }
+    }
+
+    /// It writes the end of a list or dictionary. In `MessagePack`, the whole
+    /// list or dictionary is written at this point.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if it can't write to the output, or if the list
+    /// or dictionary has too many items for `MessagePack`.
+    pub(crate) fn end_list_or_dict<W: Writer>(&mut self, writer: &mut W) -> Result<(), Error> {
+        match self.format {
+            BinaryFormat::Cbor => self.write(&[CBOR_BREAK], writer),
+            BinaryFormat::MessagePack => {
+                let Some(container) = self.pending.pop() else {
+                    return Ok(());
+                };
+
+                let (kind, len) = if container.is_dict {
+                    ("map", container.items / 2)
+                } else {
+                    ("array", container.items)
+                };
+
+                let head = msgpack_container_head(container.is_dict, len)
+                    .ok_or_else(|| too_long(kind, len))?;
+
+                self.write(&head, writer)?;
+                self.write(&container.buffer, writer)
+            }
+        }
+    }
+
+    /// It writes the bytes to the output, or to the `MessagePack` list or
+    /// dictionary that contains them.
+    fn write<W: Writer>(&mut self, bytes: &[u8], writer: &mut W) -> Result<(), Error> {
+        match self.pending.last_mut() {
+            Some(container) => container.buffer.extend_from_slice(bytes),
+            None => writer.write_bytes(bytes)?,
+        }
+
+        Ok(())
+    }
+}
+
+fn too_long(kind: &'static str, length: u64) -> Error {
+    Error::MessagePackLengthOverflow(kind, length)
+}
+
+/// It converts a decimal number into its big-endian bytes, without leading
+/// zeros.
+///
+/// The number is built in 64-bit limbs, multiplying it by `10^19` and adding
+/// the next 19 digits at a time. Each step goes through all the limbs, so the
+/// time is quadratic in the number of digits, but with 19 digits per step
+/// and per limb.
+fn decimal_to_big_endian(digits: &[u8]) -> Vec<u8> {
+    const DIGITS_PER_STEP: usize = 19;
+
+    // Little-endian limbs, so new limbs are pushed at the end
+    let mut limbs: Vec<u64> = Vec::new();
+
+    // The first step takes the digits that do not complete a full step, so
+    // all the other steps have 19 digits
+    let first_step_len = match digits.len().checked_rem(DIGITS_PER_STEP) {
+        Some(0) | None => DIGITS_PER_STEP,
+        Some(len) => len,
+    };
+
+    let mut start = 0;
+    let mut step_len = first_step_len.min(digits.len());
+
+    while start < digits.len() {
+        let step = &digits[start..start + step_len];
+
+        let value = step.iter().fold(0u64, |value, &digit| {
+            value * 10 + u64::from(digit.wrapping_sub(b'0'))
+        });
+
+        let multiplier = 10u128.pow(u32::try_from(step_len).expect("at most 19 digits"));
+
+        let mut carry = u128::from(value);
+
+        for limb in &mut limbs {
+            let product = u128::from(*limb) * multiplier + carry;
+            *limb = u64::try_from(product & u128::from(u64::MAX)).expect("masked to 64 bits");
+            carry = product >> 64;
+        }
+
+        if carry > 0 {
+            limbs.push(u64::try_from(carry).expect("the carry is less than 10^19"));
+        }
+
+        start += step_len;
+        step_len = DIGITS_PER_STEP;
+    }
+
+    let mut bytes: Vec<u8> = limbs
+        .iter()
+        .rev()
+        .flat_map(|limb| limb.to_be_bytes())
+        .collect();
+
+    let leading_zeros = bytes.iter().take_while(|&&byte| byte == 0).count();
+
+    bytes.drain(..leading_zeros);
+
+    bytes
+}
+
+/// It subtracts one from a big-endian number greater than zero.
+fn decrement_big_endian(bytes: &mut Vec<u8>) {
+    for byte in bytes.iter_mut().rev() {
+        if *byte > 0 {
+            *byte -= 1;
+            break;
+        }
+        *byte = u8::MAX;
+    }
+
+    let leading_zeros = bytes.iter().take_while(|&&byte| byte == 0).count();
+
+    bytes.drain(..leading_zeros);
+}
+
+fn big_endian_to_u64(bytes: &[u8]) -> Option<u64> {
+    if bytes.len() > 8 {
+        return None;
+    }
+
+    Some(
+        bytes
+            .iter()
+            .fold(0, |value, &byte| (value << 8) | u64::from(byte)),
+    )
+}
+
+/// It returns the head of a CBOR data item: the major type and the argument
+/// with the smallest size.
+fn cbor_head(major_type: u8, argument: u64) -> Vec<u8> {
+    let major_type = major_type << 5;
+
+    if argument < 24 {
+        vec![major_type | argument.to_be_bytes()[7]]
+    } else if let Ok(argument) = u8::try_from(argument) {
+        vec![major_type | 0x18, argument]
+    } else if let Ok(argument) = u16::try_from(argument) {
+        [&[major_type | 0x19][..], &argument.to_be_bytes()].concat()
+    } else if let Ok(argument) = u32::try_from(argument) {
+        [&[major_type | 0x1a][..], &argument.to_be_bytes()].concat()
+    } else {
+        [&[major_type | 0x1b][..], &argument.to_be_bytes()].concat()
+    }
+}
+
+/// It encodes a CBOR integer. Negative integers are encoded as `-1 - n`, so
+/// the magnitude is decremented first.
+fn cbor_integer(is_negative: bool, magnitude: &[u8]) -> Vec<u8> {
+    let mut argument = magnitude.to_vec();
+
+    // The negative zero, accepted in lenient mode, is zero
+    let is_negative = is_negative && !argument.is_empty();
+
+    if is_negative {
+        decrement_big_endian(&mut argument);
+    }
+
+    let major_type = if is_negative {
+        CBOR_NEGATIVE_INTEGER
+    } else {
+        CBOR_UNSIGNED_INTEGER
+    };
+
+    if let Some(argument) = big_endian_to_u64(&argument) {
+        return cbor_head(major_type, argument);
+    }
+
+    let tag = if is_negative {
+        CBOR_NEGATIVE_BIGNUM_TAG
+    } else {
+        CBOR_POSITIVE_BIGNUM_TAG
+    };
+
+    [
+        cbor_head(CBOR_TAG, tag),
+        cbor_head(CBOR_BYTE_STRING, argument.len() as u64),
+        argument,
+    ]
+    .concat()
+}
+
+/// It encodes a `MessagePack` integer with the smallest size. It returns `None`
+/// if the integer does not fit into 64 bits.
+fn msgpack_integer(is_negative: bool, magnitude: &[u8]) -> Option<Vec<u8>> {
+    let magnitude = big_endian_to_u64(magnitude)?;
+
+    if !is_negative || magnitude == 0 {
+        return Some(if magnitude < 0x80 {
+            vec![magnitude.to_be_bytes()[7]]
+        } else if let Ok(value) = u8::try_from(magnitude) {
+            vec![0xcc, value]
+        } else if let Ok(value) = u16::try_from(magnitude) {
+            [&[0xcd][..], &value.to_be_bytes()].concat()
+        } else if let Ok(value) = u32::try_from(magnitude) {
+            [&[0xce][..], &value.to_be_bytes()].concat()
+        } else {
+            [&[0xcf][..], &magnitude.to_be_bytes()].concat()
+        });
+    }
+
+    let value = 0_i64.checked_sub_unsigned(magnitude)?;
+
+    Some(if value >= -32 {
+        vec![value.to_be_bytes()[7]]
+    } else if let Ok(value) = i8::try_from(value) {
+        [&[0xd0][..], &value.to_be_bytes()].concat()
+    } else if let Ok(value) = i16::try_from(value) {
+        [&[0xd1][..], &value.to_be_bytes()].concat()
+    } else if let Ok(value) = i32::try_from(value) {
+        [&[0xd2][..], &value.to_be_bytes()].concat()
+    } else {
+        [&[0xd3][..], &value.to_be_bytes()].concat()
+    })
+}
+
+fn msgpack_string_head(length: usize) -> Option<Vec<u8>> {
+    if length < 32 {
+        Some(vec![0xa0 | length.to_be_bytes()[7]])
+    } else if let Ok(length) = u8::try_from(length) {
+        Some(vec![0xd9, length])
+    } else {
+        msgpack_sized_head(0xda, length as u64)
+    }
+}
+
+fn msgpack_bin_head(length: usize) -> Option<Vec<u8>> {
+    if let Ok(length) = u8::try_from(length) {
+        Some(vec![0xc4, length])
+    } else {
+        msgpack_sized_head(0xc5, length as u64)
+    }
+}
+
+fn msgpack_container_head(is_dict: bool, len: u64) -> Option<Vec<u8>> {
+    let (fix, sized) = if is_dict { (0x80, 0xde) } else { (0x90, 0xdc) };
+
+    if len < 16 {
+        Some(vec![fix | len.to_be_bytes()[7]])
+    } else {
+        msgpack_sized_head(sized, len)
+    }
+}
+
+/// It returns the head with a 16-bit length, or a 32-bit length with the next
+/// type byte.
+fn msgpack_sized_head(type_byte_16: u8, length: u64) -> Option<Vec<u8>> {
+    if let Ok(length) = u16::try_from(length) {
+        Some([&[type_byte_16][..], &length.to_be_bytes()].concat())
+    } else {
+        let length = u32::try_from(length).ok()?;
+        Some([&[type_byte_16 + 1][..], &length.to_be_bytes()].concat())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    mod the_integer_encoding {
+        use crate::parsers::binary::{cbor_integer, decimal_to_big_endian, msgpack_integer};
+
+        fn cbor(digits: &str) -> Vec<u8> {
+            let (is_negative, magnitude) = match digits.strip_prefix('-') {
+                Some(magnitude) => (true, magnitude),
+                None => (false, digits),
+            };
+            cbor_integer(is_negative, &decimal_to_big_endian(magnitude.as_bytes()))
+        }
+
+        fn msgpack(digits: &str) -> Option<Vec<u8>> {
+            let (is_negative, magnitude) = match digits.strip_prefix('-') {
+                Some(magnitude) => (true, magnitude),
+                None => (false, digits),
+            };
+            msgpack_integer(is_negative, &decimal_to_big_endian(magnitude.as_bytes()))
+        }
+
+        #[test]
+        fn should_convert_decimal_digits_into_big_endian_bytes() {
+            assert_eq!(decimal_to_big_endian(b"0"), Vec::<u8>::new());
+            assert_eq!(decimal_to_big_endian(b"255"), vec![0xFF]);
+            assert_eq!(decimal_to_big_endian(b"256"), vec![0x01, 0x00]);
+            assert_eq!(
+                decimal_to_big_endian(b"18446744073709551616"),
+                vec![0x01, 0, 0, 0, 0, 0, 0, 0, 0]
+            );
+        }
+
+        #[test]
+        fn should_convert_numbers_with_more_than_one_step_of_digits() {
+            for number in [
+                u128::from(u64::MAX),
+                10u128.pow(19),
+                10u128.pow(38),
+                u128::MAX,
+            ] {
+                let bytes = number.to_be_bytes();
+                let leading_zeros = bytes.iter().take_while(|&&byte| byte == 0).count();
+
+                assert_eq!(
+                    decimal_to_big_endian(number.to_string().as_bytes()),
+                    bytes[leading_zeros..].to_vec()
+                );
+            }
+        }
+
+        #[test]
+        fn should_convert_long_decimal_numbers() {
+            let digits = format!("1{}", "0".repeat(1000));
+
+            let bytes = decimal_to_big_endian(digits.as_bytes());
+
+            // 10^1000 needs 3322 bits and it's a multiple of 2^1000
+            assert_eq!(bytes.len(), 416);
+            assert!(bytes[bytes.len() - 125..].iter().all(|&byte| byte == 0));
+        }
+
+        #[test]
+        fn should_use_the_smallest_cbor_encoding() {
+            assert_eq!(cbor("0"), vec![0x00]);
+            assert_eq!(cbor("23"), vec![0x17]);
+            assert_eq!(cbor("24"), vec![0x18, 0x18]);
+            assert_eq!(cbor("256"), vec![0x19, 0x01, 0x00]);
+            assert_eq!(cbor("-1"), vec![0x20]);
+            assert_eq!(cbor("-25"), vec![0x38, 0x18]);
+            assert_eq!(cbor("-0"), vec![0x00]);
+        }
+
+        #[test]
+        fn should_use_cbor_bignums_for_integers_that_do_not_fit_into_64_bits() {
+            assert_eq!(
+                cbor("18446744073709551615"),
+                vec![0x1B, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]
+            );
+            assert_eq!(
+                cbor("18446744073709551616"),
+                vec![0xC2, 0x49, 0x01, 0, 0, 0, 0, 0, 0, 0, 0]
+            );
+            assert_eq!(
+                cbor("-18446744073709551617"),
+                vec![0xC3, 0x49, 0x01, 0, 0, 0, 0, 0, 0, 0, 0]
+            );
+        }
+
+        #[test]
+        fn should_use_the_smallest_msgpack_encoding() {
+            assert_eq!(msgpack("127"), Some(vec![0x7F]));
+            assert_eq!(msgpack("128"), Some(vec![0xCC, 0x80]));
+            assert_eq!(msgpack("65536"), Some(vec![0xCE, 0x00, 0x01, 0x00, 0x00]));
+            assert_eq!(msgpack("-32"), Some(vec![0xE0]));
+            assert_eq!(msgpack("-33"), Some(vec![0xD0, 0xDF]));
+            assert_eq!(msgpack("-129"), Some(vec![0xD1, 0xFF, 0x7F]));
+            assert_eq!(
+                msgpack("-9223372036854775808"),
+                Some(vec![0xD3, 0x80, 0, 0, 0, 0, 0, 0, 0])
+            );
+        }
+
+        #[test]
+        fn should_not_encode_msgpack_integers_that_do_not_fit_into_64_bits() {
+            assert_eq!(msgpack("18446744073709551616"), None);
+            assert_eq!(msgpack("-9223372036854775809"), None);
+        }
+    }
+}
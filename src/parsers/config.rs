@@ -0,0 +1,113 @@
+//! Parser configuration.
+//!
+//! By default the parser accepts any valid bencoded input without limits.
+//! When the input comes from untrusted sources, like peers or trackers, you
+//! should limit the resources the parser can use:
+//!
+//! ```rust
+//! use torrust_bencode2json::parsers::{config::ParserConfig, BencodeParser};
+//!
+//! let config = ParserConfig::default()
+//!     .with_max_depth(32)
+//!     .with_max_string_len(1024 * 1024)
+//!     .with_max_integer_digits(20)
+//!     .with_max_input_bytes(10 * 1024 * 1024)
+//!     .with_max_tokens(100_000);
+//!
+//! let mut output = String::new();
+//!
+//! BencodeParser::with_config(&b"l4:spame"[..], config)
+//!     .write_str(&mut output)
+//!     .unwrap();
+//!
+//! assert_eq!(output, r#"["spam"]"#);
+//! ```
+
+/// Options for the [`Tokenizer`](super::tokenizer::Tokenizer) and all its
+/// consumers, like the [`BencodeParser`](super::BencodeParser).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParserConfig {
+    /// It rejects dictionaries with unsorted or duplicate keys.
+    pub(crate) strict: bool,
+
+    /// It accepts non-canonical integers and string lengths.
+    pub(crate) lenient: bool,
+
+    /// The maximum number of nested lists and dictionaries.
+    pub(crate) max_depth: Option<usize>,
+
+    /// The maximum number of bytes in a string.
+    pub(crate) max_string_len: Option<usize>,
+
+    /// The maximum number of digits in an integer, without the sign.
+    pub(crate) max_integer_digits: Option<usize>,
+
+    /// The maximum number of bytes read from the input.
+    pub(crate) max_input_bytes: Option<u64>,
+
+    /// The maximum number of tokens: integers, strings, and beginnings and
+    /// ends of lists and dictionaries.
+    pub(crate) max_tokens: Option<u64>,
+}
+
+impl ParserConfig {
+    /// It enables the strict mode, which rejects input that is not canonical
+    /// as required by [BEP 3](https://www.bittorrent.org/beps/bep_0003.html).
+    ///
+    /// Dictionary keys must be unique and sorted by their raw bytes.
+    #[must_use]
+    pub fn with_strict_mode(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    /// It enables the lenient mode, which accepts some non-canonical values
+    /// found in legacy input:
+    ///
+    /// - The negative zero: `i-0e`.
+    /// - Leading zeros in string lengths: `03:abc`.
+    #[must_use]
+    pub fn with_lenient_mode(mut self) -> Self {
+        self.lenient = true;
+        self
+    }
+
+    /// It limits the number of nested lists and dictionaries.
+    #[must_use]
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// It limits the number of bytes in a string. The limit is checked before
+    /// reading the string, so memory is not allocated for longer strings.
+    #[must_use]
+    pub fn with_max_string_len(mut self, max_string_len: usize) -> Self {
+        self.max_string_len = Some(max_string_len);
+        self
+    }
+
+    /// It limits the number of digits in an integer, without the sign. The
+    /// digits of an integer are kept in memory until the integer ends, so
+    /// without this limit they are only bounded by the input size.
+    #[must_use]
+    pub fn with_max_integer_digits(mut self, max_integer_digits: usize) -> Self {
+        self.max_integer_digits = Some(max_integer_digits);
+        self
+    }
+
+    /// It limits the number of bytes read from the input.
+    #[must_use]
+    pub fn with_max_input_bytes(mut self, max_input_bytes: u64) -> Self {
+        self.max_input_bytes = Some(max_input_bytes);
+        self
+    }
+
+    /// It limits the number of tokens: integers, strings, and beginnings and
+    /// ends of lists and dictionaries.
+    #[must_use]
+    pub fn with_max_tokens(mut self, max_tokens: u64) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+}
@@ -0,0 +1,382 @@
+//! Rich diagnostics for parser errors.
+//!
+//! The [`Display`](std::fmt::Display) of an [`Error`] includes the latest read
+//! bytes as a list of decimal numbers. A [`Diagnostic`] shows the same
+//! information in a readable way: the message, where the error is in the input
+//! and an `xxd`-style hex dump around the failing byte, with a caret under it.
+//!
+//! ```text
+//! Error: Unexpected byte parsing integer
+//!   --> input offset 6, path `[1]`
+//!   expected a digit or the end of the integer `e`, found 0x78 (`x`)
+//!
+//! 00000000: 6c69 3165 6932 7865                      li1ei2xe
+//!                          ^^                              ^
+//! ```
+//!
+//! The dump includes a few lines before the failing byte, and the bytes after
+//! it that the reader had already buffered.
+use std::fmt::{self, Write};
+
+use super::error::{Error, ReadContext};
+
+/// The number of bytes in each line of the dump.
+const BYTES_PER_LINE: u64 = 16;
+
+/// The number of lines before the line with the failing byte.
+const LINES_BEFORE: u64 = 2;
+
+/// The number of lines after the line with the failing byte.
+const LINES_AFTER: u64 = 1;
+
+/// The width of the offset at the beginning of each line: `00000000: `.
+const OFFSET_WIDTH: usize = 10;
+
+/// The width of the hex column: eight groups of two bytes.
+const HEX_WIDTH: usize = 39;
+
+/// The spaces between the hex and the ASCII columns.
+const COLUMN_SEPARATOR: &str = "  ";
+
+/// A human-readable rendering of a parser [`Error`].
+pub struct Diagnostic<'a> {
+    error: &'a Error,
+}
+
+impl<'a> Diagnostic<'a> {
+    #[must_use]
+    pub fn new(error: &'a Error) -> Self {
+        Self { error }
+    }
+}
+
+impl fmt::Display for Diagnostic<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Error: {}", self.error.message())?;
+
+        let Some(read_context) = self.error.read_context() else {
+            return Ok(());
+        };
+
+        let dump = HexDump::new(read_context);
+
+        write!(f, "\n  --> input offset {}", dump.caret)?;
+
+        if !read_context.path.is_empty() {
+            write!(f, ", path `{}`", read_context.path)?;
+        }
+
+        match (self.error.expected(), self.error.found()) {
+            (Some(expected), Some(found)) => write!(f, "\n  expected {expected}, found {found}")?,
+            (Some(expected), None) => write!(f, "\n  expected {expected}")?,
+            (None, Some(found)) => write!(f, "\n  found {found}")?,
+            (None, None) => {}
+        }
+
+        write!(f, "\n\n{dump}")
+    }
+}
+
+/// The window of input bytes around the failing byte.
+struct HexDump {
+    /// The bytes from the reader context: the latest read bytes followed by
+    /// the next buffered bytes.
+    bytes: Vec<u8>,
+
+    /// The input offset of the first byte.
+    first_offset: u64,
+
+    /// The input offset the caret points to.
+    caret: u64,
+}
+
+impl HexDump {
+    fn new(read_context: &ReadContext) -> Self {
+        let latest_len = read_context.latest_bytes.len() as u64;
+
+        let mut bytes = read_context.latest_bytes.clone();
+        bytes.extend_from_slice(&read_context.next_bytes);
+
+        Self {
+            bytes,
+            first_offset: read_context.pos.saturating_sub(latest_len),
+            caret: read_context.offset(),
+        }
+    }
+
+    fn byte_at(&self, offset: u64) -> Option<u8> {
+        let index = usize::try_from(offset.checked_sub(self.first_offset)?).ok()?;
+        self.bytes.get(index).copied()
+    }
+
+    fn end_offset(&self) -> u64 {
+        self.first_offset + self.bytes.len() as u64
+    }
+
+    /// It writes one line of the dump, with blanks for the bytes that are not
+    /// in the window.
+    fn write_line(&self, line_offset: u64, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut hex = String::with_capacity(HEX_WIDTH);
+        let mut ascii = String::with_capacity(16);
+
+        // The bytes are written in groups of two, like `xxd` does
+        for group_offset in (line_offset..line_offset + BYTES_PER_LINE).step_by(2) {
+            if group_offset > line_offset {
+                hex.push(' ');
+            }
+
+            for offset in [group_offset, group_offset + 1] {
+                if let Some(byte) = self.byte_at(offset) {
+                    write!(hex, "{byte:02x}")?;
+                    ascii.push(if byte.is_ascii_graphic() || byte == b' ' {
+                        byte as char
+                    } else {
+                        '.'
+                    });
+                } else {
+                    hex.push_str("  ");
+                    ascii.push(' ');
+                }
+            }
+        }
+
+        let line = format!("{line_offset:08x}: {hex}{COLUMN_SEPARATOR}{ascii}");
+
+        write!(f, "{}", line.trim_end())
+    }
+
+    /// It writes the carets under the failing byte, in the hex and the ASCII
+    /// columns.
+    fn write_caret(&self, line_offset: u64, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let index = usize::try_from(self.caret - line_offset).unwrap_or_default();
+
+        let hex_column = OFFSET_WIDTH + (index / 2) * 5 + (index % 2) * 2;
+        let ascii_column = OFFSET_WIDTH + HEX_WIDTH + COLUMN_SEPARATOR.len() + index;
+
+        write!(
+            f,
+            "{:hex_column$}^^{:gap$}^",
+            "",
+            "",
+            gap = ascii_column - hex_column - 2
+        )
+    }
+}
+
+impl fmt::Display for HexDump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let caret_line = self.caret - self.caret % BYTES_PER_LINE;
+
+        let first_line = caret_line
+            .saturating_sub(LINES_BEFORE * BYTES_PER_LINE)
+            .max(self.first_offset - self.first_offset % BYTES_PER_LINE);
+
+        let last_byte_line = match self.end_offset().checked_sub(1) {
+            Some(last_offset) => last_offset - last_offset % BYTES_PER_LINE,
+            None => 0,
+        };
+
+        let last_line = (caret_line + LINES_AFTER * BYTES_PER_LINE)
+            .min(last_byte_line)
+            .max(caret_line);
+
+        let mut line_offset = first_line;
+
+        while line_offset <= last_line {
+            if line_offset > first_line {
+                writeln!(f)?;
+            }
+
+            self.write_line(line_offset, f)?;
+
+            if line_offset == caret_line {
+                writeln!(f)?;
+                self.write_caret(line_offset, f)?;
+            }
+
+            line_offset += BYTES_PER_LINE;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        parsers::error::{Error, ReadContext, WriteContext},
+        rw, try_bencode_to_json,
+    };
+
+    fn diagnostic(input: &[u8]) -> String {
+        try_bencode_to_json(input)
+            .unwrap_err()
+            .diagnostic()
+            .to_string()
+    }
+
+    #[test]
+    fn it_should_point_to_the_failing_byte() {
+        assert_eq!(
+            diagnostic(b"li1ei2xe"),
+            "\
+Error: Unexpected byte parsing integer
+  --> input offset 6, path `[1]`
+  expected a digit or the end of the integer `e`, found 0x78 (`x`)
+
+00000000: 6c69 3165 6932 7865                      li1ei2xe
+                         ^^                              ^"
+        );
+    }
+
+    #[test]
+    fn it_should_point_to_the_position_after_the_end_of_the_input() {
+        assert_eq!(
+            diagnostic(b"i42"),
+            "\
+Error: Unexpected end of input parsing integer
+  --> input offset 3
+  expected the end of the integer `e`, found the end of the input
+
+00000000: 6934 32                                  i42
+                 ^^                                   ^"
+        );
+    }
+
+    #[test]
+    fn it_should_include_two_lines_before_and_one_line_after_the_failing_byte() {
+        let mut input = b"l".to_vec();
+        input.extend_from_slice(&b"i1e".repeat(20));
+        input.extend_from_slice(b"x");
+        input.extend_from_slice(&b"i2e".repeat(10));
+
+        assert_eq!(
+            diagnostic(&input),
+            "\
+Error: Unrecognized first byte for new bencoded value
+  --> input offset 61, path `[20]`
+  expected the first byte of a bencoded value: `i`, `l`, `d` or a digit, found 0x78 (`x`)
+
+00000010: 6931 6569 3165 6931 6569 3165 6931 6569  i1ei1ei1ei1ei1ei
+00000020: 3165 6931 6569 3165 6931 6569 3165 6931  1ei1ei1ei1ei1ei1
+00000030: 6569 3165 6931 6569 3165 6931 6578 6932  ei1ei1ei1ei1exi2
+                                          ^^                    ^
+00000040: 6569 3265 6932 6569 3265 6932 6569       ei2ei2ei2ei2ei"
+        );
+    }
+
+    #[test]
+    fn it_should_write_non_printable_bytes_as_dots() {
+        assert_eq!(
+            diagnostic(b"l3:\x00\xFF\n4:a"),
+            "\
+Error: Unexpected end of input parsing string value
+  --> input offset 9, path `[1]`
+  expected the rest of the string, found the end of the input
+
+00000000: 6c33 3a00 ff0a 343a 61                   l3:...4:a
+                                ^^                          ^"
+        );
+    }
+
+    #[test]
+    fn it_should_leave_blank_the_bytes_the_reader_did_not_keep() {
+        let error = Error::UnexpectedByteParsingInteger(
+            ReadContext {
+                byte: Some(b'x'),
+                pos: 0x25,
+                latest_bytes: b"i4x".to_vec(),
+                path: String::new(),
+                next_bytes: b"e".to_vec(),
+                token_start: None,
+            },
+            WriteContext {
+                byte: None,
+                pos: 0,
+                latest_bytes: vec![],
+            },
+        );
+
+        assert!(error.diagnostic().to_string().ends_with(
+            "\
+00000020:      6934 7865                             i4xe
+                    ^^                                 ^"
+        ));
+    }
+
+    mod for_errors_about_a_whole_token {
+        use crate::parsers::{
+            integer::{IntegerRange, OutOfRangeIntegers},
+            mode::OutputMode,
+            BencodeParser,
+        };
+
+        fn diagnostic(mut parser: BencodeParser<&[u8]>) -> String {
+            let mut output = String::new();
+
+            parser
+                .write_str(&mut output)
+                .unwrap_err()
+                .diagnostic()
+                .to_string()
+        }
+
+        #[test]
+        fn it_should_point_to_the_first_byte_of_the_trailing_data() {
+            let parser = BencodeParser::new(&b"i1ei2e"[..]);
+
+            assert!(diagnostic(parser).ends_with(
+                "\
+00000000: 6931 6569 3265                           i1ei2e
+                 ^^                                   ^"
+            ));
+        }
+
+        #[test]
+        fn it_should_point_to_the_first_byte_of_an_integer_out_of_range() {
+            let parser = BencodeParser::new(&b"li1ei9223372036854775808ee"[..])
+                .with_out_of_range_integers(OutOfRangeIntegers::Fail(IntegerRange::I64));
+
+            assert!(diagnostic(parser).ends_with(
+                "\
+00000000: 6c69 3165 6939 3232 3333 3732 3033 3638  li1ei92233720368
+                    ^^                                 ^
+00000010: 3534 3737 3538 3038 6565                 54775808ee"
+            ));
+        }
+
+        #[test]
+        fn it_should_point_to_the_first_byte_of_a_field_value_with_an_unexpected_type() {
+            let parser =
+                BencodeParser::new(&b"d5:filesi1ee"[..]).with_output_mode(OutputMode::Scrape);
+
+            assert!(diagnostic(parser).ends_with(
+                "\
+00000000: 6435 3a66 696c 6573 6931 6565            d5:filesi1ee
+                              ^^                           ^"
+            ));
+        }
+
+        #[test]
+        fn it_should_point_to_the_end_of_a_malformed_krpc_message() {
+            let parser =
+                BencodeParser::new(&b"d1:t2:aa1:y1:qe"[..]).with_output_mode(OutputMode::Krpc);
+
+            assert!(diagnostic(parser).ends_with(
+                "\
+00000000: 6431 3a74 323a 6161 313a 7931 3a71 65    d1:t2:aa1:y1:qe
+                                             ^^                  ^"
+            ));
+        }
+    }
+
+    #[test]
+    fn it_should_only_include_the_message_for_errors_without_input_context() {
+        let error = Error::Rw(rw::error::Error::Io(std::io::Error::other("broken pipe")));
+
+        assert_eq!(
+            error.diagnostic().to_string(),
+            "Error: R/W error: I/O error: broken pipe"
+        );
+    }
+}
@@ -1,15 +1,45 @@
 //! Parser errors.
+//!
+//! Errors are displayed as human-readable messages. They can also be
+//! serialized with serde into a machine-readable report. In the read context,
+//! `pos` is the number of bytes read, and `offset` is the position of the byte
+//! the error points to:
+//!
+//! ```rust
+//! use torrust_bencode2json::try_bencode_to_json;
+//!
+//! let error = try_bencode_to_json(b"i4a2e").unwrap_err();
+//!
+//! assert_eq!(
+//!     serde_json::to_value(&error).unwrap(),
+//!     serde_json::json!({
+//!         "kind": "unexpected_byte_parsing_integer",
+//!         "expected": "a digit or the end of the integer `e`",
+//!         "found": "0x61 (`a`)",
+//!         "read_context": {
+//!             "pos": 3,
+//!             "offset": 2,
+//!             "path": "",
+//!             "byte": "61",
+//!             "latest_bytes": "693461",
+//!             "next_bytes": "3265",
+//!         },
+//!         "write_context": { "pos": 0, "byte": "61", "latest_bytes": "" },
+//!     })
+//! );
+//! ```
 use core::str;
 use std::{
     fmt::{self},
     io,
 };
 
+use serde::{ser::SerializeStruct, Serialize, Serializer};
 use thiserror::Error;
 
 use crate::rw;
 
-use super::BencodeType;
+use super::{diagnostic::Diagnostic, BencodeType, OutputFormat};
 
 #[derive(Debug, Error)]
 pub enum Error {
@@ -25,6 +55,9 @@ pub enum Error {
     #[error("Unrecognized first byte for new bencoded value; {0}; {1}")]
     UnrecognizedFirstBencodeValueByte(ReadContext, WriteContext),
 
+    #[error("Unexpected end of input expecting a bencoded value; {0}; {1}")]
+    UnexpectedEndOfInputExpectingValue(ReadContext, WriteContext),
+
     // Integers
     #[error("Unexpected byte parsing integer; {0}; {1}")]
     UnexpectedByteParsingInteger(ReadContext, WriteContext),
@@ -35,10 +68,22 @@ pub enum Error {
     #[error("Leading zeros in integers are not allowed, for example b'i00e'; {0}; {1}")]
     LeadingZerosInIntegersNotAllowed(ReadContext, WriteContext),
 
+    #[error("Negative zero is not allowed, for example b'i-0e'; {0}; {1}")]
+    NegativeZeroNotAllowed(ReadContext, WriteContext),
+
+    #[error("Integer out of range, it does not fit into the allowed range; {0}; {1}")]
+    IntegerOutOfRange(ReadContext, WriteContext),
+
     // Strings
     #[error("Invalid string length byte, expected a digit; {0}; {1}")]
     InvalidStringLengthByte(ReadContext, WriteContext),
 
+    #[error("Leading zeros in string lengths are not allowed, for example b'03:abc'; {0}; {1}")]
+    LeadingZerosInStringLength(ReadContext, WriteContext),
+
+    #[error("String length overflow, it does not fit into a usize; {0}; {1}")]
+    StringLengthOverflow(ReadContext, WriteContext),
+
     #[error("Unexpected end of input parsing string length; {0}; {1}")]
     UnexpectedEndOfInputParsingStringLength(ReadContext, WriteContext),
 
@@ -74,11 +119,385 @@ pub enum Error {
     #[error("Expected string for dictionary field key, but got: {0}, {1}")]
     ExpectedStringForDictKeyGot(BencodeType, ReadContext, WriteContext),
 
+    #[error("Unsorted dictionary keys, keys must be sorted by their raw bytes; {0}; {1}")]
+    UnsortedDictKeys(ReadContext, WriteContext),
+
+    #[error("Duplicate dictionary key; {0}; {1}")]
+    DuplicateDictKey(ReadContext, WriteContext),
+
+    // Top-level values
+    #[error("Unexpected trailing data after the bencoded value; {0}; {1}")]
+    TrailingData(ReadContext, WriteContext),
+
     // List and dictionaries
     #[error(
         "Unexpected end of list or dict. No matching start for the list or dict end: {0}, {1}"
     )]
     NoMatchingStartForListOrDictEnd(ReadContext, WriteContext),
+
+    // Limits
+    #[error("Maximum depth of nested lists and dictionaries exceeded: {0}; {1}; {2}")]
+    MaxDepthExceeded(usize, ReadContext, WriteContext),
+
+    #[error("Maximum string length exceeded: {0}; {1}; {2}")]
+    MaxStringLengthExceeded(usize, ReadContext, WriteContext),
+
+    #[error("Maximum number of integer digits exceeded: {0}; {1}; {2}")]
+    MaxIntegerDigitsExceeded(usize, ReadContext, WriteContext),
+
+    #[error("Maximum number of input bytes exceeded: {0}; {1}; {2}")]
+    MaxInputBytesExceeded(u64, ReadContext, WriteContext),
+
+    #[error("Maximum number of tokens exceeded: {0}; {1}; {2}")]
+    MaxTokensExceeded(u64, ReadContext, WriteContext),
+
+    // Output modes
+    /// The value of a field does not have the type required by the output
+    /// mode. It contains the field key, the expected type and the type found.
+    #[error("Unexpected type for field `{0}`, expected {1}, got {2}; {3}; {4}")]
+    UnexpectedFieldType(String, BencodeType, BencodeType, ReadContext, WriteContext),
+
+    #[error("Malformed KRPC message, {0}; {1}; {2}")]
+    MalformedKrpcMessage(&'static str, ReadContext, WriteContext),
+
+    // Output formats
+    /// The output format is binary and the output only accepts UTF-8
+    /// strings.
+    #[error("The {0} output format is binary, it can only be written to a byte output")]
+    BinaryOutputFormat(OutputFormat),
+
+    /// A string, list or dictionary is too long for the `MessagePack` output
+    /// format. It contains the kind of value and its length.
+    #[error("The MessagePack {0} is too long: {1}, the maximum is 4294967295")]
+    MessagePackLengthOverflow(&'static str, u64),
+}
+
+impl Error {
+    /// A short code for the kind of error, which does not change between
+    /// versions, for example, `unexpected_byte_parsing_integer`.
+    #[must_use]
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Io(_) => "io",
+            Self::Rw(_) => "rw",
+            Self::ReadByteAfterPeekingDoesMatchPeekedByte(..) => {
+                "read_byte_after_peeking_does_match_peeked_byte"
+            }
+            Self::UnrecognizedFirstBencodeValueByte(..) => "unrecognized_first_bencode_value_byte",
+            Self::UnexpectedEndOfInputExpectingValue(..) => {
+                "unexpected_end_of_input_expecting_value"
+            }
+            Self::UnexpectedByteParsingInteger(..) => "unexpected_byte_parsing_integer",
+            Self::UnexpectedEndOfInputParsingInteger(..) => {
+                "unexpected_end_of_input_parsing_integer"
+            }
+            Self::LeadingZerosInIntegersNotAllowed(..) => "leading_zeros_in_integers_not_allowed",
+            Self::NegativeZeroNotAllowed(..) => "negative_zero_not_allowed",
+            Self::IntegerOutOfRange(..) => "integer_out_of_range",
+            Self::InvalidStringLengthByte(..) => "invalid_string_length_byte",
+            Self::LeadingZerosInStringLength(..) => "leading_zeros_in_string_length",
+            Self::StringLengthOverflow(..) => "string_length_overflow",
+            Self::UnexpectedEndOfInputParsingStringLength(..) => {
+                "unexpected_end_of_input_parsing_string_length"
+            }
+            Self::UnexpectedEndOfInputParsingStringValue(..) => {
+                "unexpected_end_of_input_parsing_string_value"
+            }
+            Self::UnexpectedEndOfInputExpectingFirstListItemOrEnd(..) => {
+                "unexpected_end_of_input_expecting_first_list_item_or_end"
+            }
+            Self::UnexpectedEndOfInputExpectingNextListItem(..) => {
+                "unexpected_end_of_input_expecting_next_list_item"
+            }
+            Self::UnexpectedEndOfInputExpectingFirstDictFieldOrEnd(..) => {
+                "unexpected_end_of_input_expecting_first_dict_field_or_end"
+            }
+            Self::UnexpectedEndOfInputExpectingDictFieldValue(..) => {
+                "unexpected_end_of_input_expecting_dict_field_value"
+            }
+            Self::UnexpectedEndOfInputExpectingDictFieldKeyOrEnd(..) => {
+                "unexpected_end_of_input_expecting_dict_field_key_or_end"
+            }
+            Self::PrematureEndOfDict(..) => "premature_end_of_dict",
+            Self::ExpectedStringForDictKeyGot(..) => "expected_string_for_dict_key",
+            Self::UnsortedDictKeys(..) => "unsorted_dict_keys",
+            Self::DuplicateDictKey(..) => "duplicate_dict_key",
+            Self::TrailingData(..) => "trailing_data",
+            Self::NoMatchingStartForListOrDictEnd(..) => "no_matching_start_for_list_or_dict_end",
+            Self::MaxDepthExceeded(..) => "max_depth_exceeded",
+            Self::MaxStringLengthExceeded(..) => "max_string_length_exceeded",
+            Self::MaxIntegerDigitsExceeded(..) => "max_integer_digits_exceeded",
+            Self::MaxInputBytesExceeded(..) => "max_input_bytes_exceeded",
+            Self::MaxTokensExceeded(..) => "max_tokens_exceeded",
+            Self::UnexpectedFieldType(..) => "unexpected_field_type",
+            Self::MalformedKrpcMessage(..) => "malformed_krpc_message",
+            Self::BinaryOutputFormat(..) => "binary_output_format",
+            Self::MessagePackLengthOverflow(..) => "message_pack_length_overflow",
+        }
+    }
+
+    /// What the parser expected when the error ocurred, if it's known.
+    #[must_use]
+    pub fn expected(&self) -> Option<String> {
+        let expected = match self {
+            Self::Io(_) | Self::Rw(_) => return None,
+            Self::ReadByteAfterPeekingDoesMatchPeekedByte(..) => "the peeked byte",
+            Self::UnrecognizedFirstBencodeValueByte(..) => {
+                "the first byte of a bencoded value: `i`, `l`, `d` or a digit"
+            }
+            Self::UnexpectedEndOfInputExpectingValue(..)
+            | Self::NoMatchingStartForListOrDictEnd(..) => "a bencoded value",
+            Self::UnexpectedByteParsingInteger(..) => "a digit or the end of the integer `e`",
+            Self::UnexpectedEndOfInputParsingInteger(..) => "the end of the integer `e`",
+            Self::LeadingZerosInIntegersNotAllowed(..) => "an integer without leading zeros",
+            Self::NegativeZeroNotAllowed(..) => "an integer other than `-0`",
+            Self::IntegerOutOfRange(..) => "an integer in the allowed range",
+            Self::InvalidStringLengthByte(..) => "a digit or the end of the string length `:`",
+            Self::LeadingZerosInStringLength(..) => "a string length without leading zeros",
+            Self::StringLengthOverflow(..) => "a string length that fits into a usize",
+            Self::UnexpectedEndOfInputParsingStringLength(..) => "the end of the string length `:`",
+            Self::UnexpectedEndOfInputParsingStringValue(..) => "the rest of the string",
+            Self::UnexpectedEndOfInputExpectingFirstListItemOrEnd(..) => {
+                "the first list item or the end of the list `e`"
+            }
+            Self::UnexpectedEndOfInputExpectingNextListItem(..) => {
+                "the next list item or the end of the list `e`"
+            }
+            Self::UnexpectedEndOfInputExpectingFirstDictFieldOrEnd(..) => {
+                "the first dictionary field or the end of the dictionary `e`"
+            }
+            Self::UnexpectedEndOfInputExpectingDictFieldValue(..)
+            | Self::PrematureEndOfDict(..) => "the value of the dictionary field",
+            Self::UnexpectedEndOfInputExpectingDictFieldKeyOrEnd(..) => {
+                "the next dictionary key or the end of the dictionary `e`"
+            }
+            Self::ExpectedStringForDictKeyGot(..) => "a string dictionary key",
+            Self::UnsortedDictKeys(..) => "a key greater than the previous key",
+            Self::DuplicateDictKey(..) => "a key different from the previous keys",
+            Self::TrailingData(..) => "the end of the input",
+            Self::MaxDepthExceeded(max, ..) => {
+                return Some(format!("at most {max} nested lists and dictionaries"))
+            }
+            Self::MaxStringLengthExceeded(max, ..) => {
+                return Some(format!("a string of at most {max} bytes"))
+            }
+            Self::MaxIntegerDigitsExceeded(max, ..) => {
+                return Some(format!("an integer of at most {max} digits"))
+            }
+            Self::MaxInputBytesExceeded(max, ..) => {
+                return Some(format!("at most {max} input bytes"))
+            }
+            Self::MaxTokensExceeded(max, ..) => return Some(format!("at most {max} tokens")),
+            Self::UnexpectedFieldType(key, expected, ..) => {
+                return Some(format!("{expected} for field `{key}`"))
+            }
+            Self::MalformedKrpcMessage(..) => "a valid KRPC message",
+            Self::BinaryOutputFormat(..) => "a byte output",
+            Self::MessagePackLengthOverflow(..) => "a length of at most 4294967295",
+        };
+
+        Some(expected.to_string())
+    }
+
+    /// What the parser found instead, if it's known. It's usually the read
+    /// byte that caused the error.
+    #[must_use]
+    pub fn found(&self) -> Option<String> {
+        if self.is_unexpected_end_of_input() {
+            return Some("the end of the input".to_string());
+        }
+
+        match self {
+            Self::Io(err) => Some(err.to_string()),
+            Self::Rw(err) => Some(err.to_string()),
+            Self::ExpectedStringForDictKeyGot(found, ..)
+            | Self::UnexpectedFieldType(_, _, found, ..) => Some(found.to_string()),
+            Self::MalformedKrpcMessage(reason, ..) => Some((*reason).to_string()),
+            Self::BinaryOutputFormat(_) => Some("a string output".to_string()),
+            Self::MessagePackLengthOverflow(kind, length) => Some(format!("a {kind} of {length}")),
+            _ => self
+                .read_context()
+                .and_then(|context| context.byte)
+                .map(describe_byte),
+        }
+    }
+
+    /// It returns true if the input ended before the bencoded value was
+    /// finished.
+    #[must_use]
+    pub fn is_unexpected_end_of_input(&self) -> bool {
+        matches!(
+            self,
+            Self::UnexpectedEndOfInputExpectingValue(..)
+                | Self::UnexpectedEndOfInputParsingInteger(..)
+                | Self::UnexpectedEndOfInputParsingStringLength(..)
+                | Self::UnexpectedEndOfInputParsingStringValue(..)
+                | Self::UnexpectedEndOfInputExpectingFirstListItemOrEnd(..)
+                | Self::UnexpectedEndOfInputExpectingNextListItem(..)
+                | Self::UnexpectedEndOfInputExpectingFirstDictFieldOrEnd(..)
+                | Self::UnexpectedEndOfInputExpectingDictFieldValue(..)
+                | Self::UnexpectedEndOfInputExpectingDictFieldKeyOrEnd(..)
+        )
+    }
+
+    /// The error message without the read and write contexts, for example,
+    /// `Unexpected byte parsing integer`.
+    #[must_use]
+    pub fn message(&self) -> String {
+        let mut message = self.to_string();
+
+        if let Some((read_context, write_context)) = self.contexts() {
+            // The contexts are always at the end of the message
+            for context in [write_context.to_string(), read_context.to_string()] {
+                if let Some(rest) = message.strip_suffix(&context) {
+                    let rest = rest.trim_end_matches([';', ',', ':', ' ']);
+                    message.truncate(rest.len());
+                }
+            }
+        }
+
+        message
+    }
+
+    /// A human-readable rendering of the error, with a hex dump of the input
+    /// around the failing byte. See [`Diagnostic`].
+    #[must_use]
+    pub fn diagnostic(&self) -> Diagnostic<'_> {
+        Diagnostic::new(self)
+    }
+
+    /// The reader context when the error ocurred. Only I/O errors do not
+    /// have it.
+    #[must_use]
+    pub fn read_context(&self) -> Option<&ReadContext> {
+        self.contexts().map(|(read_context, _)| read_context)
+    }
+
+    /// The writer context when the error ocurred. Only I/O errors do not
+    /// have it.
+    #[must_use]
+    pub fn write_context(&self) -> Option<&WriteContext> {
+        self.contexts().map(|(_, write_context)| write_context)
+    }
+
+    /// It sets the path of the read context when it's not set yet. Errors
+    /// are created without the path when the parser does not know it, for
+    /// example, when parsing an integer.
+    #[must_use]
+    pub(crate) fn with_path(mut self, path: impl FnOnce() -> String) -> Self {
+        if let Some(read_context) = self.read_context_mut() {
+            if read_context.path.is_empty() {
+                read_context.path = path();
+            }
+        }
+        self
+    }
+
+    fn read_context_mut(&mut self) -> Option<&mut ReadContext> {
+        match self {
+            Self::Io(_)
+            | Self::Rw(_)
+            | Self::BinaryOutputFormat(_)
+            | Self::MessagePackLengthOverflow(..) => None,
+            Self::ReadByteAfterPeekingDoesMatchPeekedByte(read_context, _)
+            | Self::UnrecognizedFirstBencodeValueByte(read_context, _)
+            | Self::UnexpectedEndOfInputExpectingValue(read_context, _)
+            | Self::UnexpectedByteParsingInteger(read_context, _)
+            | Self::UnexpectedEndOfInputParsingInteger(read_context, _)
+            | Self::LeadingZerosInIntegersNotAllowed(read_context, _)
+            | Self::NegativeZeroNotAllowed(read_context, _)
+            | Self::IntegerOutOfRange(read_context, _)
+            | Self::InvalidStringLengthByte(read_context, _)
+            | Self::LeadingZerosInStringLength(read_context, _)
+            | Self::StringLengthOverflow(read_context, _)
+            | Self::UnexpectedEndOfInputParsingStringLength(read_context, _)
+            | Self::UnexpectedEndOfInputParsingStringValue(read_context, _)
+            | Self::UnexpectedEndOfInputExpectingFirstListItemOrEnd(read_context, _)
+            | Self::UnexpectedEndOfInputExpectingNextListItem(read_context, _)
+            | Self::UnexpectedEndOfInputExpectingFirstDictFieldOrEnd(read_context, _)
+            | Self::UnexpectedEndOfInputExpectingDictFieldValue(read_context, _)
+            | Self::UnexpectedEndOfInputExpectingDictFieldKeyOrEnd(read_context, _)
+            | Self::PrematureEndOfDict(read_context, _)
+            | Self::ExpectedStringForDictKeyGot(_, read_context, _)
+            | Self::UnsortedDictKeys(read_context, _)
+            | Self::DuplicateDictKey(read_context, _)
+            | Self::TrailingData(read_context, _)
+            | Self::NoMatchingStartForListOrDictEnd(read_context, _)
+            | Self::MaxDepthExceeded(_, read_context, _)
+            | Self::MaxStringLengthExceeded(_, read_context, _)
+            | Self::MaxIntegerDigitsExceeded(_, read_context, _)
+            | Self::MaxInputBytesExceeded(_, read_context, _)
+            | Self::MaxTokensExceeded(_, read_context, _)
+            | Self::UnexpectedFieldType(_, _, _, read_context, _)
+            | Self::MalformedKrpcMessage(_, read_context, _) => Some(read_context),
+        }
+    }
+
+    fn contexts(&self) -> Option<(&ReadContext, &WriteContext)> {
+        match self {
+            Self::Io(_)
+            | Self::Rw(_)
+            | Self::BinaryOutputFormat(_)
+            | Self::MessagePackLengthOverflow(..) => None,
+            Self::ReadByteAfterPeekingDoesMatchPeekedByte(read_context, write_context)
+            | Self::UnrecognizedFirstBencodeValueByte(read_context, write_context)
+            | Self::UnexpectedEndOfInputExpectingValue(read_context, write_context)
+            | Self::UnexpectedByteParsingInteger(read_context, write_context)
+            | Self::UnexpectedEndOfInputParsingInteger(read_context, write_context)
+            | Self::LeadingZerosInIntegersNotAllowed(read_context, write_context)
+            | Self::NegativeZeroNotAllowed(read_context, write_context)
+            | Self::IntegerOutOfRange(read_context, write_context)
+            | Self::InvalidStringLengthByte(read_context, write_context)
+            | Self::LeadingZerosInStringLength(read_context, write_context)
+            | Self::StringLengthOverflow(read_context, write_context)
+            | Self::UnexpectedEndOfInputParsingStringLength(read_context, write_context)
+            | Self::UnexpectedEndOfInputParsingStringValue(read_context, write_context)
+            | Self::UnexpectedEndOfInputExpectingFirstListItemOrEnd(read_context, write_context)
+            | Self::UnexpectedEndOfInputExpectingNextListItem(read_context, write_context)
+            | Self::UnexpectedEndOfInputExpectingFirstDictFieldOrEnd(read_context, write_context)
+            | Self::UnexpectedEndOfInputExpectingDictFieldValue(read_context, write_context)
+            | Self::UnexpectedEndOfInputExpectingDictFieldKeyOrEnd(read_context, write_context)
+            | Self::PrematureEndOfDict(read_context, write_context)
+            | Self::ExpectedStringForDictKeyGot(_, read_context, write_context)
+            | Self::UnsortedDictKeys(read_context, write_context)
+            | Self::DuplicateDictKey(read_context, write_context)
+            | Self::TrailingData(read_context, write_context)
+            | Self::NoMatchingStartForListOrDictEnd(read_context, write_context)
+            | Self::MaxDepthExceeded(_, read_context, write_context)
+            | Self::MaxStringLengthExceeded(_, read_context, write_context)
+            | Self::MaxIntegerDigitsExceeded(_, read_context, write_context)
+            | Self::MaxInputBytesExceeded(_, read_context, write_context)
+            | Self::MaxTokensExceeded(_, read_context, write_context)
+            | Self::UnexpectedFieldType(_, _, _, read_context, write_context)
+            | Self::MalformedKrpcMessage(_, read_context, write_context) => {
+                Some((read_context, write_context))
+            }
+        }
+    }
+}
+
+/// It serializes the error as a report with the kind of error, the expected
+/// and found values, and the contexts.
+impl Serialize for Error {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut report = serializer.serialize_struct("Error", 5)?;
+        report.serialize_field("kind", self.kind())?;
+        report.serialize_field("expected", &self.expected())?;
+        report.serialize_field("found", &self.found())?;
+        report.serialize_field("read_context", &self.read_context())?;
+        report.serialize_field("write_context", &self.write_context())?;
+        report.end()
+    }
+}
+
+/// It describes a byte in hexadecimal, and as a char when it's a printable
+/// ASCII char: ``0x61 (`a`)``.
+fn describe_byte(byte: u8) -> String {
+    if byte.is_ascii_graphic() {
+        format!("0x{byte:02x} (`{}`)", byte as char)
+    } else {
+        format!("0x{byte:02x}")
+    }
 }
 
 /// The reader context when the error ocurred.
@@ -87,11 +506,53 @@ pub struct ReadContext {
     /// The read byte that caused the error if any.
     pub byte: Option<u8>,
 
-    /// The position of the read byte that caused the error.
+    /// The number of bytes read from the input when the error ocurred. The
+    /// position the error points to is returned by [`ReadContext::offset`].
     pub pos: u64,
 
     /// The latest bytes read from input.
     pub latest_bytes: Vec<u8>,
+
+    /// The path to the value being parsed, for example,
+    /// `info.files[127].path[2]`. It's empty at the top level.
+    pub path: String,
+
+    /// A few bytes after the position that have not been read yet. Only the
+    /// bytes already buffered are included, so it can be empty even when the
+    /// input has not ended.
+    pub next_bytes: Vec<u8>,
+
+    /// The position of the first byte of the token that caused the error,
+    /// when the error is about a whole token, for example, an integer out of
+    /// range or a dictionary key that is not sorted.
+    pub token_start: Option<u64>,
+}
+
+impl ReadContext {
+    /// The position in the input the error points to. It's the first byte of
+    /// the token that caused the error, the read byte that caused it, or the
+    /// end of the input when the input ended too soon.
+    #[must_use]
+    pub fn offset(&self) -> u64 {
+        match (self.token_start, self.byte) {
+            (Some(token_start), _) => token_start,
+            (None, Some(_)) => self.pos.saturating_sub(1),
+            (None, None) => self.pos,
+        }
+    }
+}
+
+impl Serialize for ReadContext {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut context = serializer.serialize_struct("ReadContext", 6)?;
+        context.serialize_field("pos", &self.pos)?;
+        context.serialize_field("offset", &self.offset())?;
+        context.serialize_field("path", &self.path)?;
+        context.serialize_field("byte", &self.byte.map(|byte| hex::encode([byte])))?;
+        context.serialize_field("latest_bytes", &hex::encode(&self.latest_bytes))?;
+        context.serialize_field("next_bytes", &hex::encode(&self.next_bytes))?;
+        context.end()
+    }
 }
 
 impl fmt::Display for ReadContext {
@@ -103,11 +564,13 @@ impl fmt::Display for ReadContext {
             Some(byte) => write!(f, " byte `{}` (char: `{}`),", byte, byte as char)?,
         }
 
-        write!(
-            f,
-            " input pos {}, latest input bytes dump: {:?}",
-            self.pos, self.latest_bytes
-        )?;
+        write!(f, " input pos {},", self.pos)?;
+
+        if !self.path.is_empty() {
+            write!(f, " path `{}`,", self.path)?;
+        }
+
+        write!(f, " latest input bytes dump: {:?}", self.latest_bytes)?;
 
         if let Ok(utf8_string) = str::from_utf8(&self.latest_bytes) {
             write!(f, " (UTF-8 string: `{utf8_string}`)")?;
@@ -130,6 +593,16 @@ pub struct WriteContext {
     pub latest_bytes: Vec<u8>,
 }
 
+impl Serialize for WriteContext {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut context = serializer.serialize_struct("WriteContext", 3)?;
+        context.serialize_field("pos", &self.pos)?;
+        context.serialize_field("byte", &self.byte.map(|byte| hex::encode([byte])))?;
+        context.serialize_field("latest_bytes", &hex::encode(&self.latest_bytes))?;
+        context.end()
+    }
+}
+
 impl fmt::Display for WriteContext {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "write context:")?;
@@ -156,6 +629,98 @@ impl fmt::Display for WriteContext {
 #[cfg(test)]
 mod tests {
 
+    mod for_error_reports {
+        use std::io;
+
+        use serde_json::json;
+
+        use crate::parsers::error::{Error, ReadContext, WriteContext};
+
+        fn contexts() -> (ReadContext, WriteContext) {
+            (
+                ReadContext {
+                    byte: None,
+                    pos: 4,
+                    latest_bytes: b"lli1".to_vec(),
+                    path: String::new(),
+                    next_bytes: vec![],
+                    token_start: None,
+                },
+                WriteContext {
+                    byte: None,
+                    pos: 4,
+                    latest_bytes: b"[[1".to_vec(),
+                },
+            )
+        }
+
+        #[test]
+        fn it_should_serialize_the_contexts_with_the_bytes_in_hexadecimal() {
+            let read_context = ReadContext {
+                byte: Some(0xFF),
+                pos: 10,
+                latest_bytes: vec![b'a', 0xFF],
+                path: "info.files[3]".to_string(),
+                next_bytes: vec![b'e'],
+                token_start: None,
+            };
+
+            assert_eq!(
+                serde_json::to_value(read_context).unwrap(),
+                json!({
+                    "pos": 10,
+                    "offset": 9,
+                    "path": "info.files[3]",
+                    "byte": "ff",
+                    "latest_bytes": "61ff",
+                    "next_bytes": "65",
+                })
+            );
+        }
+
+        #[test]
+        fn it_should_report_the_end_of_the_input_as_found() {
+            let (read_context, write_context) = contexts();
+
+            let error =
+                Error::UnexpectedEndOfInputExpectingNextListItem(read_context, write_context);
+
+            assert_eq!(
+                error.kind(),
+                "unexpected_end_of_input_expecting_next_list_item"
+            );
+            assert_eq!(error.found().unwrap(), "the end of the input");
+        }
+
+        #[test]
+        fn it_should_include_the_limit_in_the_expected_value() {
+            let (read_context, write_context) = contexts();
+
+            let error = Error::MaxDepthExceeded(1, read_context, write_context);
+
+            assert_eq!(
+                error.expected().unwrap(),
+                "at most 1 nested lists and dictionaries"
+            );
+        }
+
+        #[test]
+        fn it_should_serialize_errors_without_contexts() {
+            let error = Error::Io(io::Error::other("broken pipe"));
+
+            assert_eq!(
+                serde_json::to_value(error).unwrap(),
+                json!({
+                    "kind": "io",
+                    "expected": null,
+                    "found": "broken pipe",
+                    "read_context": null,
+                    "write_context": null,
+                })
+            );
+        }
+    }
+
     mod for_read_context {
         use crate::parsers::error::ReadContext;
 
@@ -165,6 +730,9 @@ mod tests {
                 byte: Some(b'a'),
                 pos: 10,
                 latest_bytes: vec![b'a', b'b', b'c'],
+                path: String::new(),
+                next_bytes: vec![],
+                token_start: None,
             };
 
             assert_eq!( read_context.to_string(),"read context: byte `97` (char: `a`), input pos 10, latest input bytes dump: [97, 98, 99] (UTF-8 string: `abc`)");
@@ -176,17 +744,37 @@ mod tests {
                 byte: None,
                 pos: 10,
                 latest_bytes: vec![b'a', b'b', b'c'],
+                path: String::new(),
+                next_bytes: vec![],
+                token_start: None,
             };
 
             assert_eq!(read_context.to_string(), "read context: input pos 10, latest input bytes dump: [97, 98, 99] (UTF-8 string: `abc`)");
         }
 
+        #[test]
+        fn it_should_display_the_path_if_it_is_not_empty() {
+            let read_context = ReadContext {
+                byte: None,
+                pos: 10,
+                latest_bytes: vec![b'a', b'b', b'c'],
+                path: "info.files[3]".to_string(),
+                next_bytes: vec![],
+                token_start: None,
+            };
+
+            assert_eq!(read_context.to_string(), "read context: input pos 10, path `info.files[3]`, latest input bytes dump: [97, 98, 99] (UTF-8 string: `abc`)");
+        }
+
         #[test]
         fn it_should_not_display_the_latest_bytes_as_string_if_it_is_not_a_valid_string() {
             let read_context = ReadContext {
                 byte: None,
                 pos: 10,
                 latest_bytes: vec![b'\xFF', b'\xFE'],
+                path: String::new(),
+                next_bytes: vec![],
+                token_start: None,
             };
 
             assert_eq!(
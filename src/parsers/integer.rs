@@ -6,10 +6,64 @@ use std::io::{self, Read};
 use crate::rw::{byte_reader::ByteReader, writer::Writer};
 
 use super::{
+    config::ParserConfig,
     error::{Error, ReadContext, WriteContext},
     BENCODE_END_INTEGER,
 };
 
+/// The largest integer that can be represented exactly by a double-precision
+/// float, which is how many JSON parsers read numbers: `2^53 - 1`.
+const MAX_JSON_SAFE_INTEGER: i64 = (1 << 53) - 1;
+
+/// The range of integers that are written as numbers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IntegerRange {
+    /// 64-bit signed integers.
+    #[default]
+    I64,
+
+    /// Integers that JavaScript and other JSON parsers using doubles read
+    /// without losing precision: from `-(2^53 - 1)` to `2^53 - 1`.
+    JsonSafe,
+}
+
+impl IntegerRange {
+    /// It returns true if the integer, given by its decimal digits with an
+    /// optional minus sign, is in the range.
+    #[must_use]
+    pub fn contains(self, digits: &[u8]) -> bool {
+        let Some(integer) = std::str::from_utf8(digits)
+            .ok()
+            .and_then(|digits| digits.parse::<i64>().ok())
+        else {
+            return false;
+        };
+
+        match self {
+            Self::I64 => true,
+            Self::JsonSafe => integer.unsigned_abs() <= MAX_JSON_SAFE_INTEGER.unsigned_abs(),
+        }
+    }
+}
+
+/// What the [`BencodeParser`](super::BencodeParser) does with integers out of
+/// an [`IntegerRange`]. Bencoded integers do not have a size limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutOfRangeIntegers {
+    /// All the integers are written as numbers with the same digits as in
+    /// the input, even when the consumer of the output can't read them.
+    #[default]
+    Passthrough,
+
+    /// Integers out of the range are written as strings with their decimal
+    /// digits: `"18446744073709551616"`.
+    String(IntegerRange),
+
+    /// Integers out of the range make the conversion fail with an
+    /// [`Error::IntegerOutOfRange`] error.
+    Fail(IntegerRange),
+}
+
 /// The current state parsing the integer.
 #[derive(PartialEq)]
 #[allow(clippy::enum_variant_names)]
@@ -32,8 +86,33 @@ enum StateExpecting {
 /// Will panic if we reach the end of the input without completing the integer
 /// (without reaching the end of the integer `e`).
 pub fn parse<R: Read, W: Writer>(reader: &mut ByteReader<R>, writer: &mut W) -> Result<(), Error> {
+    for byte in parse_digits(reader, writer, &ParserConfig::default())? {
+        writer.write_byte(byte)?;
+    }
+
+    Ok(())
+}
+
+/// It parses an integer bencoded value returning its digits, including the
+/// sign if the integer is negative.
+///
+/// The writer is only used to include the write context in errors.
+///
+/// The negative zero (`i-0e`) is only allowed in `lenient` mode, for legacy
+/// input.
+///
+/// # Errors
+///
+/// Will return an error if it can't read from the input or the integer is not
+/// a valid bencoded integer.
+pub fn parse_digits<R: Read, W: Writer>(
+    reader: &mut ByteReader<R>,
+    writer: &W,
+    config: &ParserConfig,
+) -> Result<Vec<u8>, Error> {
     let mut state = StateExpecting::Start;
     let mut first_digit_is_zero = false;
+    let mut digits = vec![];
 
     loop {
         let byte = next_byte(reader, writer)?;
@@ -47,11 +126,11 @@ pub fn parse<R: Read, W: Writer>(reader: &mut ByteReader<R>, writer: &mut W) ->
             }
             StateExpecting::DigitOrSign => {
                 if char == '-' {
-                    writer.write_byte(byte)?;
+                    digits.push(byte);
 
                     StateExpecting::DigitAfterSign
                 } else if char.is_ascii_digit() {
-                    writer.write_byte(byte)?;
+                    digits.push(byte);
 
                     if char == '0' {
                         first_digit_is_zero = true;
@@ -59,23 +138,12 @@ pub fn parse<R: Read, W: Writer>(reader: &mut ByteReader<R>, writer: &mut W) ->
 
                     StateExpecting::DigitOrEnd
                 } else {
-                    return Err(Error::UnexpectedByteParsingInteger(
-                        ReadContext {
-                            byte: Some(byte),
-                            pos: reader.input_byte_counter(),
-                            latest_bytes: reader.captured_bytes(),
-                        },
-                        WriteContext {
-                            byte: Some(byte),
-                            pos: writer.output_byte_counter(),
-                            latest_bytes: writer.captured_bytes(),
-                        },
-                    ));
+                    return Err(unexpected_byte(byte, reader, writer));
                 }
             }
             StateExpecting::DigitAfterSign => {
                 if char.is_ascii_digit() {
-                    writer.write_byte(byte)?;
+                    digits.push(byte);
 
                     if char == '0' {
                         first_digit_is_zero = true;
@@ -83,23 +151,12 @@ pub fn parse<R: Read, W: Writer>(reader: &mut ByteReader<R>, writer: &mut W) ->
 
                     StateExpecting::DigitOrEnd
                 } else {
-                    return Err(Error::UnexpectedByteParsingInteger(
-                        ReadContext {
-                            byte: Some(byte),
-                            pos: reader.input_byte_counter(),
-                            latest_bytes: reader.captured_bytes(),
-                        },
-                        WriteContext {
-                            byte: Some(byte),
-                            pos: writer.output_byte_counter(),
-                            latest_bytes: writer.captured_bytes(),
-                        },
-                    ));
+                    return Err(unexpected_byte(byte, reader, writer));
                 }
             }
             StateExpecting::DigitOrEnd => {
                 if char.is_ascii_digit() {
-                    writer.write_byte(byte)?;
+                    digits.push(byte);
 
                     if char == '0' && first_digit_is_zero {
                         return Err(Error::LeadingZerosInIntegersNotAllowed(
@@ -107,6 +164,9 @@ pub fn parse<R: Read, W: Writer>(reader: &mut ByteReader<R>, writer: &mut W) ->
                                 byte: Some(byte),
                                 pos: reader.input_byte_counter(),
                                 latest_bytes: reader.captured_bytes(),
+                                path: String::new(),
+                                next_bytes: reader.next_bytes(),
+                                token_start: None,
                             },
                             WriteContext {
                                 byte: Some(byte),
@@ -118,24 +178,110 @@ pub fn parse<R: Read, W: Writer>(reader: &mut ByteReader<R>, writer: &mut W) ->
 
                     StateExpecting::DigitOrEnd
                 } else if byte == BENCODE_END_INTEGER {
-                    return Ok(());
+                    if !config.lenient {
+                        check_negative_zero(&digits, reader, writer)?;
+                    }
+
+                    return Ok(digits);
                 } else {
-                    return Err(Error::UnexpectedByteParsingInteger(
-                        ReadContext {
-                            byte: Some(byte),
-                            pos: reader.input_byte_counter(),
-                            latest_bytes: reader.captured_bytes(),
-                        },
-                        WriteContext {
-                            byte: Some(byte),
-                            pos: writer.output_byte_counter(),
-                            latest_bytes: writer.captured_bytes(),
-                        },
-                    ));
+                    return Err(unexpected_byte(byte, reader, writer));
                 }
             }
         };
+
+        check_max_digits(&digits, byte, config, reader, writer)?;
+    }
+}
+
+/// It returns the error for a byte that is not allowed in the integer.
+fn unexpected_byte<R: Read, W: Writer>(byte: u8, reader: &ByteReader<R>, writer: &W) -> Error {
+    Error::UnexpectedByteParsingInteger(
+        ReadContext {
+            byte: Some(byte),
+            pos: reader.input_byte_counter(),
+            latest_bytes: reader.captured_bytes(),
+            path: String::new(),
+            next_bytes: reader.next_bytes(),
+            token_start: None,
+        },
+        WriteContext {
+            byte: Some(byte),
+            pos: writer.output_byte_counter(),
+            latest_bytes: writer.captured_bytes(),
+        },
+    )
+}
+
+/// It checks the integer does not have more digits than the maximum in the
+/// config. The sign is not counted.
+///
+/// # Errors
+///
+/// Will return an error if the integer has too many digits.
+fn check_max_digits<R: Read, W: Writer>(
+    digits: &[u8],
+    byte: u8,
+    config: &ParserConfig,
+    reader: &ByteReader<R>,
+    writer: &W,
+) -> Result<(), Error> {
+    let Some(max_integer_digits) = config.max_integer_digits else {
+        return Ok(());
+    };
+
+    let sign_len = usize::from(digits.first() == Some(&b'-'));
+
+    if digits.len() - sign_len <= max_integer_digits {
+        return Ok(());
+    }
+
+    Err(Error::MaxIntegerDigitsExceeded(
+        max_integer_digits,
+        ReadContext {
+            byte: Some(byte),
+            pos: reader.input_byte_counter(),
+            latest_bytes: reader.captured_bytes(),
+            path: String::new(),
+            next_bytes: reader.next_bytes(),
+            token_start: None,
+        },
+        WriteContext {
+            byte: Some(byte),
+            pos: writer.output_byte_counter(),
+            latest_bytes: writer.captured_bytes(),
+        },
+    ))
+}
+
+/// It checks the finished integer is not a negative zero: `i-0e`.
+///
+/// # Errors
+///
+/// Will return an error if the integer is a negative zero.
+fn check_negative_zero<R: Read, W: Writer>(
+    digits: &[u8],
+    reader: &ByteReader<R>,
+    writer: &W,
+) -> Result<(), Error> {
+    if digits != b"-0" {
+        return Ok(());
     }
+
+    Err(Error::NegativeZeroNotAllowed(
+        ReadContext {
+            byte: Some(BENCODE_END_INTEGER),
+            pos: reader.input_byte_counter(),
+            latest_bytes: reader.captured_bytes(),
+            path: String::new(),
+            next_bytes: reader.next_bytes(),
+            token_start: None,
+        },
+        WriteContext {
+            byte: Some(BENCODE_END_INTEGER),
+            pos: writer.output_byte_counter(),
+            latest_bytes: writer.captured_bytes(),
+        },
+    ))
 }
 
 /// It reads the next byte from the input.
@@ -153,6 +299,9 @@ fn next_byte<R: Read, W: Writer>(reader: &mut ByteReader<R>, writer: &W) -> Resu
                         byte: None,
                         pos: reader.input_byte_counter(),
                         latest_bytes: reader.captured_bytes(),
+                        path: String::new(),
+                        next_bytes: reader.next_bytes(),
+                        token_start: None,
                     },
                     WriteContext {
                         byte: None,
@@ -169,7 +318,11 @@ fn next_byte<R: Read, W: Writer>(reader: &mut ByteReader<R>, writer: &W) -> Resu
 #[cfg(test)]
 mod tests {
     use crate::{
-        parsers::{error::Error, integer::parse},
+        parsers::{
+            config::ParserConfig,
+            error::Error,
+            integer::{parse, parse_digits},
+        },
         rw::{byte_reader::ByteReader, string_writer::StringWriter},
     };
 
@@ -232,6 +385,96 @@ mod tests {
         assert_eq!(bencode_to_json_unchecked(b"i-1e"), "-1".to_string());
     }
 
+    #[test]
+    fn it_should_allow_parsing_the_digits_without_writing_them() {
+        let mut reader = ByteReader::new(&b"i-42e"[..]);
+
+        let mut output = String::new();
+        let writer = StringWriter::new(&mut output);
+
+        assert_eq!(
+            parse_digits(&mut reader, &writer, &ParserConfig::default()).unwrap(),
+            b"-42".to_vec()
+        );
+    }
+
+    #[test]
+    fn it_should_allow_a_negative_zero_in_lenient_mode() {
+        let mut reader = ByteReader::new(&b"i-0e"[..]);
+
+        let mut output = String::new();
+        let writer = StringWriter::new(&mut output);
+
+        assert_eq!(
+            parse_digits(
+                &mut reader,
+                &writer,
+                &ParserConfig::default().with_lenient_mode()
+            )
+            .unwrap(),
+            b"-0".to_vec()
+        );
+    }
+
+    #[test]
+    fn it_should_allow_integers_with_the_maximum_number_of_digits() {
+        let mut reader = ByteReader::new(&b"i-123e"[..]);
+
+        let mut output = String::new();
+        let writer = StringWriter::new(&mut output);
+
+        assert_eq!(
+            parse_digits(
+                &mut reader,
+                &writer,
+                &ParserConfig::default().with_max_integer_digits(3)
+            )
+            .unwrap(),
+            b"-123".to_vec()
+        );
+    }
+
+    #[test]
+    fn it_should_fail_at_the_first_digit_over_the_maximum_number_of_digits() {
+        let mut reader = ByteReader::new(&b"i12345e"[..]);
+
+        let mut output = String::new();
+        let writer = StringWriter::new(&mut output);
+
+        let result = parse_digits(
+            &mut reader,
+            &writer,
+            &ParserConfig::default().with_max_integer_digits(3),
+        );
+
+        assert!(matches!(
+            result,
+            Err(Error::MaxIntegerDigitsExceeded(3, read_context, _))
+                if read_context.byte == Some(b'4') && read_context.pos == 5
+        ));
+    }
+
+    mod integer_range {
+        use crate::parsers::integer::IntegerRange;
+
+        #[test]
+        fn i64_should_contain_only_64_bit_signed_integers() {
+            assert!(IntegerRange::I64.contains(i64::MAX.to_string().as_bytes()));
+            assert!(IntegerRange::I64.contains(i64::MIN.to_string().as_bytes()));
+            assert!(!IntegerRange::I64.contains(u64::MAX.to_string().as_bytes()));
+            assert!(!IntegerRange::I64.contains(format!("{}1", i64::MIN).as_bytes()));
+        }
+
+        #[test]
+        fn json_safe_should_contain_only_integers_represented_exactly_by_doubles() {
+            assert!(IntegerRange::JsonSafe.contains(b"9007199254740991"));
+            assert!(IntegerRange::JsonSafe.contains(b"-9007199254740991"));
+            assert!(!IntegerRange::JsonSafe.contains(b"9007199254740992"));
+            assert!(!IntegerRange::JsonSafe.contains(b"-9007199254740992"));
+            assert!(!IntegerRange::JsonSafe.contains(i64::MIN.to_string().as_bytes()));
+        }
+    }
+
     mod it_should_fail {
         use std::io::{self, Read};
 
@@ -281,6 +524,13 @@ mod tests {
             ));
         }
 
+        #[test]
+        fn when_it_finds_a_negative_zero() {
+            let result = try_bencode_to_json(b"i-0e");
+
+            assert!(matches!(result, Err(Error::NegativeZeroNotAllowed { .. })));
+        }
+
         #[test]
         fn when_it_finds_leading_zeros_in_a_negative_integer() {
             // Leading zeros are not allowed.Only the zero integer can start with zero.
@@ -1,29 +1,46 @@
 //! Parsers, including the main parser and the parsers for the basic types
 //! (integer and string)
+pub mod binary;
+pub mod config;
+pub mod diagnostic;
 pub mod error;
 pub mod integer;
+pub mod mode;
 pub mod stack;
 pub mod string;
+pub mod tokenizer;
+pub mod yaml;
 
 use std::{
     fmt::Write as FmtWrite,
-    io::{self, Read, Write as IoWrite},
+    io::{BufReader, Read, Write as IoWrite},
 };
 
+use binary::{BinaryEmitter, BinaryFormat};
+use config::ParserConfig;
 use derive_more::derive::Display;
-use error::{ReadContext, WriteContext};
-use stack::{Stack, State};
+use integer::OutOfRangeIntegers;
+use mode::{FormattedStringEncoder, KrpcMessage, OutputMode, StringFormat};
+use stack::State;
+use string::{StringEncoding, STRING_CHUNK_SIZE};
+use tokenizer::{Token, Tokenizer};
+use yaml::YamlEmitter;
 
 use crate::rw::{
-    byte_reader::ByteReader, byte_writer::ByteWriter, string_writer::StringWriter, writer::Writer,
+    byte_writer::ByteWriter,
+    pretty_writer::{PrettyOptions, PrettyWriter},
+    string_writer::StringWriter,
+    writer::Writer,
 };
+use error::{ReadContext, WriteContext};
 
 // Bencoded reserved bytes
-const BENCODE_BEGIN_INTEGER: u8 = b'i';
-const BENCODE_END_INTEGER: u8 = b'e';
-const BENCODE_BEGIN_LIST: u8 = b'l';
-const BENCODE_BEGIN_DICT: u8 = b'd';
-const BENCODE_END_LIST_OR_DICT: u8 = b'e';
+pub(crate) const BENCODE_BEGIN_INTEGER: u8 = b'i';
+pub(crate) const BENCODE_END_INTEGER: u8 = b'e';
+pub(crate) const BENCODE_END_STRING_LENGTH: u8 = b':';
+pub(crate) const BENCODE_BEGIN_LIST: u8 = b'l';
+pub(crate) const BENCODE_BEGIN_DICT: u8 = b'd';
+pub(crate) const BENCODE_END_LIST_OR_DICT: u8 = b'e';
 
 #[derive(Debug, PartialEq, Display)]
 pub enum BencodeType {
@@ -33,10 +50,83 @@ pub enum BencodeType {
     Dict,
 }
 
+/// How the parser writes the input when it contains more than one top-level
+/// bencoded value, for example, `i1ei2e`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MultiValueFormat {
+    /// Only one value is allowed. Trailing data after the first value, except
+    /// line breaks, makes the conversion fail with an
+    /// [`Error::TrailingData`](error::Error::TrailingData) error.
+    #[default]
+    Single,
+
+    /// Each value is written as a JSON document followed by a line break:
+    /// [NDJSON](https://github.com/ndjson/ndjson-spec).
+    Ndjson,
+
+    /// All the values are written in a JSON array: `[1,2]`.
+    Array,
+}
+
+/// The output format.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Display)]
+pub enum OutputFormat {
+    #[default]
+    Json,
+
+    /// Block-style [YAML](https://yaml.org/spec/1.2.2/), which is easier to
+    /// review. See the [`yaml`] module.
+    Yaml,
+
+    /// Binary [CBOR](https://www.rfc-editor.org/rfc/rfc8949.html), with
+    /// bencoded strings as byte strings. See the [`binary`] module.
+    Cbor,
+
+    /// Binary [MessagePack](https://msgpack.org/), with bencoded strings as
+    /// `bin` strings. Lists and dictionaries are kept in memory until they
+    /// end. See the [`binary`] module.
+    MessagePack,
+}
+
+impl OutputFormat {
+    /// The binary formats can only be written with
+    /// [`BencodeParser::write_bytes`] or [`BencodeParser::parse_one`].
+    #[must_use]
+    pub fn is_binary(self) -> bool {
+        self.binary_format().is_some()
+    }
+
+    fn binary_format(self) -> Option<BinaryFormat> {
+        match self {
+            Self::Json | Self::Yaml => None,
+            Self::Cbor => Some(BinaryFormat::Cbor),
+            Self::MessagePack => Some(BinaryFormat::MessagePack),
+        }
+    }
+}
+
+/// It converts bencoded bytes into JSON.
+///
+/// It's a consumer of the [`Tokenizer`] that writes each token as JSON.
 pub struct BencodeParser<R: Read> {
-    byte_reader: ByteReader<R>,
-    num_processed_tokens: u64,
-    stack: Stack,
+    tokenizer: Tokenizer<R>,
+    string_encoding: StringEncoding,
+    output_mode: OutputMode,
+    multi_value_format: MultiValueFormat,
+    output_format: OutputFormat,
+    out_of_range_integers: OutOfRangeIntegers,
+
+    /// The options to pretty-print the JSON. It's compact when `None`.
+    pretty_options: Option<PrettyOptions>,
+
+    /// It writes the delimiters in the YAML output format.
+    yaml: YamlEmitter,
+
+    /// It writes the tokens in the binary output formats.
+    binary: Option<BinaryEmitter>,
+
+    /// The KRPC message being parsed in the KRPC mode.
+    krpc_message: Option<KrpcMessage>,
 }
 
 impl<R: Read> BencodeParser<R> {
@@ -50,28 +140,167 @@ impl<R: Read> BencodeParser<R> {
     const JSON_OBJ_END: u8 = b'}';
 
     pub fn new(reader: R) -> Self {
+        Self::with_config(reader, ParserConfig::default())
+    }
+
+    /// It creates a parser with the given options, for example, to limit the
+    /// resources used when parsing untrusted input.
+    #[must_use]
+    pub fn with_config(reader: R, config: ParserConfig) -> Self {
         BencodeParser {
-            byte_reader: ByteReader::new(reader),
-            num_processed_tokens: 1,
-            stack: Stack::default(),
+            tokenizer: Tokenizer::with_config(reader, config),
+            string_encoding: StringEncoding::default(),
+            output_mode: OutputMode::default(),
+            multi_value_format: MultiValueFormat::default(),
+            output_format: OutputFormat::default(),
+            out_of_range_integers: OutOfRangeIntegers::default(),
+            pretty_options: None,
+            yaml: YamlEmitter::default(),
+            binary: None,
+            krpc_message: None,
         }
     }
 
+    /// It sets how strings that are not valid UTF-8 are written. By default,
+    /// they are written in hexadecimal inside a wrapper: `"<hex>fffe</hex>"`.
+    #[must_use]
+    pub fn with_string_encoding(mut self, string_encoding: StringEncoding) -> Self {
+        self.string_encoding = string_encoding;
+        self
+    }
+
+    /// It sets the output mode, which writes some fields of well-known
+    /// bencoded data in a more readable way. For example, the hashes in the
+    /// `piece layers` of a torrent are written in hexadecimal with
+    /// [`OutputMode::Torrent`].
+    #[must_use]
+    pub fn with_output_mode(mut self, output_mode: OutputMode) -> Self {
+        self.output_mode = output_mode;
+        self
+    }
+
+    /// It sets how the input is written when it contains more than one
+    /// top-level value. By default, only one value is allowed.
+    #[must_use]
+    pub fn with_multi_value_format(mut self, multi_value_format: MultiValueFormat) -> Self {
+        self.multi_value_format = multi_value_format;
+        self
+    }
+
+    /// It sets the output format. By default, it's compact JSON.
+    ///
+    /// ```rust
+    /// use torrust_bencode2json::parsers::{BencodeParser, OutputFormat};
+    ///
+    /// let mut output = String::new();
+    ///
+    /// BencodeParser::new(&b"d4:listli1ei2ee4:spam4:eggse"[..])
+    ///     .with_output_format(OutputFormat::Yaml)
+    ///     .write_str(&mut output)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(output, "list:\n  - 1\n  - 2\nspam: \"eggs\"\n");
+    /// ```
+    #[must_use]
+    pub fn with_output_format(mut self, output_format: OutputFormat) -> Self {
+        self.output_format = output_format;
+        self
+    }
+
+    /// It sets what to do with integers that the consumer of the output may
+    /// not be able to read. By default, all the integers are written as
+    /// numbers with the same digits as in the input.
+    ///
+    /// ```rust
+    /// use torrust_bencode2json::parsers::{
+    ///     integer::{IntegerRange, OutOfRangeIntegers},
+    ///     BencodeParser,
+    /// };
+    ///
+    /// let mut output = String::new();
+    ///
+    /// BencodeParser::new(&b"li42ei18446744073709551616ee"[..])
+    ///     .with_out_of_range_integers(OutOfRangeIntegers::String(IntegerRange::I64))
+    ///     .write_str(&mut output)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(output, r#"[42,"18446744073709551616"]"#);
+    /// ```
+    #[must_use]
+    pub fn with_out_of_range_integers(mut self, out_of_range_integers: OutOfRangeIntegers) -> Self {
+        self.out_of_range_integers = out_of_range_integers;
+        self
+    }
+
+    /// It pretty-prints the JSON with line breaks and indentation. The output
+    /// is still written while the input is read. It's ignored in the
+    /// [`OutputFormat::Yaml`] format, which is always indented.
+    ///
+    /// ```rust
+    /// use torrust_bencode2json::{
+    ///     parsers::BencodeParser,
+    ///     rw::pretty_writer::PrettyOptions,
+    /// };
+    ///
+    /// let mut output = String::new();
+    ///
+    /// BencodeParser::new(&b"d4:listli1ei2ee4:spam4:eggse"[..])
+    ///     .with_pretty_print(PrettyOptions::default())
+    ///     .write_str(&mut output)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(output, "{\n  \"list\": [1, 2],\n  \"spam\": \"eggs\"\n}");
+    /// ```
+    #[must_use]
+    pub fn with_pretty_print(mut self, options: PrettyOptions) -> Self {
+        self.pretty_options = Some(options);
+        self
+    }
+
+    /// It enables the strict mode, which rejects input that is not canonical
+    /// as required by [BEP 3](https://www.bittorrent.org/beps/bep_0003.html).
+    ///
+    /// Dictionary keys must be unique and sorted by their raw bytes. For
+    /// example, `d1:bi1e1:ai2ee` is rejected.
+    #[must_use]
+    pub fn strict(mut self) -> Self {
+        self.tokenizer = self.tokenizer.strict();
+        self
+    }
+
+    /// It enables the lenient mode, which accepts some non-canonical values
+    /// found in legacy input, like the negative zero `i-0e` or leading zeros
+    /// in string lengths `03:abc`.
+    #[must_use]
+    pub fn lenient(mut self) -> Self {
+        self.tokenizer = self.tokenizer.lenient();
+        self
+    }
+
     /// It parses a bencoded value read from input and writes the corresponding
     /// JSON UTF-8 string value to the output.
     ///
     /// # Errors
     ///
-    /// Will return an error if it can't read from the input or write to the
-    /// output.
-    ///
-    /// # Panics
+    /// Will return an error if:
     ///
-    /// Will panic if receives a byte that isn't a valid begin or end of a
-    /// bencoded type: integer, string, list or dictionary.
+    /// - It can't read from the input or write to the output.
+    /// - The input is not valid bencode, or it does not match the selected
+    ///   output mode or the limits in the config.
+    /// - The output format is binary, which can't be written to a string:
+    ///   [`Error::BinaryOutputFormat`](error::Error::BinaryOutputFormat). Use
+    ///   [`BencodeParser::write_bytes`] instead.
     pub fn write_str<W: FmtWrite>(&mut self, writer: W) -> Result<(), error::Error> {
+        if self.output_format.is_binary() {
+            return Err(error::Error::BinaryOutputFormat(self.output_format));
+        }
+
         let mut writer = StringWriter::new(writer);
-        self.parse(&mut writer)
+
+        match self.json_pretty_options() {
+            Some(options) => self.parse(&mut PrettyWriter::new(writer, options)),
+            None => self.parse(&mut writer),
+        }
     }
 
     /// It parses a bencoded value read from input and writes the corresponding
@@ -79,20 +308,113 @@ impl<R: Read> BencodeParser<R> {
     ///
     /// # Errors
     ///
-    /// Will return an error if it can't read from the input or write to the
-    /// output.
-    ///
-    /// # Panics
+    /// Will return an error if:
     ///
-    /// Will panic if receives a byte that isn't a valid begin or end of a
-    /// bencoded type: integer, string, list or dictionary.
+    /// - It can't read from the input or write to the output.
+    /// - The input is not valid bencode, or it does not match the selected
+    ///   output mode or the limits in the config.
     pub fn write_bytes<W: IoWrite>(&mut self, writer: W) -> Result<(), error::Error> {
         let mut writer = ByteWriter::new(writer);
-        self.parse(&mut writer)
+
+        match self.json_pretty_options() {
+            Some(options) => self.parse(&mut PrettyWriter::new(writer, options)),
+            None => self.parse(&mut writer),
+        }
     }
 
-    /// It parses a bencoded value read from input and writes the corresponding
-    /// JSON value to the output.
+    /// It parses only the next bencoded value read from input and writes the
+    /// corresponding JSON value to the output. It returns the number of bytes
+    /// consumed from the input, including the line breaks before the value.
+    ///
+    /// It stops right after the end of the value, so the input can contain
+    /// other data after it. For example, the raw piece data that follows the
+    /// dictionary in the `ut_metadata` messages of
+    /// [BEP 9](https://www.bittorrent.org/beps/bep_0009.html). The rest of the
+    /// input can be read with [`BencodeParser::into_inner`], or parsed with
+    /// another call to this function.
+    ///
+    /// ```rust
+    /// use std::io::Read;
+    ///
+    /// use torrust_bencode2json::parsers::BencodeParser;
+    ///
+    /// let mut parser = BencodeParser::new(&b"d5:piecei0eeRAW PIECE DATA"[..]);
+    ///
+    /// let mut output = Vec::new();
+    ///
+    /// let consumed = parser.parse_one(&mut output).unwrap();
+    ///
+    /// let mut rest = String::new();
+    /// parser.into_inner().read_to_string(&mut rest).unwrap();
+    ///
+    /// assert_eq!(output, br#"{"piece":0}"#);
+    /// assert_eq!(consumed, 12);
+    /// assert_eq!(rest, "RAW PIECE DATA");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if:
+    ///
+    /// - It can't read from the input or write to the output.
+    /// - The input is invalid Bencode.
+    /// - The input ends before the value, or before the end of the value.
+    pub fn parse_one<W: IoWrite>(&mut self, writer: W) -> Result<u64, error::Error> {
+        let mut writer = ByteWriter::new(writer);
+
+        match self.json_pretty_options() {
+            Some(options) => self.parse_next_value(&mut PrettyWriter::new(writer, options)),
+            None => self.parse_next_value(&mut writer),
+        }
+    }
+
+    /// It parses only the next bencoded value. See [`BencodeParser::parse_one`].
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the input does not contain a complete value.
+    fn parse_next_value<W: Writer>(&mut self, writer: &mut W) -> Result<u64, error::Error> {
+        self.binary = self.output_format.binary_format().map(BinaryEmitter::new);
+
+        let start = self.tokenizer.input_byte_counter();
+
+        loop {
+            if !self.write_next_token(writer)? {
+                return Err(error::Error::UnexpectedEndOfInputExpectingValue(
+                    self.read_context(),
+                    Self::write_context(writer),
+                ));
+            }
+
+            // The stack returns to the initial state when the value ends
+            if self.tokenizer.state() == State::Initial {
+                return Ok(self.tokenizer.input_byte_counter() - start);
+            }
+        }
+    }
+
+    /// It returns the input reader. After [`BencodeParser::parse_one`], it's
+    /// positioned right after the parsed value.
+    ///
+    /// The input is buffered, so the original reader could have been read
+    /// beyond that position. That's why the buffered reader is returned.
+    #[must_use]
+    pub fn into_inner(self) -> BufReader<R> {
+        self.tokenizer.into_inner()
+    }
+
+    /// It returns the options to pretty-print the output, which only apply to
+    /// JSON.
+    fn json_pretty_options(&self) -> Option<PrettyOptions> {
+        match self.output_format {
+            OutputFormat::Json => self.pretty_options,
+            OutputFormat::Yaml | OutputFormat::Cbor | OutputFormat::MessagePack => None,
+        }
+    }
+
+    /// It parses the bencoded values read from input and writes the
+    /// corresponding JSON values to the output, in the selected
+    /// [`MultiValueFormat`].
     ///
     /// # Errors
     ///
@@ -100,424 +422,1192 @@ impl<R: Read> BencodeParser<R> {
     ///
     /// - It can't read from the input or write to the output.
     /// - The input is invalid Bencode.
+    /// - The input contains more than one value in the
+    ///   [`MultiValueFormat::Single`] format.
     fn parse<W: Writer>(&mut self, writer: &mut W) -> Result<(), error::Error> {
-        while let Some(peeked_byte) = Self::peek_byte(&mut self.byte_reader, writer)? {
-            match peeked_byte {
-                BENCODE_BEGIN_INTEGER => {
-                    self.begin_bencoded_value(BencodeType::Integer, writer)?;
-                    integer::parse(&mut self.byte_reader, writer)?;
+        let is_array = self.multi_value_format == MultiValueFormat::Array;
+
+        self.yaml = YamlEmitter::new(is_array);
+        self.binary = self.output_format.binary_format().map(BinaryEmitter::new);
+
+        if is_array {
+            match &mut self.binary {
+                Some(binary) => binary.begin_list_or_dict(false, writer)?,
+                None if self.output_format == OutputFormat::Json => {
+                    writer.write_byte(Self::JSON_ARRAY_BEGIN)?;
                 }
-                b'0'..=b'9' => {
-                    self.begin_bencoded_value(BencodeType::String, writer)?;
-                    string::parse(&mut self.byte_reader, writer)?;
+                None => {}
+            }
+        }
+
+        let mut values: u64 = 0;
+
+        loop {
+            if values > 0 && self.tokenizer.depth() == 0 {
+                if !self.tokenizer.has_next_token(writer)? {
+                    break;
                 }
-                BENCODE_BEGIN_LIST => {
-                    let _byte = Self::read_peeked_byte(peeked_byte, &mut self.byte_reader, writer)?;
-                    self.begin_bencoded_value(BencodeType::List, writer)?;
-                    writer.write_byte(Self::JSON_ARRAY_BEGIN)?;
-                    self.stack.push(State::ExpectingFirstListItemOrEnd);
+
+                self.begin_next_top_level_value(writer)?;
+            }
+
+            if !self.write_next_token(writer)? {
+                break;
+            }
+
+            if self.tokenizer.depth() == 0 {
+                values += 1;
+
+                // YAML documents always end with a line break. Binary
+                // values do not need a separator.
+                let has_line_break = match self.output_format {
+                    OutputFormat::Json => self.multi_value_format == MultiValueFormat::Ndjson,
+                    OutputFormat::Yaml => true,
+                    OutputFormat::Cbor | OutputFormat::MessagePack => false,
+                };
+
+                if has_line_break {
+                    writer.write_byte(b'\n')?;
                 }
-                BENCODE_BEGIN_DICT => {
-                    let _byte = Self::read_peeked_byte(peeked_byte, &mut self.byte_reader, writer)?;
-                    self.begin_bencoded_value(BencodeType::Dict, writer)?;
-                    writer.write_byte(Self::JSON_OBJ_BEGIN)?;
-                    self.stack.push(State::ExpectingFirstDictFieldOrEnd);
+            }
+        }
+
+        if is_array {
+            match self.output_format {
+                OutputFormat::Json => writer.write_byte(Self::JSON_ARRAY_END)?,
+                OutputFormat::Yaml if values == 0 => writer.write_str("[]\n")?,
+                OutputFormat::Yaml => {}
+                OutputFormat::Cbor | OutputFormat::MessagePack => {
+                    if let Some(binary) = &mut self.binary {
+                        binary.end_list_or_dict(writer)?;
+                    }
                 }
-                BENCODE_END_LIST_OR_DICT => {
-                    let _byte = Self::read_peeked_byte(peeked_byte, &mut self.byte_reader, writer)?;
-                    self.end_list_or_dict(writer)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// It writes the separator between top-level values, when the input
+    /// contains another top-level value. In YAML, the values are separate
+    /// documents with [`MultiValueFormat::Ndjson`], and the items of a
+    /// sequence with [`MultiValueFormat::Array`]. Binary values are written
+    /// one after the other, or as the items of an array.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if:
+    ///
+    /// - The writer can't write to the output.
+    /// - Only one value is allowed: the [`MultiValueFormat::Single`] format.
+    fn begin_next_top_level_value<W: Writer>(&self, writer: &mut W) -> Result<(), error::Error> {
+        match self.multi_value_format {
+            MultiValueFormat::Single => Err(error::Error::TrailingData(
+                self.token_read_context(),
+                Self::write_context(writer),
+            )),
+            MultiValueFormat::Ndjson => {
+                if self.output_format == OutputFormat::Yaml {
+                    writer.write_str("---\n")?;
                 }
-                b'\n' => {
-                    // Ignore line breaks at the beginning, the end, or between values
-                    let _byte = Self::read_peeked_byte(peeked_byte, &mut self.byte_reader, writer)?;
+                Ok(())
+            }
+            MultiValueFormat::Array => {
+                if self.output_format == OutputFormat::Json {
+                    writer.write_byte(Self::JSON_ARRAY_ITEMS_SEPARATOR)?;
                 }
-                _ => {
-                    return Err(error::Error::UnrecognizedFirstBencodeValueByte(
-                        ReadContext {
-                            byte: Some(peeked_byte),
-                            pos: self.byte_reader.input_byte_counter(),
-                            latest_bytes: self.byte_reader.captured_bytes(),
-                        },
-                        WriteContext {
-                            byte: Some(peeked_byte),
-                            pos: writer.output_byte_counter(),
-                            latest_bytes: writer.captured_bytes(),
-                        },
-                    ));
+                Ok(())
+            }
+        }
+    }
+
+    /// It reads the next token and writes the corresponding JSON. It returns
+    /// `false` when the input ends.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if:
+    ///
+    /// - It can't read from the input or write to the output.
+    /// - The input is invalid Bencode.
+    fn write_next_token<W: Writer>(&mut self, writer: &mut W) -> Result<bool, error::Error> {
+        // The state before the token tells where the token is
+        let state = self.tokenizer.state();
+
+        // String values are written while they are read
+        if let Some(length) = self.tokenizer.begin_string_value(writer)? {
+            self.begin_value(&state, BencodeType::String, writer)?;
+
+            let format = self.string_format(false, length);
+            let capture_limit = if self.is_krpc_message_type() {
+                KrpcMessage::MESSAGE_TYPE_CAPTURE_LIMIT
+            } else {
+                0
+            };
+
+            let captured = self.write_string_value(length, format, capture_limit, writer)?;
+
+            if let Some(message) = &mut self.krpc_message {
+                if capture_limit > 0 {
+                    message.set_message_type(captured);
                 }
             }
 
-            self.num_processed_tokens += 1;
+            return Ok(true);
+        }
+
+        let Some(event) = self.tokenizer.next_event(writer)? else {
+            return Ok(false);
+        };
+
+        match event.token {
+            Token::Integer(digits) => {
+                self.write_integer(&state, &digits, writer)?;
+            }
+            Token::Bytes(bytes) => {
+                let is_dict_key = matches!(
+                    state,
+                    State::ExpectingFirstDictFieldOrEnd | State::ExpectingDictFieldKeyOrEnd
+                );
+
+                if is_dict_key {
+                    self.begin_bencoded_value(&state, false, writer)?;
+
+                    if let Some(message) = &mut self.krpc_message {
+                        if self.tokenizer.depth() == 1 {
+                            message.add_field(&bytes);
+                        }
+                    }
+                } else {
+                    self.begin_value(&state, BencodeType::String, writer)?;
+                }
+
+                if let Some(binary) = &mut self.binary {
+                    if is_dict_key {
+                        binary.write_key(&bytes, writer)?;
+                    } else {
+                        binary.write_bytes(&bytes, writer)?;
+                    }
+                    return Ok(true);
+                }
+
+                let encoding = if is_dict_key {
+                    self.string_encoding.for_dict_key()
+                } else {
+                    self.string_encoding
+                };
+
+                let format = self.string_format(is_dict_key, bytes.len());
+
+                if is_dict_key
+                    && self.output_format == OutputFormat::Yaml
+                    && format == StringFormat::Generic
+                    && yaml::is_plain_key(&bytes)
+                {
+                    writer.write_str(&String::from_utf8_lossy(&bytes))?;
+                    return Ok(true);
+                }
+
+                let mut encoder = FormattedStringEncoder::begin(format, encoding, writer)?;
+                encoder.write(&bytes, writer)?;
+                encoder.finish(writer)?;
+            }
+            Token::ListStart => {
+                self.begin_value(&state, BencodeType::List, writer)?;
+                self.begin_list_or_dict(false, writer)?;
+            }
+            Token::DictStart => {
+                self.begin_value(&state, BencodeType::Dict, writer)?;
+                self.begin_list_or_dict(true, writer)?;
+            }
+            Token::End => {
+                match &mut self.binary {
+                    Some(binary) => binary.end_list_or_dict(writer)?,
+                    None if self.output_format == OutputFormat::Json => {
+                        Self::end_list_or_dict(&state, writer)?;
+                    }
+                    None => self.yaml.end_list_or_dict(&state, writer)?,
+                }
+
+                if self.tokenizer.depth() == 0 {
+                    self.end_krpc_message(writer)?;
+                }
+            }
         }
 
-        self.check_bad_end_stack_state(writer)
+        Ok(true)
     }
 
-    /// It reads the next byte from the input consuming it. It returns `None` if
-    /// the input has ended.
+    /// It writes the beginning of a list or dictionary in the output format.
     ///
     /// # Errors
     ///
-    /// Will return and errors if:
+    /// Will return an error if the writer can't write to the output.
+    fn begin_list_or_dict<W: Writer>(
+        &mut self,
+        is_dict: bool,
+        writer: &mut W,
+    ) -> Result<(), error::Error> {
+        match &mut self.binary {
+            Some(binary) => binary.begin_list_or_dict(is_dict, writer)?,
+            None if self.output_format == OutputFormat::Json => {
+                writer.write_byte(if is_dict {
+                    Self::JSON_OBJ_BEGIN
+                } else {
+                    Self::JSON_ARRAY_BEGIN
+                })?;
+            }
+            None => {}
+        }
+
+        Ok(())
+    }
+
+    /// It writes the string value started by the tokenizer, in chunks, so
+    /// the whole string is never kept in memory.
+    ///
+    /// It returns the first bytes of the string, up to the capture limit.
     ///
-    /// - It can't read from the input.
-    /// - The byte read is not the expected one (the previously peeked byte).
-    fn read_peeked_byte<W: Writer>(
-        peeked_byte: u8,
-        reader: &mut ByteReader<R>,
-        writer: &W,
-    ) -> Result<Option<u8>, error::Error> {
-        match reader.read_byte() {
-            Ok(byte) => {
-                if byte == peeked_byte {
-                    return Ok(Some(byte));
+    /// # Errors
+    ///
+    /// Will return an error if it can't read from the input or write to the
+    /// output.
+    fn write_string_value<W: Writer>(
+        &mut self,
+        length: usize,
+        format: StringFormat,
+        capture_limit: usize,
+        writer: &mut W,
+    ) -> Result<Vec<u8>, error::Error> {
+        let mut chunk = vec![0; length.min(STRING_CHUNK_SIZE)];
+        let mut captured = Vec::new();
+
+        // Binary formats write the raw bytes
+        let mut encoder = match &mut self.binary {
+            Some(binary) => {
+                binary.begin_bytes(length, writer)?;
+                None
+            }
+            None => Some(FormattedStringEncoder::begin(
+                format,
+                self.string_encoding,
+                writer,
+            )?),
+        };
+
+        loop {
+            let chunk_len = self.tokenizer.read_string_value_chunk(&mut chunk, writer)?;
+
+            if chunk_len == 0 {
+                if let Some(encoder) = encoder {
+                    encoder.finish(writer)?;
                 }
-                Err(error::Error::ReadByteAfterPeekingDoesMatchPeekedByte(
-                    ReadContext {
-                        byte: Some(byte),
-                        pos: reader.input_byte_counter(),
-                        latest_bytes: reader.captured_bytes(),
-                    },
-                    WriteContext {
-                        byte: Some(byte),
-                        pos: writer.output_byte_counter(),
-                        latest_bytes: writer.captured_bytes(),
-                    },
-                ))
+                return Ok(captured);
             }
-            Err(err) => {
-                if err.kind() == io::ErrorKind::UnexpectedEof {
-                    return Ok(None);
-                }
-                Err(err.into())
+
+            let capture_len = chunk_len.min(capture_limit - captured.len());
+            captured.extend_from_slice(&chunk[..capture_len]);
+
+            match (&mut encoder, &mut self.binary) {
+                (Some(encoder), _) => encoder.write(&chunk[..chunk_len], writer)?,
+                (None, Some(binary)) => binary.write_raw(&chunk[..chunk_len], writer)?,
+                (None, None) => {}
+            }
+        }
+    }
+
+    /// It returns how the next string is written, depending on the output
+    /// mode and the field the string belongs to.
+    fn string_format(&self, is_dict_key: bool, length: usize) -> StringFormat {
+        if self.output_mode == OutputMode::Generic {
+            return StringFormat::Generic;
+        }
+
+        self.output_mode
+            .string_format(&self.tokenizer.last_keys(), is_dict_key, length)
+    }
+
+    /// It validates the value with the output mode and prints the delimiters
+    /// when needed.
+    ///
+    /// Called after the first token of a bencoded value (integer, string,
+    /// list or dict) has been read.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if:
+    ///
+    /// - The writer can't write to the output.
+    /// - The value does not have the type required by the output mode.
+    fn begin_value<W: Writer>(
+        &mut self,
+        state: &State,
+        found: BencodeType,
+        writer: &mut W,
+    ) -> Result<(), error::Error> {
+        if *state == State::Initial && self.output_mode == OutputMode::Krpc {
+            if found != BencodeType::Dict {
+                return Err(error::Error::MalformedKrpcMessage(
+                    "the message is not a dictionary",
+                    self.token_read_context(),
+                    Self::write_context(writer),
+                ));
             }
+
+            self.krpc_message = Some(KrpcMessage::default());
+        }
+
+        let is_list_or_dict = matches!(found, BencodeType::List | BencodeType::Dict);
+
+        self.check_value_type(state, found, writer)?;
+
+        self.begin_bencoded_value(state, is_list_or_dict, writer)
+    }
+
+    /// It checks the type of a dictionary field value when the output mode
+    /// requires a specific type for the field.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the value does not have the expected type.
+    fn check_value_type<W: Writer>(
+        &self,
+        state: &State,
+        found: BencodeType,
+        writer: &W,
+    ) -> Result<(), error::Error> {
+        if self.output_mode == OutputMode::Generic || *state != State::ExpectingDictFieldValue {
+            return Ok(());
+        }
+
+        let mut keys = self.tokenizer.last_keys();
+
+        // Lists and dictionaries have already pushed a new level
+        if matches!(found, BencodeType::List | BencodeType::Dict) {
+            keys.pop();
+        }
+
+        match self.output_mode.expected_value_type(&keys) {
+            Some(expected) if expected != found => Err(error::Error::UnexpectedFieldType(
+                String::from_utf8_lossy(keys.last().copied().flatten().unwrap_or_default())
+                    .into_owned(),
+                expected,
+                found,
+                self.token_read_context(),
+                Self::write_context(writer),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// It returns true if the next string value is the message type `y` of a
+    /// KRPC message.
+    fn is_krpc_message_type(&self) -> bool {
+        self.krpc_message.is_some()
+            && self.tokenizer.depth() == 1
+            && self.tokenizer.last_keys().last() == Some(&Some(b"y".as_slice()))
+    }
+
+    /// It checks the KRPC message that has just ended, if any.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the message does not contain the fields
+    /// required by its type.
+    fn end_krpc_message<W: Writer>(&mut self, writer: &W) -> Result<(), error::Error> {
+        let Some(message) = self.krpc_message.take() else {
+            return Ok(());
+        };
+
+        message.validate().map_err(|reason| {
+            error::Error::MalformedKrpcMessage(
+                reason,
+                self.token_read_context(),
+                Self::write_context(writer),
+            )
+        })
+    }
+
+    fn read_context(&self) -> ReadContext {
+        ReadContext {
+            byte: None,
+            pos: self.tokenizer.input_byte_counter(),
+            latest_bytes: self.tokenizer.captured_bytes(),
+            path: self.tokenizer.path(),
+            next_bytes: self.tokenizer.next_bytes(),
+            token_start: None,
+        }
+    }
+
+    /// The read context for errors about the whole latest token, for
+    /// example, an integer out of range.
+    fn token_read_context(&self) -> ReadContext {
+        ReadContext {
+            token_start: Some(self.tokenizer.token_start()),
+            ..self.read_context()
+        }
+    }
+
+    fn write_context<W: Writer>(writer: &W) -> WriteContext {
+        WriteContext {
+            byte: None,
+            pos: writer.output_byte_counter(),
+            latest_bytes: writer.captured_bytes(),
         }
     }
 
-    /// It peeks the next byte from the input without consuming it. It returns
-    /// `None` if the input has ended.
-    ///
-    /// # Errors
-    ///
-    /// Will return and errors if it can't read from the input.
-    fn peek_byte<W: Writer>(
-        reader: &mut ByteReader<R>,
-        _writer: &W,
-    ) -> Result<Option<u8>, error::Error> {
-        match reader.peek_byte() {
-            Ok(byte) => Ok(Some(byte)),
-            Err(err) => {
-                if err.kind() == io::ErrorKind::UnexpectedEof {
-                    return Ok(None);
-                }
-                Err(err.into())
-            }
+    /// It writes an integer as a number, or as a string when it's out of the
+    /// range of [`OutOfRangeIntegers::String`].
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the writer can't write to the output, or the
+    /// integer is out of the range of [`OutOfRangeIntegers::Fail`].
+    fn write_integer<W: Writer>(
+        &mut self,
+        state: &State,
+        digits: &[u8],
+        writer: &mut W,
+    ) -> Result<(), error::Error> {
+        let as_string = self.is_integer_written_as_string(digits, writer)?;
+
+        self.begin_value(state, BencodeType::Integer, writer)?;
+
+        if let Some(binary) = &mut self.binary {
+            if as_string {
+                binary.write_text(digits, writer)?;
+            } else {
+                binary.write_integer(digits, writer)?;
+            }
+            return Ok(());
+        }
+
+        if as_string {
+            string::write_json(digits, self.string_encoding, writer)?;
+        } else {
+            for &byte in digits {
+                writer.write_byte(byte)?;
+            }
+        }
+
+        self.write_integer_companion(digits, writer)
+    }
+
+    /// It returns true if the integer is out of the range of
+    /// [`OutOfRangeIntegers::String`], so it's written as a string.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the integer is out of the range of
+    /// [`OutOfRangeIntegers::Fail`].
+    fn is_integer_written_as_string<W: Writer>(
+        &self,
+        digits: &[u8],
+        writer: &W,
+    ) -> Result<bool, error::Error> {
+        match self.out_of_range_integers {
+            OutOfRangeIntegers::Passthrough => Ok(false),
+            OutOfRangeIntegers::String(range) => Ok(!range.contains(digits)),
+            OutOfRangeIntegers::Fail(range) => {
+                if range.contains(digits) {
+                    Ok(false)
+                } else {
+                    Err(error::Error::IntegerOutOfRange(
+                        self.token_read_context(),
+                        Self::write_context(writer),
+                    ))
+                }
+            }
+        }
+    }
+
+    /// It writes the field that follows an integer field in some output
+    /// modes, for example, the `creation date` in ISO 8601 format in the
+    /// torrent mode.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the writer can't write to the output.
+    fn write_integer_companion<W: Writer>(
+        &self,
+        digits: &[u8],
+        writer: &mut W,
+    ) -> Result<(), error::Error> {
+        if self.output_mode == OutputMode::Generic {
+            return Ok(());
+        }
+
+        if let Some((key, value)) = self
+            .output_mode
+            .integer_companion(&self.tokenizer.last_keys(), digits)
+        {
+            if self.output_format == OutputFormat::Yaml {
+                self.yaml
+                    .write_extra_field(key, &value, self.tokenizer.depth(), writer)?;
+                return Ok(());
+            }
+
+            writer.write_byte(Self::JSON_OBJ_FIELDS_SEPARATOR)?;
+            string::write_json(key.as_bytes(), self.string_encoding, writer)?;
+            writer.write_byte(Self::JSON_OBJ_FIELD_KEY_VALUE_SEPARATOR)?;
+            writer.write_str(&value)?;
+        }
+
+        Ok(())
+    }
+
+    /// It prints the delimiters when needed, in the output format.
+    ///
+    /// Called when a bencoded value (integer, string, list or dict) begins.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the writer can't write to the output.
+    fn begin_bencoded_value<W: Writer>(
+        &mut self,
+        state: &State,
+        is_list_or_dict: bool,
+        writer: &mut W,
+    ) -> Result<(), error::Error> {
+        match self.output_format {
+            OutputFormat::Json => return Self::begin_json_value(state, writer),
+            OutputFormat::Yaml => {}
+            OutputFormat::Cbor | OutputFormat::MessagePack => {
+                if let Some(binary) = &mut self.binary {
+                    binary.begin_item();
+                }
+                return Ok(());
+            }
+        }
+
+        // Lists and dictionaries have already pushed a new level
+        let depth = self.tokenizer.depth() - usize::from(is_list_or_dict);
+
+        if matches!(
+            state,
+            State::ExpectingFirstDictFieldOrEnd | State::ExpectingDictFieldKeyOrEnd
+        ) {
+            self.yaml.begin_key(state, depth, writer)?;
+        } else {
+            self.yaml
+                .begin_value(state, depth, is_list_or_dict, writer)?;
+        }
+
+        Ok(())
+    }
+
+    /// It prints the JSON delimiters when needed.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the writer can't write to the output.
+    fn begin_json_value<W: Writer>(state: &State, writer: &mut W) -> Result<(), error::Error> {
+        match state {
+            State::Initial
+            | State::ExpectingFirstListItemOrEnd
+            | State::ExpectingFirstDictFieldOrEnd => {}
+            State::ExpectingNextListItem => {
+                writer.write_byte(Self::JSON_ARRAY_ITEMS_SEPARATOR)?;
+            }
+            State::ExpectingDictFieldValue => {
+                writer.write_byte(Self::JSON_OBJ_FIELD_KEY_VALUE_SEPARATOR)?;
+            }
+            State::ExpectingDictFieldKeyOrEnd => {
+                writer.write_byte(Self::JSON_OBJ_FIELDS_SEPARATOR)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// It prints the end of the JSON array or object.
+    ///
+    /// Called when a list or dictionary ends.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the writer can't write to the output.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the state is not inside a list or dictionary. The
+    /// tokenizer only accepts the end inside a list or dictionary.
+    fn end_list_or_dict<W: Writer>(state: &State, writer: &mut W) -> Result<(), error::Error> {
+        match state {
+            State::ExpectingFirstListItemOrEnd | State::ExpectingNextListItem => {
+                writer.write_byte(Self::JSON_ARRAY_END)?;
+            }
+            State::ExpectingFirstDictFieldOrEnd | State::ExpectingDictFieldKeyOrEnd => {
+                writer.write_byte(Self::JSON_OBJ_END)?;
+            }
+            State::Initial | State::ExpectingDictFieldValue => {
+                panic!("unexpected end of list or dictionary in state {state}")
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::io::{self, Read};
+
+    use crate::{parsers::BencodeParser, test::bencode_to_json_unchecked, try_bencode_to_json};
+
+    mod it_should_allow_writing {
+        use crate::parsers::BencodeParser;
+
+        #[test]
+        fn to_any_type_implementing_io_write_trait() {
+            let mut output = Vec::new();
+
+            let mut parser = BencodeParser::new(&b"i0e"[..]);
+
+            parser
+                .write_bytes(&mut output)
+                .expect("Bencode to JSON conversion failed");
+
+            assert_eq!(output, vec!(b'0'));
+        }
+
+        #[test]
+        fn writing_to_any_type_implementing_fmt_write_trait() {
+            let mut output = String::new();
+
+            let mut parser = BencodeParser::new(&b"i0e"[..]);
+
+            parser
+                .write_str(&mut output)
+                .expect("Bencode to JSON conversion failed");
+
+            assert_eq!(output, "0".to_string());
+        }
+    }
+
+    #[test]
+    fn it_should_allow_reading_from_an_empty_input() {
+        struct EmptyReader;
+
+        impl Read for EmptyReader {
+            fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "Unexpected EOF",
+                ))
+            }
+        }
+
+        let mut output = String::new();
+
+        let mut parser = BencodeParser::new(EmptyReader);
+
+        parser.write_str(&mut output).unwrap();
+
+        assert_eq!(output, "");
+    }
+
+    mod it_should_allow_special_bencode_cases {
+
+        use crate::{parsers::BencodeParser, test::bencode_to_json_unchecked};
+
+        #[test]
+        fn an_empty_input() {
+            let mut output = String::new();
+
+            let mut parser = BencodeParser::new(&b""[..]);
+
+            parser
+                .write_str(&mut output)
+                .expect("Bencode to JSON conversion failed");
+
+            assert_eq!(output, String::new());
+        }
+
+        #[test]
+        fn line_breaks_at_the_beginning_of_the_input_stream() {
+            assert_eq!(bencode_to_json_unchecked(b"\ni0e"), "0".to_string());
+        }
+
+        #[test]
+        fn line_breaks_at_the_end_of_the_input_stream() {
+            assert_eq!(bencode_to_json_unchecked(b"i0e\n"), "0".to_string());
+        }
+
+        #[test]
+        fn line_breaks_between_bencoded_values() {
+            assert_eq!(
+                bencode_to_json_unchecked(b"li0e\ni1ee"),
+                "[0,1]".to_string()
+            );
+        }
+    }
+
+    mod with_multiple_top_level_values {
+        use crate::parsers::{error::Error, BencodeParser, MultiValueFormat};
+
+        fn bencode_to_json(input: &[u8], format: MultiValueFormat) -> Result<String, Error> {
+            let mut output = String::new();
+
+            BencodeParser::new(input)
+                .with_multi_value_format(format)
+                .write_str(&mut output)?;
+
+            Ok(output)
+        }
+
+        mod in_the_single_value_format {
+            use super::bencode_to_json;
+            use crate::parsers::{error::Error, MultiValueFormat};
+
+            #[test]
+            fn it_should_be_the_default_format() {
+                assert_eq!(MultiValueFormat::default(), MultiValueFormat::Single);
+            }
+
+            #[test]
+            fn it_should_allow_line_breaks_after_the_value() {
+                assert_eq!(
+                    bencode_to_json(b"li1ee\n\n", MultiValueFormat::Single).unwrap(),
+                    "[1]"
+                );
+            }
+
+            #[test]
+            fn it_should_allow_windows_line_breaks_after_the_value() {
+                assert_eq!(
+                    bencode_to_json(b"li1ee\r\n", MultiValueFormat::Single).unwrap(),
+                    "[1]"
+                );
+            }
+
+            #[test]
+            fn it_should_fail_when_there_is_trailing_data_after_the_value() {
+                let result = bencode_to_json(b"i1e\ni2e", MultiValueFormat::Single);
+
+                let Err(Error::TrailingData(read_context, write_context)) = result else {
+                    panic!("expected a trailing data error, got {result:?}");
+                };
+
+                // The first byte of the trailing data has been peeked
+                assert_eq!(read_context.pos, 5);
+                assert_eq!(read_context.offset(), 4);
+                assert_eq!(write_context.pos, 1);
+            }
+
+            #[test]
+            fn it_should_fail_when_the_trailing_data_is_not_bencode() {
+                assert!(matches!(
+                    bencode_to_json(b"i1exyz", MultiValueFormat::Single),
+                    Err(Error::TrailingData(_, _))
+                ));
+            }
+        }
+
+        mod in_the_ndjson_format {
+            use super::bencode_to_json;
+            use crate::parsers::MultiValueFormat;
+
+            #[test]
+            fn it_should_write_each_value_in_a_line() {
+                assert_eq!(
+                    bencode_to_json(b"i1eli2eed1:ai3ee4:spam", MultiValueFormat::Ndjson).unwrap(),
+                    "1\n[2]\n{\"a\":3}\n\"spam\"\n"
+                );
+            }
+
+            #[test]
+            fn it_should_ignore_line_breaks_between_values() {
+                assert_eq!(
+                    bencode_to_json(b"i1e\ni2e\n", MultiValueFormat::Ndjson).unwrap(),
+                    "1\n2\n"
+                );
+            }
+
+            #[test]
+            fn it_should_write_nothing_for_an_empty_input() {
+                assert_eq!(bencode_to_json(b"", MultiValueFormat::Ndjson).unwrap(), "");
+            }
+        }
+
+        mod in_the_array_format {
+            use super::bencode_to_json;
+            use crate::parsers::{error::Error, MultiValueFormat};
+
+            #[test]
+            fn it_should_write_all_values_in_an_array() {
+                assert_eq!(
+                    bencode_to_json(b"i1e\nli2ee4:spam", MultiValueFormat::Array).unwrap(),
+                    r#"[1,[2],"spam"]"#
+                );
+            }
+
+            #[test]
+            fn it_should_write_an_empty_array_for_an_empty_input() {
+                assert_eq!(bencode_to_json(b"", MultiValueFormat::Array).unwrap(), "[]");
+            }
+
+            #[test]
+            fn it_should_fail_when_one_of_the_values_is_invalid() {
+                assert!(matches!(
+                    bencode_to_json(b"i1ei2", MultiValueFormat::Array),
+                    Err(Error::UnexpectedEndOfInputParsingInteger(_, _))
+                ));
+            }
+        }
+    }
+
+    mod with_pretty_print {
+        use crate::{
+            parsers::{BencodeParser, MultiValueFormat},
+            rw::pretty_writer::{Indent, PrettyOptions},
+        };
+
+        fn bencode_to_pretty_json(input: &[u8], parser_options: PrettyOptions) -> String {
+            let mut output = String::new();
+
+            BencodeParser::new(input)
+                .with_pretty_print(parser_options)
+                .write_str(&mut output)
+                .unwrap();
+
+            output
+        }
+
+        #[test]
+        fn it_should_write_nested_values_indented() {
+            assert_eq!(
+                bencode_to_pretty_json(b"d1:ad1:bli1eld1:ci2eeeeee", PrettyOptions::default()),
+                "{\n  \"a\": {\n    \"b\": [\n      1,\n      [\n        {\n          \"c\": 2\n        }\n      ]\n    ]\n  }\n}"
+            );
+        }
+
+        #[test]
+        fn it_should_allow_indenting_with_tabs() {
+            assert_eq!(
+                bencode_to_pretty_json(
+                    b"d1:ali1ei2eee",
+                    PrettyOptions::default().with_indent(Indent::Tabs(1))
+                ),
+                "{\n\t\"a\": [1, 2]\n}"
+            );
+        }
+
+        #[test]
+        fn it_should_write_scalar_values_as_in_the_compact_output() {
+            assert_eq!(
+                bencode_to_pretty_json(b"i-42e", PrettyOptions::default()),
+                "-42"
+            );
+            assert_eq!(
+                bencode_to_pretty_json(b"2:\xFF\xFE", PrettyOptions::default()),
+                r#""<hex>fffe</hex>""#
+            );
+        }
+
+        #[test]
+        fn it_should_pretty_print_each_value_when_there_are_many_top_level_values() {
+            let mut output = String::new();
+
+            BencodeParser::new(&b"d1:ai1eed1:bi2ee"[..])
+                .with_multi_value_format(MultiValueFormat::Ndjson)
+                .with_pretty_print(PrettyOptions::default())
+                .write_str(&mut output)
+                .unwrap();
+
+            assert_eq!(output, "{\n  \"a\": 1\n}\n{\n  \"b\": 2\n}\n");
+        }
+    }
+
+    mod in_yaml_format {
+        use crate::parsers::{
+            mode::OutputMode, string::StringEncoding, BencodeParser, MultiValueFormat, OutputFormat,
+        };
+
+        fn bencode_to_yaml(input: &[u8]) -> String {
+            let mut output = String::new();
+
+            BencodeParser::new(input)
+                .with_output_format(OutputFormat::Yaml)
+                .write_str(&mut output)
+                .unwrap();
+
+            output
+        }
+
+        #[test]
+        fn it_should_write_scalar_values() {
+            assert_eq!(bencode_to_yaml(b"i-42e"), "-42\n");
+            assert_eq!(bencode_to_yaml(b"4:spam"), "\"spam\"\n");
+        }
+
+        #[test]
+        fn it_should_write_dictionaries_with_one_field_per_line() {
+            assert_eq!(
+                bencode_to_yaml(b"d1:ai1e1:bd1:ci2eee"),
+                "a: 1\nb:\n  c: 2\n"
+            );
+        }
+
+        #[test]
+        fn it_should_write_lists_with_one_item_per_line() {
+            assert_eq!(
+                bencode_to_yaml(b"d1:ali1eli2ei3eed1:bi4e1:ci5eeee"),
+                "a:\n  - 1\n  - - 2\n    - 3\n  - b: 4\n    c: 5\n"
+            );
+        }
+
+        #[test]
+        fn it_should_write_empty_lists_and_dictionaries_in_flow_style() {
+            assert_eq!(bencode_to_yaml(b"le"), "[]\n");
+            assert_eq!(bencode_to_yaml(b"d1:ale1:bdee"), "a: []\nb: {}\n");
+            assert_eq!(bencode_to_yaml(b"llee"), "- []\n");
+        }
+
+        #[test]
+        fn it_should_quote_dictionary_keys_only_when_needed() {
+            assert_eq!(
+                bencode_to_yaml(b"d13:creation datei1e4:truei2e4:a: bi3ee"),
+                "creation date: 1\n\"true\": 2\n\"a: b\": 3\n"
+            );
+        }
+
+        #[test]
+        fn it_should_write_non_utf8_strings_with_the_selected_encoding() {
+            let mut output = String::new();
+
+            BencodeParser::new(&b"d1:a2:\xFF\xFEe"[..])
+                .with_output_format(OutputFormat::Yaml)
+                .with_string_encoding(StringEncoding::Base64)
+                .write_str(&mut output)
+                .unwrap();
+
+            assert_eq!(output, "a: \"//4=\"\n");
+        }
+
+        #[test]
+        fn it_should_write_the_extra_fields_of_the_output_mode() {
+            let mut output = String::new();
+
+            BencodeParser::new(&b"d13:creation datei0ee"[..])
+                .with_output_format(OutputFormat::Yaml)
+                .with_output_mode(OutputMode::Torrent)
+                .write_str(&mut output)
+                .unwrap();
+
+            assert_eq!(
+                output,
+                "creation date: 0\ncreation date (ISO 8601): \"1970-01-01T00:00:00Z\"\n"
+            );
+        }
+
+        #[test]
+        fn it_should_write_many_top_level_values_as_documents_or_as_a_sequence() {
+            let bencode_to_yaml = |format| {
+                let mut output = String::new();
+
+                BencodeParser::new(&b"i1ed1:ai2ee"[..])
+                    .with_output_format(OutputFormat::Yaml)
+                    .with_multi_value_format(format)
+                    .write_str(&mut output)
+                    .unwrap();
+
+                output
+            };
+
+            assert_eq!(bencode_to_yaml(MultiValueFormat::Ndjson), "1\n---\na: 2\n");
+            assert_eq!(bencode_to_yaml(MultiValueFormat::Array), "- 1\n- a: 2\n");
         }
     }
 
-    /// It updates the stack state and prints the delimiters when needed.
-    ///
-    /// Called when the first byt of a bencoded value (integer, string, list or dict)
-    /// is received.
-    ///
-    /// # Errors
-    ///
-    /// Will return an error if the writer can't write to the output.
-    pub fn begin_bencoded_value<W: Writer>(
-        &mut self,
-        bencode_type: BencodeType,
-        writer: &mut W,
-    ) -> Result<(), error::Error> {
-        match self.stack.peek() {
-            State::Initial => {}
-            State::ExpectingFirstListItemOrEnd => {
-                self.stack.swap_top(State::ExpectingNextListItem);
-            }
-            State::ExpectingNextListItem => {
-                writer.write_byte(Self::JSON_ARRAY_ITEMS_SEPARATOR)?;
-            }
-            State::ExpectingFirstDictFieldOrEnd => {
-                if bencode_type != BencodeType::String {
-                    return Err(error::Error::ExpectedStringForDictKeyGot(
-                        bencode_type,
-                        ReadContext {
-                            byte: None,
-                            pos: self.byte_reader.input_byte_counter(),
-                            latest_bytes: self.byte_reader.captured_bytes(),
-                        },
-                        WriteContext {
-                            byte: None,
-                            pos: writer.output_byte_counter(),
-                            latest_bytes: writer.captured_bytes(),
-                        },
-                    ));
-                }
-
-                self.stack.swap_top(State::ExpectingDictFieldValue);
-            }
-            State::ExpectingDictFieldValue => {
-                writer.write_byte(Self::JSON_OBJ_FIELD_KEY_VALUE_SEPARATOR)?;
+    mod in_binary_formats {
+        use crate::parsers::{
+            config::ParserConfig, error::Error, BencodeParser, MultiValueFormat, OutputFormat,
+        };
 
-                self.stack.swap_top(State::ExpectingDictFieldKeyOrEnd);
-            }
-            State::ExpectingDictFieldKeyOrEnd => {
-                if bencode_type != BencodeType::String {
-                    return Err(error::Error::ExpectedStringForDictKeyGot(
-                        bencode_type,
-                        ReadContext {
-                            byte: None,
-                            pos: self.byte_reader.input_byte_counter(),
-                            latest_bytes: self.byte_reader.captured_bytes(),
-                        },
-                        WriteContext {
-                            byte: None,
-                            pos: writer.output_byte_counter(),
-                            latest_bytes: writer.captured_bytes(),
-                        },
-                    ));
-                }
+        fn bencode_to(input: &[u8], format: OutputFormat) -> Vec<u8> {
+            let mut output = Vec::new();
 
-                writer.write_byte(Self::JSON_OBJ_FIELDS_SEPARATOR)?;
+            BencodeParser::new(input)
+                .with_output_format(format)
+                .write_bytes(&mut output)
+                .unwrap();
 
-                self.stack.swap_top(State::ExpectingDictFieldValue);
-            }
+            output
         }
 
-        Ok(())
-    }
-
-    /// It updates the stack state and prints the delimiters when needed.
-    ///
-    /// Called when the end of list or dictionary byte is received. End of
-    /// integers or strings are processed while parsing them.
-    ///
-    /// # Errors
-    ///
-    /// Will return an error if the writer can't write to the output.
-    ///
-    /// # Panics
-    ///
-    /// Will panic if the end of bencoded value (list or dictionary) was not
-    /// expected.
-    pub fn end_list_or_dict<W: Writer>(&mut self, writer: &mut W) -> Result<(), error::Error> {
-        match self.stack.peek() {
-            State::ExpectingFirstListItemOrEnd | State::ExpectingNextListItem => {
-                writer.write_byte(Self::JSON_ARRAY_END)?;
-                self.stack.pop();
-            }
-            State::ExpectingFirstDictFieldOrEnd | State::ExpectingDictFieldKeyOrEnd => {
-                writer.write_byte(Self::JSON_OBJ_END)?;
-                self.stack.pop();
-            }
-            State::ExpectingDictFieldValue => {
-                return Err(error::Error::PrematureEndOfDict(
-                    ReadContext {
-                        byte: None,
-                        pos: self.byte_reader.input_byte_counter(),
-                        latest_bytes: self.byte_reader.captured_bytes(),
-                    },
-                    WriteContext {
-                        byte: None,
-                        pos: writer.output_byte_counter(),
-                        latest_bytes: writer.captured_bytes(),
-                    },
-                ))
-            }
-            State::Initial => {
-                return Err(error::Error::NoMatchingStartForListOrDictEnd(
-                    ReadContext {
-                        byte: None,
-                        pos: self.byte_reader.input_byte_counter(),
-                        latest_bytes: self.byte_reader.captured_bytes(),
-                    },
-                    WriteContext {
-                        byte: None,
-                        pos: writer.output_byte_counter(),
-                        latest_bytes: writer.captured_bytes(),
-                    },
-                ))
-            }
+        #[test]
+        fn it_should_write_cbor_with_indefinite_length_lists_and_dictionaries() {
+            assert_eq!(
+                bencode_to(b"d1:ali1e2:\xFF\xFEee", OutputFormat::Cbor),
+                vec![0xBF, 0x61, b'a', 0x9F, 0x01, 0x42, 0xFF, 0xFE, 0xFF, 0xFF]
+            );
         }
 
-        Ok(())
-    }
+        #[test]
+        fn it_should_write_non_utf8_cbor_dictionary_keys_as_byte_strings() {
+            assert_eq!(
+                bencode_to(b"d1:\xFFi1ee", OutputFormat::Cbor),
+                vec![0xBF, 0x41, 0xFF, 0x01, 0xFF]
+            );
+        }
 
-    /// It checks if the stack state is correct at the end of the parsing.
-    ///
-    /// That could happen, for example, when bencode values are not finished.
-    ///
-    /// # Errors
-    ///
-    /// Will return an error if the stack state is not correct.
-    fn check_bad_end_stack_state<W: Writer>(&self, writer: &W) -> Result<(), error::Error> {
-        match self.stack.peek() {
-            State::Initial => Ok(()),
-            State::ExpectingFirstListItemOrEnd => Err(
-                error::Error::UnexpectedEndOfInputExpectingFirstListItemOrEnd(
-                    ReadContext {
-                        byte: None,
-                        pos: self.byte_reader.input_byte_counter(),
-                        latest_bytes: self.byte_reader.captured_bytes(),
-                    },
-                    WriteContext {
-                        byte: None,
-                        pos: writer.output_byte_counter(),
-                        latest_bytes: writer.captured_bytes(),
-                    },
-                ),
-            ),
-            State::ExpectingNextListItem => {
-                Err(error::Error::UnexpectedEndOfInputExpectingNextListItem(
-                    ReadContext {
-                        byte: None,
-                        pos: self.byte_reader.input_byte_counter(),
-                        latest_bytes: self.byte_reader.captured_bytes(),
-                    },
-                    WriteContext {
-                        byte: None,
-                        pos: writer.output_byte_counter(),
-                        latest_bytes: writer.captured_bytes(),
-                    },
-                ))
-            }
-            State::ExpectingFirstDictFieldOrEnd => Err(
-                error::Error::UnexpectedEndOfInputExpectingFirstDictFieldOrEnd(
-                    ReadContext {
-                        byte: None,
-                        pos: self.byte_reader.input_byte_counter(),
-                        latest_bytes: self.byte_reader.captured_bytes(),
-                    },
-                    WriteContext {
-                        byte: None,
-                        pos: writer.output_byte_counter(),
-                        latest_bytes: writer.captured_bytes(),
-                    },
-                ),
-            ),
-            State::ExpectingDictFieldValue => {
-                Err(error::Error::UnexpectedEndOfInputExpectingDictFieldValue(
-                    ReadContext {
-                        byte: None,
-                        pos: self.byte_reader.input_byte_counter(),
-                        latest_bytes: self.byte_reader.captured_bytes(),
-                    },
-                    WriteContext {
-                        byte: None,
-                        pos: writer.output_byte_counter(),
-                        latest_bytes: writer.captured_bytes(),
-                    },
-                ))
-            }
-            State::ExpectingDictFieldKeyOrEnd => Err(
-                error::Error::UnexpectedEndOfInputExpectingDictFieldKeyOrEnd(
-                    ReadContext {
-                        byte: None,
-                        pos: self.byte_reader.input_byte_counter(),
-                        latest_bytes: self.byte_reader.captured_bytes(),
-                    },
-                    WriteContext {
-                        byte: None,
-                        pos: writer.output_byte_counter(),
-                        latest_bytes: writer.captured_bytes(),
-                    },
-                ),
-            ),
+        #[test]
+        fn it_should_write_messagepack_with_the_number_of_items() {
+            assert_eq!(
+                bencode_to(b"d1:ali1e2:\xFF\xFEee", OutputFormat::MessagePack),
+                vec![0x81, 0xA1, b'a', 0x92, 0x01, 0xC4, 0x02, 0xFF, 0xFE]
+            );
         }
-    }
-}
 
-#[cfg(test)]
-mod tests {
+        #[test]
+        fn it_should_write_messagepack_integers_that_do_not_fit_into_64_bits_as_strings() {
+            assert_eq!(
+                bencode_to(b"i18446744073709551616e", OutputFormat::MessagePack),
+                [&[0xB4][..], b"18446744073709551616"].concat()
+            );
+        }
 
-    use std::io::{self, Read};
+        #[test]
+        fn it_should_not_keep_in_memory_messagepack_lists_longer_than_the_input_limit() {
+            let mut output = Vec::new();
 
-    use crate::{parsers::BencodeParser, test::bencode_to_json_unchecked, try_bencode_to_json};
+            let result = BencodeParser::with_config(
+                &b"li1ei2ee"[..],
+                ParserConfig::default().with_max_input_bytes(4),
+            )
+            .with_output_format(OutputFormat::MessagePack)
+            .write_bytes(&mut output);
 
-    mod it_should_allow_writing {
-        use crate::parsers::BencodeParser;
+            assert!(matches!(result, Err(Error::MaxInputBytesExceeded(4, _, _))));
+            assert!(output.is_empty());
+        }
 
         #[test]
-        fn to_any_type_implementing_io_write_trait() {
+        fn it_should_fail_when_a_string_is_too_long_for_messagepack() {
             let mut output = Vec::new();
 
-            let mut parser = BencodeParser::new(&b"i0e"[..]);
+            let result = BencodeParser::new(&b"4294967296:"[..])
+                .with_output_format(OutputFormat::MessagePack)
+                .write_bytes(&mut output);
 
-            parser
+            assert!(matches!(
+                result,
+                Err(Error::MessagePackLengthOverflow(
+                    "binary string",
+                    4_294_967_296
+                ))
+            ));
+        }
+
+        #[test]
+        fn it_should_write_many_top_level_values_in_an_array() {
+            let mut output = Vec::new();
+
+            BencodeParser::new(&b"i1e0:"[..])
+                .with_output_format(OutputFormat::MessagePack)
+                .with_multi_value_format(MultiValueFormat::Array)
                 .write_bytes(&mut output)
-                .expect("Bencode to JSON conversion failed");
+                .unwrap();
 
-            assert_eq!(output, vec!(b'0'));
+            assert_eq!(output, vec![0x92, 0x01, 0xC4, 0x00]);
         }
 
         #[test]
-        fn writing_to_any_type_implementing_fmt_write_trait() {
+        fn it_should_fail_writing_to_a_string() {
             let mut output = String::new();
 
-            let mut parser = BencodeParser::new(&b"i0e"[..]);
-
-            parser
-                .write_str(&mut output)
-                .expect("Bencode to JSON conversion failed");
+            let result = BencodeParser::new(&b"i1e"[..])
+                .with_output_format(OutputFormat::Cbor)
+                .write_str(&mut output);
 
-            assert_eq!(output, "0".to_string());
+            assert!(matches!(
+                result,
+                Err(Error::BinaryOutputFormat(OutputFormat::Cbor))
+            ));
         }
     }
 
-    #[test]
-    fn it_should_allow_reading_from_an_empty_input() {
-        struct EmptyReader;
+    mod parsing_only_one_value {
+        use std::io::Read;
 
-        impl Read for EmptyReader {
-            fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
-                Err(io::Error::new(
-                    io::ErrorKind::UnexpectedEof,
-                    "Unexpected EOF",
-                ))
-            }
-        }
+        use crate::parsers::{error::Error, BencodeParser};
 
-        let mut output = String::new();
+        fn parse_one(input: &[u8]) -> (Result<u64, Error>, String, Vec<u8>) {
+            let mut output = Vec::new();
 
-        let mut parser = BencodeParser::new(EmptyReader);
+            let mut parser = BencodeParser::new(input);
 
-        parser.write_str(&mut output).unwrap();
+            let result = parser.parse_one(&mut output);
 
-        assert_eq!(output, "");
-    }
+            let mut rest = Vec::new();
+            parser.into_inner().read_to_end(&mut rest).unwrap();
 
-    mod it_should_allow_special_bencode_cases {
+            (result, String::from_utf8(output).unwrap(), rest)
+        }
 
-        use crate::{parsers::BencodeParser, test::bencode_to_json_unchecked};
+        #[test]
+        fn it_should_return_the_number_of_bytes_consumed_for_each_type_of_value() {
+            for (input, expected_json) in [
+                (&b"i42e"[..], "42"),
+                (b"4:spam", r#""spam""#),
+                (b"li1ee", "[1]"),
+                (b"d1:ai1ee", r#"{"a":1}"#),
+            ] {
+                let (result, json, rest) = parse_one(input);
+
+                assert_eq!(result.unwrap(), input.len() as u64);
+                assert_eq!(json, expected_json);
+                assert!(rest.is_empty());
+            }
+        }
 
         #[test]
-        fn an_empty_input() {
-            let mut output = String::new();
+        fn it_should_leave_the_reader_right_after_the_value() {
+            let (result, json, rest) = parse_one(b"d5:piecei0ee\x00\xFF\xFEi1e");
 
-            let mut parser = BencodeParser::new(&b""[..]);
+            assert_eq!(result.unwrap(), 12);
+            assert_eq!(json, r#"{"piece":0}"#);
+            assert_eq!(rest, b"\x00\xFF\xFEi1e");
+        }
 
-            parser
-                .write_str(&mut output)
-                .expect("Bencode to JSON conversion failed");
+        #[test]
+        fn it_should_include_the_line_breaks_before_the_value() {
+            let (result, _, rest) = parse_one(b"\n\ni1e\n");
 
-            assert_eq!(output, String::new());
+            assert_eq!(result.unwrap(), 5);
+            assert_eq!(rest, b"\n");
         }
 
         #[test]
-        fn line_breaks_at_the_beginning_of_the_input_stream() {
-            assert_eq!(bencode_to_json_unchecked(b"\ni0e"), "0".to_string());
+        fn it_should_allow_parsing_the_next_value_with_another_call() {
+            let mut parser = BencodeParser::new(&b"i1e4:spam"[..]);
+
+            let mut first = Vec::new();
+            let mut second = Vec::new();
+
+            assert_eq!(parser.parse_one(&mut first).unwrap(), 3);
+            assert_eq!(parser.parse_one(&mut second).unwrap(), 6);
+
+            assert_eq!(first, b"1");
+            assert_eq!(second, br#""spam""#);
         }
 
         #[test]
-        fn line_breaks_at_the_end_of_the_input_stream() {
-            assert_eq!(bencode_to_json_unchecked(b"i0e\n"), "0".to_string());
+        fn it_should_fail_when_the_input_does_not_contain_a_value() {
+            assert!(matches!(
+                parse_one(b"\n").0,
+                Err(Error::UnexpectedEndOfInputExpectingValue(_, _))
+            ));
         }
 
         #[test]
-        fn line_breaks_between_bencoded_values() {
-            assert_eq!(
-                bencode_to_json_unchecked(b"li0e\ni1ee"),
-                "[0,1]".to_string()
-            );
+        fn it_should_fail_when_the_input_ends_before_the_end_of_the_value() {
+            assert!(matches!(
+                parse_one(b"li1e").0,
+                Err(Error::UnexpectedEndOfInputExpectingNextListItem(_, _))
+            ));
         }
     }
 
@@ -623,6 +1713,83 @@ mod tests {
             );
         }
 
+        mod out_of_range {
+            use crate::parsers::{
+                error::Error,
+                integer::{IntegerRange, OutOfRangeIntegers},
+                BencodeParser, OutputFormat,
+            };
+
+            fn bencode_to_json(
+                input: &[u8],
+                out_of_range_integers: OutOfRangeIntegers,
+            ) -> Result<String, Error> {
+                let mut output = String::new();
+
+                BencodeParser::new(input)
+                    .with_out_of_range_integers(out_of_range_integers)
+                    .write_str(&mut output)?;
+
+                Ok(output)
+            }
+
+            #[test]
+            fn it_should_write_them_as_strings() {
+                assert_eq!(
+                    bencode_to_json(
+                        b"li9223372036854775807ei9223372036854775808ee",
+                        OutOfRangeIntegers::String(IntegerRange::I64)
+                    )
+                    .unwrap(),
+                    r#"[9223372036854775807,"9223372036854775808"]"#
+                );
+            }
+
+            #[test]
+            fn it_should_write_them_as_strings_in_the_json_safe_range() {
+                assert_eq!(
+                    bencode_to_json(
+                        b"d1:ai9007199254740991e1:bi-9007199254740992ee",
+                        OutOfRangeIntegers::String(IntegerRange::JsonSafe)
+                    )
+                    .unwrap(),
+                    r#"{"a":9007199254740991,"b":"-9007199254740992"}"#
+                );
+            }
+
+            #[test]
+            fn it_should_fail_with_an_integer_out_of_range_error() {
+                let result = bencode_to_json(
+                    b"li1ei9007199254740992ee",
+                    OutOfRangeIntegers::Fail(IntegerRange::JsonSafe),
+                );
+
+                assert!(matches!(result, Err(Error::IntegerOutOfRange { .. })));
+            }
+
+            #[test]
+            fn it_should_not_fail_when_all_the_integers_are_in_the_range() {
+                assert_eq!(
+                    bencode_to_json(b"li1ei-2ee", OutOfRangeIntegers::Fail(IntegerRange::I64))
+                        .unwrap(),
+                    "[1,-2]"
+                );
+            }
+
+            #[test]
+            fn it_should_write_them_as_text_strings_in_binary_formats() {
+                let mut output = Vec::new();
+
+                BencodeParser::new(&b"i18446744073709551616e"[..])
+                    .with_output_format(OutputFormat::Cbor)
+                    .with_out_of_range_integers(OutOfRangeIntegers::String(IntegerRange::I64))
+                    .write_bytes(&mut output)
+                    .unwrap();
+
+                assert_eq!(output, [&[0x74][..], b"18446744073709551616"].concat());
+            }
+        }
+
         mod should_fail {
             use crate::{parsers::error::Error, try_bencode_to_json};
 
@@ -654,13 +1821,29 @@ mod tests {
 
     mod strings {
         use crate::{
+            parsers::{error::Error, BencodeParser},
             test::{bencode_to_json_unchecked, bencoded_string_with_repeated_byte},
-            to_bencode,
+            to_bencode, try_bencode_to_json,
         };
 
         #[test]
-        fn length_can_contain_leading_zeros() {
-            assert_eq!(bencode_to_json_unchecked(b"00:"), r#""""#.to_string());
+        fn length_cannot_contain_leading_zeros() {
+            assert!(matches!(
+                try_bencode_to_json(b"00:"),
+                Err(Error::LeadingZerosInStringLength { .. })
+            ));
+        }
+
+        #[test]
+        fn length_can_contain_leading_zeros_in_lenient_mode() {
+            let mut output = String::new();
+
+            BencodeParser::new(&b"03:abc"[..])
+                .lenient()
+                .write_str(&mut output)
+                .unwrap();
+
+            assert_eq!(output, r#""abc""#.to_string());
         }
 
         #[test]
@@ -684,6 +1867,37 @@ mod tests {
             );
         }
 
+        #[test]
+        fn non_utf8_with_a_different_encoding() {
+            use crate::parsers::string::StringEncoding;
+
+            let mut output = String::new();
+
+            BencodeParser::new(&b"l2:\xFF\xFEe"[..])
+                .with_string_encoding(StringEncoding::Base64)
+                .write_str(&mut output)
+                .unwrap();
+
+            assert_eq!(output, r#"["//4="]"#.to_string());
+        }
+
+        #[test]
+        fn non_utf8_dictionary_keys_as_strings_when_the_encoding_uses_objects() {
+            use crate::parsers::string::StringEncoding;
+
+            let mut output = String::new();
+
+            BencodeParser::new(&b"d2:\xFF\xFE2:\xFD\xFCe"[..])
+                .with_string_encoding(StringEncoding::BytesObject)
+                .write_str(&mut output)
+                .unwrap();
+
+            assert_eq!(
+                output,
+                r#"{"<hex>fffe</hex>":{"$bytes":"fdfc"}}"#.to_string()
+            );
+        }
+
         #[test]
         fn big_utf8_string() {
             let big_string = "a".repeat(1_000_000);
@@ -703,6 +1917,21 @@ mod tests {
             assert_eq!(bencode_to_json_unchecked(&big_non_utf8_string), expected);
         }
 
+        #[test]
+        fn big_string_switching_to_hex_after_the_first_chunk() {
+            use crate::parsers::string::STRING_CHUNK_SIZE;
+
+            let text = "a".repeat(STRING_CHUNK_SIZE + 1);
+
+            let mut bencoded = format!("l{}:{text}", text.len() + 2).into_bytes();
+            bencoded.extend_from_slice(b"\xFF\xFEe");
+
+            assert_eq!(
+                bencode_to_json_unchecked(&bencoded),
+                format!(r#"["{text}<hex>fffe</hex>"]"#)
+            );
+        }
+
         #[test]
         fn ending_with_bencode_end_char() {
             assert_eq!(bencode_to_json_unchecked(b"1:e"), r#""e""#.to_string());
@@ -2092,5 +3321,42 @@ mod tests {
                 }
             }
         }
+
+        mod in_strict_mode {
+            use crate::parsers::{error::Error, BencodeParser};
+
+            fn try_strict_bencode_to_json(input_buffer: &[u8]) -> Result<String, Error> {
+                let mut output = String::new();
+
+                BencodeParser::new(input_buffer)
+                    .strict()
+                    .write_str(&mut output)
+                    .map(|()| output)
+            }
+
+            #[test]
+            fn it_should_convert_dictionaries_with_sorted_keys() {
+                assert_eq!(
+                    try_strict_bencode_to_json(b"d3:bar4:spam3:fooi42ee").unwrap(),
+                    r#"{"bar":"spam","foo":42}"#.to_string()
+                );
+            }
+
+            #[test]
+            fn it_should_fail_when_the_dictionary_keys_are_not_sorted() {
+                assert!(matches!(
+                    try_strict_bencode_to_json(b"d3:fooi42e3:bar4:spame"),
+                    Err(Error::UnsortedDictKeys { .. })
+                ));
+            }
+
+            #[test]
+            fn it_should_fail_when_the_dictionary_keys_are_duplicated() {
+                assert!(matches!(
+                    try_strict_bencode_to_json(b"d3:fooi42e3:fooi43ee"),
+                    Err(Error::DuplicateDictKey { .. })
+                ));
+            }
+        }
     }
 }
@@ -0,0 +1,1301 @@
+//! Output modes for well-known bencoded data, like torrent files.
+//!
+//! In the default [`OutputMode::Generic`] mode, strings are always written
+//! with the selected [`StringEncoding`]. The other modes recognize some fields
+//! by their position in the bencoded data and write them in a more readable
+//! way. For example, the binary hashes in a torrent are written in plain
+//! hexadecimal.
+//!
+//! When a recognized field does not have the expected size, it's written as
+//! in the generic mode.
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6};
+
+use super::{
+    error::Error,
+    string::{JsonStringEncoder, StringEncoding},
+    BencodeType,
+};
+use crate::rw::writer::Writer;
+
+/// The size of the SHA-1 hashes used in version 1 torrents.
+const SHA1_HASH_SIZE: usize = 20;
+
+/// The size of the SHA-256 hashes used in version 2 torrents.
+const SHA256_HASH_SIZE: usize = 32;
+
+/// The size of the DHT node IDs.
+const NODE_ID_SIZE: usize = 20;
+
+/// The key of the field added after the `creation date` with the date in
+/// ISO 8601 format.
+const CREATION_DATE_ISO_8601_KEY: &str = "creation date (ISO 8601)";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputMode {
+    /// All strings are written with the selected [`StringEncoding`].
+    #[default]
+    Generic,
+
+    /// For `.torrent` metainfo files.
+    ///
+    /// - The `pieces` of the `info` dictionary are written as an array with
+    ///   the hexadecimal of each SHA-1 hash: `"pieces":["abab…","cdcd…"]`.
+    /// - The `creation date` is followed by a field with the date in ISO 8601
+    ///   format: `"creation date (ISO 8601)":"2024-01-01T00:00:00Z"`.
+    /// - Compact `peers` and `peers6` lists in the root dictionary, with 6
+    ///   and 18 bytes for each peer, are written as arrays of addresses:
+    ///   `"peers":["1.2.3.4:6881"]`, `"peers6":["[::1]:6881"]`.
+    ///
+    /// [BEP 52](https://www.bittorrent.org/beps/bep_0052.html) fields:
+    ///
+    /// - The `pieces root` of each file in the `file tree` is written in
+    ///   hexadecimal: `"pieces root":"abab…"`.
+    /// - The keys of the `piece layers` dictionary are written in hexadecimal,
+    ///   and the values are written as arrays with the hexadecimal of each
+    ///   hash: `"piece layers":{"abab…":["cdcd…","efef…"]}`.
+    Torrent,
+
+    /// For the responses of HTTP trackers to announce requests.
+    ///
+    /// - Compact `peers` and `peers6` lists, described in
+    ///   [BEP 23](https://www.bittorrent.org/beps/bep_0023.html) and
+    ///   [BEP 7](https://www.bittorrent.org/beps/bep_0007.html), are written as
+    ///   arrays of objects: `"peers":[{"ip":"1.2.3.4","port":6881}]`.
+    /// - In the dictionary model of the `peers` list, the `peer id` of each
+    ///   peer is written like any other string: as text when it's valid
+    ///   UTF-8, like the `-qB4500-` IDs of many clients, and with the selected
+    ///   [`StringEncoding`] otherwise.
+    ///
+    /// The other fields, like the `failure reason`, the `interval` and the
+    /// `min interval`, are already readable and are written as in the
+    /// generic mode. The `interval` and `min interval` must be integers, and
+    /// the `failure reason` a string. Otherwise, the conversion fails with an
+    /// [`Error::UnexpectedFieldType`] error.
+    TrackerResponse,
+
+    /// For the responses of HTTP trackers to scrape requests.
+    ///
+    /// - The keys of the `files` dictionary, which are 20-byte info-hashes,
+    ///   are written in hexadecimal: `"files":{"abab…":{"complete":1}}`.
+    /// - The `complete`, `downloaded` and `incomplete` fields of each file
+    ///   must be integers. Otherwise, the conversion fails with an
+    ///   [`Error::UnexpectedFieldType`] error.
+    Scrape,
+
+    /// For KRPC messages of the DHT protocol, described in
+    /// [BEP 5](https://www.bittorrent.org/beps/bep_0005.html).
+    ///
+    /// - The transaction ID `t`, the node IDs, the `target`, the `info_hash`
+    ///   and the `token` are written in hexadecimal.
+    /// - Compact `nodes` and `nodes6` lists, with 26 and 38 bytes for each
+    ///   node, are written as arrays of objects:
+    ///   `"nodes":[{"id":"abab…","ip":"1.2.3.4","port":6881}]`.
+    /// - Each compact peer in the `values` list is written as an object:
+    ///   `"values":[{"ip":"1.2.3.4","port":6881}]`.
+    ///
+    /// Malformed messages make the conversion fail. The fields `t`, `y` and
+    /// `q` must be strings, `a` and `r` dictionaries, and `e` a list, with an
+    /// [`Error::UnexpectedFieldType`] error otherwise. A message that is not a
+    /// dictionary, or a message without the fields required by its type `y`,
+    /// fails with an [`Error::MalformedKrpcMessage`] error.
+    Krpc,
+}
+
+/// How a string is written to the output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum StringFormat {
+    /// A JSON string, or the selected [`StringEncoding`] if it's not valid
+    /// UTF-8.
+    Generic,
+
+    /// A JSON string with the plain lowercase hexadecimal of the bytes.
+    Hex,
+
+    /// A JSON array with one value for each fixed-size record in the string.
+    Records(Record),
+
+    /// The JSON value of the record. The string has the size of the record.
+    Record(Record),
+}
+
+/// The format of the fixed-size records in a string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Record {
+    /// A hash of the given size, written in plain lowercase hexadecimal.
+    Hash(usize),
+
+    /// A compact IPv4 address and port: `"1.2.3.4:6881"`.
+    PeerV4,
+
+    /// A compact IPv6 address and port: `"[::1]:6881"`.
+    PeerV6,
+
+    /// A compact IPv4 address and port as an object:
+    /// `{"ip":"1.2.3.4","port":6881}`.
+    PeerV4Object,
+
+    /// A compact IPv6 address and port as an object:
+    /// `{"ip":"::1","port":6881}`.
+    PeerV6Object,
+
+    /// A compact DHT node ID, IPv4 address and port as an object:
+    /// `{"id":"abab…","ip":"1.2.3.4","port":6881}`.
+    NodeV4,
+
+    /// A compact DHT node ID, IPv6 address and port as an object:
+    /// `{"id":"abab…","ip":"::1","port":6881}`.
+    NodeV6,
+}
+
+impl Record {
+    fn size(self) -> usize {
+        match self {
+            Self::Hash(size) => size,
+            Self::PeerV4 | Self::PeerV4Object => 6,
+            Self::PeerV6 | Self::PeerV6Object => 18,
+            Self::NodeV4 => NODE_ID_SIZE + 6,
+            Self::NodeV6 => NODE_ID_SIZE + 18,
+        }
+    }
+
+    /// It returns the JSON value of the record. The bytes always have the
+    /// size of the record.
+    fn to_json(self, bytes: &[u8]) -> String {
+        match self {
+            Self::Hash(_) => format!("\"{}\"", hex::encode(bytes)),
+            Self::PeerV4 => format!("\"{}\"", compact_peer_v4(bytes)),
+            Self::PeerV6 => format!("\"{}\"", compact_peer_v6(bytes)),
+            Self::PeerV4Object => {
+                let peer = compact_peer_v4(bytes);
+                format!(r#"{{"ip":"{}","port":{}}}"#, peer.ip(), peer.port())
+            }
+            Self::PeerV6Object => {
+                let peer = compact_peer_v6(bytes);
+                format!(r#"{{"ip":"{}","port":{}}}"#, peer.ip(), peer.port())
+            }
+            Self::NodeV4 => {
+                let (id, peer) = bytes.split_at(NODE_ID_SIZE);
+                let peer = compact_peer_v4(peer);
+                format!(
+                    r#"{{"id":"{}","ip":"{}","port":{}}}"#,
+                    hex::encode(id),
+                    peer.ip(),
+                    peer.port()
+                )
+            }
+            Self::NodeV6 => {
+                let (id, peer) = bytes.split_at(NODE_ID_SIZE);
+                let peer = compact_peer_v6(peer);
+                format!(
+                    r#"{{"id":"{}","ip":"{}","port":{}}}"#,
+                    hex::encode(id),
+                    peer.ip(),
+                    peer.port()
+                )
+            }
+        }
+    }
+}
+
+/// It decodes a compact IPv4 peer: 4 bytes for the IP address and 2 bytes for
+/// the port, in network byte order.
+fn compact_peer_v4(bytes: &[u8]) -> SocketAddrV4 {
+    let ip: [u8; 4] = bytes[..4].try_into().expect("the record has 6 bytes");
+    let port = u16::from_be_bytes([bytes[4], bytes[5]]);
+    SocketAddrV4::new(Ipv4Addr::from(ip), port)
+}
+
+/// It decodes a compact IPv6 peer: 16 bytes for the IP address and 2 bytes
+/// for the port, in network byte order.
+fn compact_peer_v6(bytes: &[u8]) -> SocketAddrV6 {
+    let ip: [u8; 16] = bytes[..16].try_into().expect("the record has 18 bytes");
+    let port = u16::from_be_bytes([bytes[16], bytes[17]]);
+    SocketAddrV6::new(Ipv6Addr::from(ip), port, 0, 0)
+}
+
+impl OutputMode {
+    /// It returns how a string is written.
+    ///
+    /// The `keys` contain the latest dictionary key at each level of the
+    /// stack, including the key of the string when it's a dictionary value,
+    /// or the string itself when it's a dictionary key. Levels that are not
+    /// dictionaries do not have a key.
+    pub(crate) fn string_format(
+        self,
+        keys: &[Option<&[u8]>],
+        is_dict_key: bool,
+        length: usize,
+    ) -> StringFormat {
+        match self {
+            Self::Generic => StringFormat::Generic,
+            Self::Torrent => torrent_string_format(keys, is_dict_key, length),
+            Self::TrackerResponse => tracker_response_string_format(keys, is_dict_key, length),
+            Self::Scrape => scrape_string_format(keys, is_dict_key, length),
+            Self::Krpc => krpc_string_format(keys, is_dict_key, length),
+        }
+    }
+
+    /// It returns the type that the value of a dictionary field must have, if
+    /// the output mode validates it. The `keys` are the same as in
+    /// [`OutputMode::string_format`] for the value.
+    pub(crate) fn expected_value_type(self, keys: &[Option<&[u8]>]) -> Option<BencodeType> {
+        match (self, keys) {
+            (Self::Scrape, [None, Some(b"files")] | [None, Some(b"files"), Some(_)]) => {
+                Some(BencodeType::Dict)
+            }
+            (
+                Self::Scrape,
+                [None, Some(b"files"), Some(_), Some(b"complete" | b"downloaded" | b"incomplete")],
+            ) => Some(BencodeType::Integer),
+            (Self::TrackerResponse, [None, Some(b"interval" | b"min interval")]) => {
+                Some(BencodeType::Integer)
+            }
+            (Self::TrackerResponse, [None, Some(b"failure reason")]) => Some(BencodeType::String),
+            (Self::Krpc, keys) => krpc_value_type(keys),
+            _ => None,
+        }
+    }
+
+    /// It returns the key and the JSON value of a field that is written after
+    /// an integer field, if any. The `keys` are the same as in
+    /// [`OutputMode::string_format`].
+    pub(crate) fn integer_companion(
+        self,
+        keys: &[Option<&[u8]>],
+        digits: &[u8],
+    ) -> Option<(&'static str, String)> {
+        match (self, keys) {
+            (Self::Torrent, [None, Some(b"creation date")]) => {
+                let timestamp = std::str::from_utf8(digits).ok()?.parse::<i64>().ok()?;
+                let date = iso_8601(timestamp)?;
+                Some((CREATION_DATE_ISO_8601_KEY, format!("\"{date}\"")))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn torrent_string_format(keys: &[Option<&[u8]>], is_dict_key: bool, length: usize) -> StringFormat {
+    match keys {
+        [None, Some(b"piece layers"), Some(_)] => {
+            if is_dict_key {
+                hex_if(length == SHA256_HASH_SIZE)
+            } else {
+                records_if_multiple(length, Record::Hash(SHA256_HASH_SIZE))
+            }
+        }
+        [None, Some(b"info"), Some(b"pieces")] if !is_dict_key => {
+            records_if_multiple(length, Record::Hash(SHA1_HASH_SIZE))
+        }
+        [None, Some(b"peers")] if !is_dict_key => records_if_multiple(length, Record::PeerV4),
+        [None, Some(b"peers6")] if !is_dict_key => records_if_multiple(length, Record::PeerV6),
+        [.., Some(b"pieces root")]
+            if !is_dict_key && keys.contains(&Some(b"file tree".as_slice())) =>
+        {
+            hex_if(length == SHA256_HASH_SIZE)
+        }
+        _ => StringFormat::Generic,
+    }
+}
+
+fn tracker_response_string_format(
+    keys: &[Option<&[u8]>],
+    is_dict_key: bool,
+    length: usize,
+) -> StringFormat {
+    if is_dict_key {
+        return StringFormat::Generic;
+    }
+
+    match keys {
+        [None, Some(b"peers")] => records_if_multiple(length, Record::PeerV4Object),
+        [None, Some(b"peers6")] => records_if_multiple(length, Record::PeerV6Object),
+        _ => StringFormat::Generic,
+    }
+}
+
+fn scrape_string_format(keys: &[Option<&[u8]>], is_dict_key: bool, length: usize) -> StringFormat {
+    match keys {
+        [None, Some(b"files"), Some(_)] if is_dict_key => hex_if(length == SHA1_HASH_SIZE),
+        _ => StringFormat::Generic,
+    }
+}
+
+fn krpc_string_format(keys: &[Option<&[u8]>], is_dict_key: bool, length: usize) -> StringFormat {
+    if is_dict_key {
+        return StringFormat::Generic;
+    }
+
+    match keys {
+        [None, Some(b"t")] | [None, Some(b"a" | b"r"), Some(b"token")] => StringFormat::Hex,
+        [None, Some(b"a" | b"r"), Some(b"id" | b"target" | b"info_hash")] => {
+            hex_if(length == NODE_ID_SIZE)
+        }
+        [None, Some(b"r"), Some(b"nodes")] => records_if_multiple(length, Record::NodeV4),
+        [None, Some(b"r"), Some(b"nodes6")] => records_if_multiple(length, Record::NodeV6),
+        [None, Some(b"r"), Some(b"values"), None] => match length {
+            6 => StringFormat::Record(Record::PeerV4Object),
+            18 => StringFormat::Record(Record::PeerV6Object),
+            _ => StringFormat::Generic,
+        },
+        _ => StringFormat::Generic,
+    }
+}
+
+fn krpc_value_type(keys: &[Option<&[u8]>]) -> Option<BencodeType> {
+    match keys {
+        [None, Some(b"t" | b"y" | b"q")]
+        | [None, Some(b"a" | b"r"), Some(b"id" | b"target" | b"info_hash" | b"token")]
+        | [None, Some(b"r"), Some(b"nodes" | b"nodes6")] => Some(BencodeType::String),
+        [None, Some(b"a" | b"r")] => Some(BencodeType::Dict),
+        [None, Some(b"e")] | [None, Some(b"r"), Some(b"values")] => Some(BencodeType::List),
+        [None, Some(b"a"), Some(b"port" | b"implied_port")] => Some(BencodeType::Integer),
+        _ => None,
+    }
+}
+
+/// The fields of a KRPC message found so far, to check that the message
+/// contains the fields required by its type when it ends.
+#[derive(Debug, Default)]
+pub(crate) struct KrpcMessage {
+    fields: Vec<Vec<u8>>,
+    message_type: Option<Vec<u8>>,
+}
+
+impl KrpcMessage {
+    /// The maximum number of bytes of the message type kept to validate it.
+    /// Valid message types only have one byte.
+    pub(crate) const MESSAGE_TYPE_CAPTURE_LIMIT: usize = 2;
+
+    /// It adds a field of the message.
+    pub(crate) fn add_field(&mut self, key: &[u8]) {
+        self.fields.push(key.to_vec());
+    }
+
+    /// It sets the message type: the value of the `y` field.
+    pub(crate) fn set_message_type(&mut self, message_type: Vec<u8>) {
+        self.message_type = Some(message_type);
+    }
+
+    /// It checks that the message has the fields required by its type.
+    ///
+    /// # Errors
+    ///
+    /// Will return the reason why the message is malformed.
+    pub(crate) fn validate(&self) -> Result<(), &'static str> {
+        if !self.has_field(b"t") {
+            return Err("missing transaction ID `t`");
+        }
+
+        let Some(message_type) = &self.message_type else {
+            return Err("missing message type `y`");
+        };
+
+        match message_type.as_slice() {
+            b"q" if !self.has_field(b"q") => Err("missing method name `q` in query"),
+            b"q" if !self.has_field(b"a") => Err("missing arguments `a` in query"),
+            b"r" if !self.has_field(b"r") => Err("missing return values `r` in response"),
+            b"e" if !self.has_field(b"e") => Err("missing error `e` in error message"),
+            b"q" | b"r" | b"e" => Ok(()),
+            _ => Err("unknown message type `y`, expected `q`, `r` or `e`"),
+        }
+    }
+
+    fn has_field(&self, key: &[u8]) -> bool {
+        self.fields.iter().any(|field| field == key)
+    }
+}
+
+fn hex_if(condition: bool) -> StringFormat {
+    if condition {
+        StringFormat::Hex
+    } else {
+        StringFormat::Generic
+    }
+}
+
+fn records_if_multiple(length: usize, record: Record) -> StringFormat {
+    if length.checked_rem(record.size()) == Some(0) {
+        StringFormat::Records(record)
+    } else {
+        StringFormat::Generic
+    }
+}
+
+/// It returns the UTC date and time of a Unix timestamp in ISO 8601 format,
+/// for example, `2024-01-01T00:00:00Z`. It returns `None` if the year does not
+/// have four digits.
+fn iso_8601(timestamp: i64) -> Option<String> {
+    let days = timestamp.div_euclid(86_400);
+    let seconds = timestamp.rem_euclid(86_400);
+
+    // Days to civil date, from http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    if !(0..=9999).contains(&year) {
+        return None;
+    }
+
+    Some(format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    ))
+}
+
+/// It writes a string in the given format while its bytes are read, in
+/// chunks.
+#[derive(Debug)]
+pub(crate) enum FormattedStringEncoder {
+    Generic(JsonStringEncoder),
+    Hex,
+    Records {
+        format: Record,
+        record: Vec<u8>,
+        records: usize,
+        in_array: bool,
+    },
+}
+
+impl FormattedStringEncoder {
+    /// It writes the beginning of the string, if the format has one.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if it can't write to the output.
+    pub(crate) fn begin<W: Writer>(
+        format: StringFormat,
+        encoding: StringEncoding,
+        writer: &mut W,
+    ) -> Result<Self, Error> {
+        match format {
+            StringFormat::Generic => Ok(Self::Generic(JsonStringEncoder::new(encoding))),
+            StringFormat::Hex => {
+                writer.write_byte(b'"')?;
+                Ok(Self::Hex)
+            }
+            StringFormat::Records(format) => {
+                writer.write_byte(b'[')?;
+                Ok(Self::Records {
+                    format,
+                    record: Vec::with_capacity(format.size()),
+                    records: 0,
+                    in_array: true,
+                })
+            }
+            StringFormat::Record(format) => Ok(Self::Records {
+                format,
+                record: Vec::with_capacity(format.size()),
+                records: 0,
+                in_array: false,
+            }),
+        }
+    }
+
+    /// It writes the next bytes of the string.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if it can't write to the output.
+    pub(crate) fn write<W: Writer>(&mut self, bytes: &[u8], writer: &mut W) -> Result<(), Error> {
+        match self {
+            Self::Generic(encoder) => encoder.write(bytes, writer),
+            Self::Hex => {
+                writer.write_str(&hex::encode(bytes))?;
+                Ok(())
+            }
+            Self::Records {
+                format,
+                record,
+                records,
+                ..
+            } => {
+                for &byte in bytes {
+                    record.push(byte);
+
+                    if record.len() == format.size() {
+                        if *records > 0 {
+                            writer.write_byte(b',')?;
+                        }
+                        writer.write_str(&format.to_json(record))?;
+
+                        record.clear();
+                        *records += 1;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// It writes the end of the string.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if it can't write to the output.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the length of the string is not a multiple of the
+    /// record size. The format is only selected when it is.
+    pub(crate) fn finish<W: Writer>(self, writer: &mut W) -> Result<(), Error> {
+        match self {
+            Self::Generic(encoder) => encoder.finish(writer),
+            Self::Hex => {
+                writer.write_byte(b'"')?;
+                Ok(())
+            }
+            Self::Records {
+                record, in_array, ..
+            } => {
+                assert!(
+                    record.is_empty(),
+                    "incomplete record at the end of the string"
+                );
+                if in_array {
+                    writer.write_byte(b']')?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parsers::{mode::OutputMode, BencodeParser};
+
+    fn torrent_json(input: &[u8]) -> String {
+        let mut output = String::new();
+
+        BencodeParser::new(input)
+            .with_output_mode(OutputMode::Torrent)
+            .write_str(&mut output)
+            .expect("Bencode to JSON conversion failed");
+
+        output
+    }
+
+    fn bencoded(prefix: &[u8], bytes: &[u8], suffix: &[u8]) -> Vec<u8> {
+        let mut input = prefix.to_vec();
+        input.extend_from_slice(format!("{}:", bytes.len()).as_bytes());
+        input.extend_from_slice(bytes);
+        input.extend_from_slice(suffix);
+        input
+    }
+
+    mod in_torrent_mode_it_should_write_in_hexadecimal {
+        use super::{bencoded, torrent_json};
+
+        #[test]
+        fn the_pieces_root_of_files_in_the_file_tree() {
+            let input = bencoded(
+                b"d4:infod9:file treed1:ad0:d6:lengthi1e11:pieces root",
+                &[0xAB; 32],
+                b"eeeee",
+            );
+
+            assert_eq!(
+                torrent_json(&input),
+                format!(
+                    r#"{{"info":{{"file tree":{{"a":{{"":{{"length":1,"pieces root":"{}"}}}}}}}}}}"#,
+                    "ab".repeat(32)
+                )
+            );
+        }
+
+        #[test]
+        fn the_keys_of_the_piece_layers_and_each_hash_of_their_values() {
+            let mut layer = [0xCD; 64].to_vec();
+            layer[32..].fill(0xEF);
+
+            let input = bencoded(
+                &bencoded(b"d12:piece layersd", &[0xAB; 32], b""),
+                &layer,
+                b"ee",
+            );
+
+            assert_eq!(
+                torrent_json(&input),
+                format!(
+                    r#"{{"piece layers":{{"{}":["{}","{}"]}}}}"#,
+                    "ab".repeat(32),
+                    "cd".repeat(32),
+                    "ef".repeat(32)
+                )
+            );
+        }
+
+        #[test]
+        fn piece_layers_longer_than_a_string_chunk() {
+            let layer = vec![0xCD; 3 * crate::parsers::string::STRING_CHUNK_SIZE];
+
+            let input = bencoded(
+                &bencoded(b"d12:piece layersd", &[0xAB; 32], b""),
+                &layer,
+                b"ee",
+            );
+
+            let hashes = vec![format!(r#""{}""#, "cd".repeat(32)); layer.len() / 32].join(",");
+
+            assert_eq!(
+                torrent_json(&input),
+                format!(r#"{{"piece layers":{{"{}":[{hashes}]}}}}"#, "ab".repeat(32))
+            );
+        }
+    }
+
+    mod in_torrent_mode_it_should_write {
+        use super::{bencoded, torrent_json};
+
+        #[test]
+        fn the_pieces_as_an_array_of_sha1_hashes() {
+            let mut pieces = [0xAB; 40].to_vec();
+            pieces[20..].fill(0xCD);
+
+            let input = bencoded(b"d4:infod6:pieces", &pieces, b"ee");
+
+            assert_eq!(
+                torrent_json(&input),
+                format!(
+                    r#"{{"info":{{"pieces":["{}","{}"]}}}}"#,
+                    "ab".repeat(20),
+                    "cd".repeat(20)
+                )
+            );
+        }
+
+        #[test]
+        fn the_creation_date_followed_by_the_date_in_iso_8601_format() {
+            assert_eq!(
+                torrent_json(b"d7:comment1:a13:creation datei1700000000e4:infodee"),
+                r#"{"comment":"a","creation date":1700000000,"creation date (ISO 8601)":"2023-11-14T22:13:20Z","info":{}}"#
+            );
+        }
+
+        #[test]
+        fn compact_ipv4_peers_as_an_array_of_addresses() {
+            let input = bencoded(
+                b"d5:peers",
+                b"\x01\x02\x03\x04\x1a\xe1\x7f\x00\x00\x01\x00\x50",
+                b"e",
+            );
+
+            assert_eq!(
+                torrent_json(&input),
+                r#"{"peers":["1.2.3.4:6881","127.0.0.1:80"]}"#
+            );
+        }
+
+        #[test]
+        fn compact_ipv6_peers_as_an_array_of_addresses() {
+            let mut peer = [0; 18];
+            peer[15] = 1;
+            peer[16..].copy_from_slice(&6881u16.to_be_bytes());
+
+            let input = bencoded(b"d6:peers6", &peer, b"e");
+
+            assert_eq!(torrent_json(&input), r#"{"peers6":["[::1]:6881"]}"#);
+        }
+    }
+
+    mod in_torrent_mode_it_should_write_as_in_the_generic_mode {
+        use super::{bencoded, torrent_json};
+        use crate::try_bencode_to_json;
+
+        #[test]
+        fn a_pieces_root_that_does_not_have_32_bytes() {
+            let input = bencoded(b"d9:file treed1:ad0:d11:pieces root", &[0xAB; 31], b"eeee");
+
+            assert_eq!(torrent_json(&input), try_bencode_to_json(&input).unwrap());
+        }
+
+        #[test]
+        fn a_pieces_root_outside_the_file_tree() {
+            let input = bencoded(b"d11:pieces root", &[0xAB; 32], b"e");
+
+            assert_eq!(torrent_json(&input), try_bencode_to_json(&input).unwrap());
+        }
+
+        #[test]
+        fn a_piece_layer_whose_length_is_not_a_multiple_of_32() {
+            let input = bencoded(
+                &bencoded(b"d12:piece layersd", &[0xAB; 32], b""),
+                &[0xCD; 33],
+                b"ee",
+            );
+
+            assert_eq!(
+                torrent_json(&input),
+                format!(
+                    r#"{{"piece layers":{{"{}":"<hex>{}</hex>"}}}}"#,
+                    "ab".repeat(32),
+                    "cd".repeat(33)
+                )
+            );
+        }
+
+        #[test]
+        fn pieces_whose_length_is_not_a_multiple_of_20() {
+            let input = bencoded(b"d4:infod6:pieces", &[0xAB; 21], b"ee");
+
+            assert_eq!(torrent_json(&input), try_bencode_to_json(&input).unwrap());
+        }
+
+        #[test]
+        fn a_creation_date_that_is_not_in_the_root_dictionary() {
+            let input = b"d4:infod13:creation datei1700000000eee";
+
+            assert_eq!(torrent_json(input), try_bencode_to_json(input).unwrap());
+        }
+
+        #[test]
+        fn a_creation_date_that_does_not_fit_in_a_four_digit_year() {
+            let input = b"d13:creation datei99999999999999ee";
+
+            assert_eq!(torrent_json(input), try_bencode_to_json(input).unwrap());
+        }
+
+        #[test]
+        fn peers_that_are_not_compact() {
+            let input = b"d5:peersld2:ip7:1.2.3.44:porti6881eeee";
+
+            assert_eq!(torrent_json(input), try_bencode_to_json(input).unwrap());
+        }
+
+        #[test]
+        fn peers_that_are_not_in_the_root_dictionary() {
+            let input = b"d4:infod5:peers6:abcdefee";
+
+            assert_eq!(torrent_json(input), try_bencode_to_json(input).unwrap());
+        }
+
+        #[test]
+        fn piece_layers_that_are_not_in_the_root_dictionary() {
+            let input = bencoded(b"d4:infod12:piece layersd1:a", &[0xCD; 32], b"eee");
+
+            assert_eq!(torrent_json(&input), try_bencode_to_json(&input).unwrap());
+        }
+    }
+
+    mod in_tracker_response_mode_it_should_write {
+        use super::bencoded;
+        use crate::{
+            parsers::{mode::OutputMode, string::StringEncoding, BencodeParser},
+            try_bencode_to_json,
+        };
+
+        fn tracker_response_json(input: &[u8]) -> String {
+            let mut output = String::new();
+
+            BencodeParser::new(input)
+                .with_output_mode(OutputMode::TrackerResponse)
+                .write_str(&mut output)
+                .expect("Bencode to JSON conversion failed");
+
+            output
+        }
+
+        #[test]
+        fn compact_ipv4_peers_as_an_array_of_objects() {
+            let input = bencoded(
+                b"d8:completei1e10:incompletei0e8:intervali1800e12:min intervali900e5:peers",
+                b"\x01\x02\x03\x04\x1a\xe1\x7f\x00\x00\x01\x00\x50",
+                b"e",
+            );
+
+            assert_eq!(
+                tracker_response_json(&input),
+                r#"{"complete":1,"incomplete":0,"interval":1800,"min interval":900,"peers":[{"ip":"1.2.3.4","port":6881},{"ip":"127.0.0.1","port":80}]}"#
+            );
+        }
+
+        #[test]
+        fn compact_ipv6_peers_as_an_array_of_objects() {
+            let mut peer = [0; 18];
+            peer[15] = 1;
+            peer[16..].copy_from_slice(&6881u16.to_be_bytes());
+
+            let input = bencoded(b"d6:peers6", &peer, b"e");
+
+            assert_eq!(
+                tracker_response_json(&input),
+                r#"{"peers6":[{"ip":"::1","port":6881}]}"#
+            );
+        }
+
+        #[test]
+        fn the_utf8_peer_ids_of_the_dictionary_model_peers_as_text() {
+            let input = b"d5:peersld2:ip7:1.2.3.47:peer id20:-qB4500-abcdefghijkl4:porti6881eeee";
+
+            assert_eq!(
+                tracker_response_json(input),
+                r#"{"peers":[{"ip":"1.2.3.4","peer id":"-qB4500-abcdefghijkl","port":6881}]}"#
+            );
+        }
+
+        #[test]
+        fn the_non_utf8_peer_ids_of_the_dictionary_model_peers_with_the_selected_encoding() {
+            let input = bencoded(
+                b"d5:peersld2:ip7:1.2.3.47:peer id",
+                &[0xAB; 20],
+                b"4:porti6881eeee",
+            );
+
+            let mut output = String::new();
+
+            BencodeParser::new(&input[..])
+                .with_output_mode(OutputMode::TrackerResponse)
+                .with_string_encoding(StringEncoding::Base64)
+                .write_str(&mut output)
+                .unwrap();
+
+            assert_eq!(
+                output,
+                r#"{"peers":[{"ip":"1.2.3.4","peer id":"q6urq6urq6urq6urq6urq6urq6s=","port":6881}]}"#
+            );
+            assert_eq!(
+                tracker_response_json(&input),
+                format!(
+                    r#"{{"peers":[{{"ip":"1.2.3.4","peer id":"<hex>{}</hex>","port":6881}}]}}"#,
+                    "ab".repeat(20)
+                )
+            );
+        }
+
+        #[test]
+        fn the_failure_reason_as_in_the_generic_mode() {
+            let input = b"d14:failure reason17:torrent not founde";
+
+            assert_eq!(
+                tracker_response_json(input),
+                r#"{"failure reason":"torrent not found"}"#
+            );
+        }
+
+        #[test]
+        fn compact_peers_whose_length_is_not_a_multiple_of_6_as_in_the_generic_mode() {
+            let input = bencoded(b"d5:peers", b"\x01\x02\x03\x04\x1a", b"e");
+
+            assert_eq!(
+                tracker_response_json(&input),
+                try_bencode_to_json(&input).unwrap()
+            );
+        }
+    }
+
+    mod in_tracker_response_mode_it_should_fail_when {
+        use crate::parsers::{error::Error, mode::OutputMode, BencodeParser, BencodeType};
+
+        fn tracker_response_json(input: &[u8]) -> Result<String, Error> {
+            let mut output = String::new();
+
+            BencodeParser::new(input)
+                .with_output_mode(OutputMode::TrackerResponse)
+                .write_str(&mut output)?;
+
+            Ok(output)
+        }
+
+        #[test]
+        fn the_interval_is_not_an_integer() {
+            assert!(matches!(
+                tracker_response_json(b"d8:interval3:abce"),
+                Err(Error::UnexpectedFieldType(field, BencodeType::Integer, BencodeType::String, _, _)) if field == "interval"
+            ));
+        }
+
+        #[test]
+        fn the_min_interval_is_not_an_integer() {
+            assert!(matches!(
+                tracker_response_json(b"d12:min intervalli900eee"),
+                Err(Error::UnexpectedFieldType(field, BencodeType::Integer, BencodeType::List, _, _)) if field == "min interval"
+            ));
+        }
+
+        #[test]
+        fn the_failure_reason_is_not_a_string() {
+            assert!(matches!(
+                tracker_response_json(b"d14:failure reasoni1ee"),
+                Err(Error::UnexpectedFieldType(field, BencodeType::String, BencodeType::Integer, _, _)) if field == "failure reason"
+            ));
+        }
+    }
+
+    mod in_scrape_mode {
+        use super::bencoded;
+        use crate::parsers::{error::Error, mode::OutputMode, BencodeParser, BencodeType};
+
+        fn scrape_json(input: &[u8]) -> Result<String, Error> {
+            let mut output = String::new();
+
+            BencodeParser::new(input)
+                .with_output_mode(OutputMode::Scrape)
+                .write_str(&mut output)?;
+
+            Ok(output)
+        }
+
+        #[test]
+        fn it_should_write_the_info_hashes_of_the_files_in_hexadecimal() {
+            let input = bencoded(
+                b"d5:filesd",
+                &[0xAB; 20],
+                b"d8:completei5e10:downloadedi50e10:incompletei10eeee",
+            );
+
+            assert_eq!(
+                scrape_json(&input).unwrap(),
+                format!(
+                    r#"{{"files":{{"{}":{{"complete":5,"downloaded":50,"incomplete":10}}}}}}"#,
+                    "ab".repeat(20)
+                )
+            );
+        }
+
+        #[test]
+        fn it_should_write_keys_that_are_not_info_hashes_as_in_the_generic_mode() {
+            assert_eq!(
+                scrape_json(b"d5:filesd1:\xFFdeee").unwrap(),
+                r#"{"files":{"<hex>ff</hex>":{}}}"#
+            );
+        }
+
+        #[test]
+        fn it_should_allow_other_fields_in_the_files() {
+            let input = bencoded(b"d5:filesd", &[0xAB; 20], b"d4:name1:aeee");
+
+            assert!(scrape_json(&input).is_ok());
+        }
+
+        mod it_should_fail_when {
+            use super::{bencoded, scrape_json};
+            use crate::parsers::{error::Error, BencodeType};
+
+            #[test]
+            fn the_files_field_is_not_a_dictionary() {
+                assert!(matches!(
+                    scrape_json(b"d5:fileslee"),
+                    Err(Error::UnexpectedFieldType(field, BencodeType::Dict, BencodeType::List, _, _)) if field == "files"
+                ));
+            }
+
+            #[test]
+            fn a_file_is_not_a_dictionary() {
+                let input = bencoded(b"d5:filesd", &[0xAB; 20], b"i1eee");
+
+                assert!(matches!(
+                    scrape_json(&input),
+                    Err(Error::UnexpectedFieldType(
+                        _,
+                        BencodeType::Dict,
+                        BencodeType::Integer,
+                        _,
+                        _
+                    ))
+                ));
+            }
+
+            #[test]
+            fn a_counter_is_not_an_integer() {
+                for counter in ["complete", "downloaded", "incomplete"] {
+                    let input = bencoded(
+                        b"d5:filesd",
+                        &[0xAB; 20],
+                        format!("d{}:{counter}1:5eee", counter.len()).as_bytes(),
+                    );
+
+                    assert!(matches!(
+                        scrape_json(&input),
+                        Err(Error::UnexpectedFieldType(field, BencodeType::Integer, BencodeType::String, _, _)) if field == counter
+                    ));
+                }
+            }
+
+            #[test]
+            fn a_counter_is_a_list() {
+                let input = bencoded(b"d5:filesd", &[0xAB; 20], b"d8:completeli5eeeee");
+
+                assert!(matches!(
+                    scrape_json(&input),
+                    Err(Error::UnexpectedFieldType(field, BencodeType::Integer, BencodeType::List, _, _)) if field == "complete"
+                ));
+            }
+        }
+
+        #[test]
+        fn it_should_include_the_read_context_in_errors() {
+            let input = bencoded(b"d5:filesd", &[0xAB; 20], b"d8:complete1:5eee");
+
+            let Err(Error::UnexpectedFieldType(_, _, _, read_context, _)) = scrape_json(&input)
+            else {
+                panic!("expected an unexpected field type error");
+            };
+
+            // Position after the length of the string value
+            assert_eq!(read_context.pos, 45);
+        }
+
+        #[test]
+        fn it_should_not_validate_the_fields_in_other_modes() {
+            let input = b"d5:fileslee";
+
+            let mut output = String::new();
+
+            BencodeParser::new(&input[..])
+                .write_str(&mut output)
+                .unwrap();
+
+            assert_eq!(output, r#"{"files":[]}"#);
+            assert!(matches!(
+                scrape_json(input),
+                Err(Error::UnexpectedFieldType(_, BencodeType::Dict, _, _, _))
+            ));
+        }
+    }
+
+    mod in_krpc_mode {
+        use super::bencoded;
+        use crate::parsers::{error::Error, mode::OutputMode, BencodeParser};
+
+        fn krpc_json(input: &[u8]) -> Result<String, Error> {
+            let mut output = String::new();
+
+            BencodeParser::new(input)
+                .with_output_mode(OutputMode::Krpc)
+                .write_str(&mut output)?;
+
+            Ok(output)
+        }
+
+        fn node_id(byte: u8) -> String {
+            hex::encode([byte; 20])
+        }
+
+        mod it_should_write {
+            use super::{bencoded, krpc_json, node_id};
+
+            #[test]
+            fn the_transaction_id_and_the_node_ids_of_queries_in_hexadecimal() {
+                let input = bencoded(
+                    &bencoded(b"d1:ad2:id", &[0xAB; 20], b"6:target"),
+                    &[0xCD; 20],
+                    b"e1:q9:find_node1:t2:\x00\x011:y1:qe",
+                );
+
+                assert_eq!(
+                    krpc_json(&input).unwrap(),
+                    format!(
+                        r#"{{"a":{{"id":"{}","target":"{}"}},"q":"find_node","t":"0001","y":"q"}}"#,
+                        node_id(0xAB),
+                        node_id(0xCD)
+                    )
+                );
+            }
+
+            #[test]
+            fn compact_nodes_as_an_array_of_objects() {
+                let mut nodes = [0xCD; 20].to_vec();
+                nodes.extend_from_slice(b"\x01\x02\x03\x04\x1a\xe1");
+
+                let input = bencoded(
+                    &bencoded(b"d1:rd2:id", &[0xAB; 20], b"5:nodes"),
+                    &nodes,
+                    b"e1:t1:a1:y1:re",
+                );
+
+                assert_eq!(
+                    krpc_json(&input).unwrap(),
+                    format!(
+                        r#"{{"r":{{"id":"{}","nodes":[{{"id":"{}","ip":"1.2.3.4","port":6881}}]}},"t":"61","y":"r"}}"#,
+                        node_id(0xAB),
+                        node_id(0xCD)
+                    )
+                );
+            }
+
+            #[test]
+            fn compact_ipv6_nodes_as_an_array_of_objects() {
+                let mut nodes = [0xCD; 20].to_vec();
+                nodes.extend_from_slice(&[0; 15]);
+                nodes.extend_from_slice(b"\x01\x1a\xe1");
+
+                let input = bencoded(b"d1:rd6:nodes6", &nodes, b"e1:t1:a1:y1:re");
+
+                assert_eq!(
+                    krpc_json(&input).unwrap(),
+                    format!(
+                        r#"{{"r":{{"nodes6":[{{"id":"{}","ip":"::1","port":6881}}]}},"t":"61","y":"r"}}"#,
+                        node_id(0xCD)
+                    )
+                );
+            }
+
+            #[test]
+            fn the_values_as_an_array_of_peers() {
+                let input = bencoded(
+                    b"d1:rd5:token2:\xAA\xBB6:valuesl6:\x01\x02\x03\x04\x1a\xe1",
+                    &[0; 18],
+                    b"ee1:t1:a1:y1:re",
+                );
+
+                assert_eq!(
+                    krpc_json(&input).unwrap(),
+                    r#"{"r":{"token":"aabb","values":[{"ip":"1.2.3.4","port":6881},{"ip":"::","port":0}]},"t":"61","y":"r"}"#
+                );
+            }
+
+            #[test]
+            fn error_messages_as_in_the_generic_mode() {
+                assert_eq!(
+                    krpc_json(b"d1:eli201e23:A Generic Error Ocurrede1:t2:aa1:y1:ee").unwrap(),
+                    r#"{"e":[201,"A Generic Error Ocurred"],"t":"6161","y":"e"}"#
+                );
+            }
+        }
+
+        mod it_should_fail_when {
+            use super::krpc_json;
+            use crate::parsers::{error::Error, BencodeType};
+
+            fn malformed_reason(input: &[u8]) -> &'static str {
+                match krpc_json(input) {
+                    Err(Error::MalformedKrpcMessage(reason, _, _)) => reason,
+                    result => panic!("expected a malformed KRPC message error, got {result:?}"),
+                }
+            }
+
+            #[test]
+            fn the_message_is_not_a_dictionary() {
+                assert_eq!(
+                    malformed_reason(b"l1:t1:ye"),
+                    "the message is not a dictionary"
+                );
+            }
+
+            #[test]
+            fn the_transaction_id_is_missing() {
+                assert_eq!(malformed_reason(b"d1:y1:re"), "missing transaction ID `t`");
+            }
+
+            #[test]
+            fn the_message_type_is_missing() {
+                assert_eq!(malformed_reason(b"d1:t2:aae"), "missing message type `y`");
+            }
+
+            #[test]
+            fn the_message_type_is_unknown() {
+                assert_eq!(
+                    malformed_reason(b"d1:t2:aa1:y1:xe"),
+                    "unknown message type `y`, expected `q`, `r` or `e`"
+                );
+                assert_eq!(
+                    malformed_reason(b"d1:t2:aa1:y2:qqe"),
+                    "unknown message type `y`, expected `q`, `r` or `e`"
+                );
+            }
+
+            #[test]
+            fn a_query_does_not_have_the_method_name_or_the_arguments() {
+                assert_eq!(
+                    malformed_reason(b"d1:ade1:t2:aa1:y1:qe"),
+                    "missing method name `q` in query"
+                );
+                assert_eq!(
+                    malformed_reason(b"d1:q4:ping1:t2:aa1:y1:qe"),
+                    "missing arguments `a` in query"
+                );
+            }
+
+            #[test]
+            fn a_response_does_not_have_the_return_values() {
+                assert_eq!(
+                    malformed_reason(b"d1:t2:aa1:y1:re"),
+                    "missing return values `r` in response"
+                );
+            }
+
+            #[test]
+            fn an_error_message_does_not_have_the_error() {
+                assert_eq!(
+                    malformed_reason(b"d1:t2:aa1:y1:ee"),
+                    "missing error `e` in error message"
+                );
+            }
+
+            #[test]
+            fn a_field_does_not_have_the_expected_type() {
+                assert!(matches!(
+                    krpc_json(b"d1:ai1e1:q4:ping1:t2:aa1:y1:qe"),
+                    Err(Error::UnexpectedFieldType(field, BencodeType::Dict, BencodeType::Integer, _, _)) if field == "a"
+                ));
+                assert!(matches!(
+                    krpc_json(b"d1:ad4:port1:1e1:q4:ping1:t2:aa1:y1:qe"),
+                    Err(Error::UnexpectedFieldType(field, BencodeType::Integer, BencodeType::String, _, _)) if field == "port"
+                ));
+            }
+
+            #[test]
+            fn including_the_position_of_the_end_of_the_message() {
+                let input = b"d1:t2:aa1:y1:re";
+
+                let Err(Error::MalformedKrpcMessage(_, read_context, _)) = krpc_json(input) else {
+                    panic!("expected a malformed KRPC message error");
+                };
+
+                assert_eq!(read_context.pos, input.len() as u64);
+            }
+        }
+    }
+
+    #[test]
+    fn the_generic_mode_should_not_change_torrent_fields() {
+        let input = bencoded(b"d13:creation datei0e4:infod6:pieces", &[0xAB; 20], b"ee");
+
+        assert_eq!(
+            crate::try_bencode_to_json(&input).unwrap(),
+            format!(
+                r#"{{"creation date":0,"info":{{"pieces":"<hex>{}</hex>"}}}}"#,
+                "ab".repeat(20)
+            )
+        );
+    }
+
+    mod the_iso_8601_date {
+        use crate::parsers::mode::iso_8601;
+
+        #[test]
+        fn of_the_unix_epoch() {
+            assert_eq!(iso_8601(0).unwrap(), "1970-01-01T00:00:00Z");
+        }
+
+        #[test]
+        fn of_a_leap_day() {
+            assert_eq!(
+                iso_8601(951_782_400 + 86_399).unwrap(),
+                "2000-02-29T23:59:59Z"
+            );
+        }
+
+        #[test]
+        fn of_a_timestamp_before_the_unix_epoch() {
+            assert_eq!(iso_8601(-1).unwrap(), "1969-12-31T23:59:59Z");
+        }
+
+        #[test]
+        fn of_a_timestamp_whose_year_does_not_have_four_digits() {
+            assert_eq!(iso_8601(253_402_300_800), None);
+            assert_eq!(iso_8601(i64::MIN), None);
+        }
+    }
+}
@@ -1,6 +1,6 @@
 //! The stack used by the Bencoded to JSON converter to keep track of the
 //! current parsing state.
-use std::fmt::Display;
+use std::fmt::{Display, Write};
 
 /// Stack with containing states for nested Bencoded values.
 ///
@@ -8,8 +8,24 @@ use std::fmt::Display;
 #[derive(Debug)]
 pub struct Stack {
     states: Vec<State>,
+
+    /// The latest dictionary key parsed at each level. It's always `None` for
+    /// levels that are not dictionaries.
+    keys: Vec<Option<Vec<u8>>>,
+
+    /// The index of the latest item begun at each level. It's always `None`
+    /// for levels that are not lists, or lists without items yet.
+    indexes: Vec<Option<usize>>,
+
+    /// The maximum number of nested lists and dictionaries.
+    max_depth: Option<usize>,
 }
 
+/// The error returned when pushing a new state would exceed the maximum
+/// depth of the stack.
+#[derive(Debug, PartialEq)]
+pub struct MaxDepthExceeded;
+
 /// States while parsing list or dictionaries.
 ///
 /// There are no states for integers and strings because it's a straightforward
@@ -51,7 +67,14 @@ impl Display for State {
 impl Default for Stack {
     fn default() -> Self {
         let states = vec![State::Initial];
-        Self { states }
+        let keys = vec![None];
+        let indexes = vec![None];
+        Self {
+            states,
+            keys,
+            indexes,
+            max_depth: None,
+        }
     }
 }
 
@@ -73,9 +96,41 @@ impl Display for Stack {
 }
 
 impl Stack {
+    /// It creates a stack that can only contain up to `max_depth` nested
+    /// lists and dictionaries.
+    #[must_use]
+    pub fn with_max_depth(max_depth: usize) -> Self {
+        Self {
+            max_depth: Some(max_depth),
+            ..Self::default()
+        }
+    }
+
     /// It adds a new state to the stack.
-    pub fn push(&mut self, state: State) {
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the stack already contains the maximum number
+    /// of nested lists and dictionaries.
+    pub fn push(&mut self, state: State) -> Result<(), MaxDepthExceeded> {
+        if self
+            .max_depth
+            .is_some_and(|max_depth| self.depth() >= max_depth)
+        {
+            return Err(MaxDepthExceeded);
+        }
+
         self.states.push(state);
+        self.keys.push(None);
+        self.indexes.push(None);
+
+        Ok(())
+    }
+
+    /// It returns the number of nested lists and dictionaries.
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.states.len() - 1
     }
 
     /// It returns and consumes the stack top.
@@ -88,6 +143,8 @@ impl Stack {
     pub fn pop(&mut self) {
         self.guard_immutable_initial_state();
         self.states.pop();
+        self.keys.pop();
+        self.indexes.pop();
     }
 
     /// It swaps the stack top with the new state.
@@ -100,7 +157,7 @@ impl Stack {
     pub fn swap_top(&mut self, new_state: State) {
         self.guard_immutable_initial_state();
         self.states.pop();
-        self.push(new_state);
+        self.states.push(new_state);
     }
 
     /// It returns the top element on the stack without consuming it.
@@ -117,6 +174,86 @@ impl Stack {
         }
     }
 
+    /// It returns the latest dictionary key parsed in the dictionary on top
+    /// of the stack, if any.
+    #[must_use]
+    pub fn last_key(&self) -> Option<&[u8]> {
+        self.keys.last().and_then(Option::as_deref)
+    }
+
+    /// It returns the latest dictionary key parsed at each level of the
+    /// stack, from the bottom to the top. Levels that are not dictionaries, or
+    /// dictionaries without keys yet, do not have a key.
+    #[must_use]
+    pub fn last_keys(&self) -> Vec<Option<&[u8]>> {
+        self.keys.iter().map(Option::as_deref).collect()
+    }
+
+    /// It sets the latest dictionary key parsed in the dictionary on top of
+    /// the stack.
+    pub fn set_last_key(&mut self, key: Vec<u8>) {
+        if let Some(top) = self.keys.last_mut() {
+            *top = Some(key);
+        }
+    }
+
+    /// It moves to the next item in the list on top of the stack.
+    pub fn begin_list_item(&mut self) {
+        if let Some(top) = self.indexes.last_mut() {
+            *top = Some(top.map_or(0, |index| index + 1));
+        }
+    }
+
+    /// It returns the path to the current value from the top-level value,
+    /// with the latest dictionary key and list index at each level, for
+    /// example, `info.files[127].path[2]`. It's empty at the top level.
+    ///
+    /// Keys that are not valid UTF-8, or that contain `.`, `[`, `]` or quotes,
+    /// are written between brackets and quotes: `info["a.b"]`.
+    #[must_use]
+    pub fn path(&self) -> String {
+        let mut path = String::new();
+
+        for (key, index) in self.keys.iter().zip(&self.indexes) {
+            write_segment(&mut path, key.as_deref(), *index);
+        }
+
+        path
+    }
+
+    /// It returns the path to the next value, the one expected by the state
+    /// on top of the stack, before its first byte is read. In a list, it's the
+    /// index of the next item. When a dictionary key is expected, it's the
+    /// path to the dictionary, because the key is not known yet.
+    ///
+    /// For example, the path is `[2]` before reading the third item of a
+    /// list, while [`Stack::path`] is still `[1]`.
+    #[must_use]
+    pub fn next_path(&self) -> String {
+        let mut path = String::new();
+
+        let top = self.states.len() - 1;
+
+        for (level, (key, index)) in self.keys.iter().zip(&self.indexes).enumerate() {
+            if level < top {
+                write_segment(&mut path, key.as_deref(), *index);
+                continue;
+            }
+
+            match self.peek() {
+                State::Initial
+                | State::ExpectingFirstDictFieldOrEnd
+                | State::ExpectingDictFieldKeyOrEnd => {}
+                State::ExpectingFirstListItemOrEnd | State::ExpectingNextListItem => {
+                    write_segment(&mut path, None, Some(index.map_or(0, |index| index + 1)));
+                }
+                State::ExpectingDictFieldValue => write_segment(&mut path, key.as_deref(), None),
+            }
+        }
+
+        path
+    }
+
     /// Prevent from mutating the initial state.
     fn guard_immutable_initial_state(&self) {
         if let Some(top) = self.states.last() {
@@ -129,6 +266,37 @@ impl Stack {
     }
 }
 
+/// It appends the dictionary key or, when there is no key, the list index of
+/// a level to the path.
+fn write_segment(path: &mut String, key: Option<&[u8]>, index: Option<usize>) {
+    if let Some(key) = key {
+        write_key_segment(path, key);
+    } else if let Some(index) = index {
+        write!(path, "[{index}]").expect("writing to a string should not fail");
+    }
+}
+
+/// It appends a dictionary key to the path.
+fn write_key_segment(path: &mut String, key: &[u8]) {
+    match std::str::from_utf8(key) {
+        Ok(key)
+            if !key.is_empty()
+                && !key
+                    .chars()
+                    .any(|char| char.is_control() || ".[]\"'".contains(char)) =>
+        {
+            if !path.is_empty() {
+                path.push('.');
+            }
+            path.push_str(key);
+        }
+        _ => {
+            write!(path, "[{:?}]", String::from_utf8_lossy(key))
+                .expect("writing to a string should not fail");
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     mod the_stack_state {
@@ -147,7 +315,7 @@ mod tests {
 
     mod the_stack {
         mod it_should {
-            use crate::parsers::stack::{Stack, State};
+            use crate::parsers::stack::{MaxDepthExceeded, Stack, State};
 
             #[test]
             fn have_an_initial_state() {
@@ -178,7 +346,7 @@ mod tests {
             fn allow_pushing_new_states() {
                 let mut stack = Stack::default();
 
-                stack.push(State::ExpectingDictFieldKeyOrEnd);
+                stack.push(State::ExpectingDictFieldKeyOrEnd).unwrap();
 
                 assert_eq!(stack.peek(), State::ExpectingDictFieldKeyOrEnd);
             }
@@ -187,7 +355,7 @@ mod tests {
             fn allow_popping_the_current_top_state() {
                 let mut stack = Stack::default();
 
-                stack.push(State::ExpectingDictFieldKeyOrEnd);
+                stack.push(State::ExpectingDictFieldKeyOrEnd).unwrap();
                 stack.pop();
 
                 assert_eq!(stack.peek(), State::Initial);
@@ -203,7 +371,7 @@ mod tests {
             fn allow_swapping_the_top_state() {
                 let mut stack = Stack::default();
 
-                stack.push(State::ExpectingDictFieldKeyOrEnd);
+                stack.push(State::ExpectingDictFieldKeyOrEnd).unwrap();
                 stack.swap_top(State::ExpectingDictFieldValue);
 
                 assert_eq!(stack.peek(), State::ExpectingDictFieldValue);
@@ -215,6 +383,115 @@ mod tests {
                 Stack::default().swap_top(State::Initial);
             }
 
+            #[test]
+            fn not_allow_pushing_more_states_than_the_maximum_depth() {
+                let mut stack = Stack::with_max_depth(1);
+
+                stack.push(State::ExpectingFirstListItemOrEnd).unwrap();
+
+                assert_eq!(
+                    stack.push(State::ExpectingFirstListItemOrEnd),
+                    Err(MaxDepthExceeded)
+                );
+                assert_eq!(stack.depth(), 1);
+            }
+
+            #[test]
+            fn keep_the_last_dictionary_key_for_each_level() {
+                let mut stack = Stack::default();
+
+                stack.push(State::ExpectingFirstDictFieldOrEnd).unwrap();
+                stack.set_last_key(b"outer".to_vec());
+                stack.swap_top(State::ExpectingDictFieldValue);
+
+                stack.push(State::ExpectingFirstDictFieldOrEnd).unwrap();
+                assert_eq!(stack.last_key(), None);
+
+                stack.set_last_key(b"inner".to_vec());
+                assert_eq!(stack.last_key(), Some(&b"inner"[..]));
+                assert_eq!(
+                    stack.last_keys(),
+                    vec![None, Some(&b"outer"[..]), Some(&b"inner"[..])]
+                );
+
+                stack.pop();
+                assert_eq!(stack.last_key(), Some(&b"outer"[..]));
+            }
+
+            #[test]
+            fn keep_the_index_of_the_latest_list_item_for_each_level() {
+                let mut stack = Stack::default();
+
+                stack.push(State::ExpectingFirstListItemOrEnd).unwrap();
+                stack.begin_list_item();
+                stack.begin_list_item();
+
+                stack.push(State::ExpectingFirstListItemOrEnd).unwrap();
+                assert_eq!(stack.path(), "[1]");
+
+                stack.begin_list_item();
+                assert_eq!(stack.path(), "[1][0]");
+
+                stack.pop();
+                stack.begin_list_item();
+                assert_eq!(stack.path(), "[2]");
+            }
+
+            #[test]
+            fn build_the_path_with_the_dictionary_keys_and_list_indexes() {
+                let mut stack = Stack::default();
+
+                stack.push(State::ExpectingFirstDictFieldOrEnd).unwrap();
+                stack.set_last_key(b"info".to_vec());
+
+                stack.push(State::ExpectingFirstDictFieldOrEnd).unwrap();
+                stack.set_last_key(b"files".to_vec());
+
+                stack.push(State::ExpectingFirstListItemOrEnd).unwrap();
+                stack.begin_list_item();
+
+                stack.push(State::ExpectingFirstDictFieldOrEnd).unwrap();
+                stack.set_last_key(b"path".to_vec());
+
+                assert_eq!(stack.path(), "info.files[0].path");
+            }
+
+            #[test]
+            fn build_the_path_to_the_next_value_before_it_begins() {
+                let mut stack = Stack::default();
+                assert_eq!(stack.next_path(), "");
+
+                stack.push(State::ExpectingFirstListItemOrEnd).unwrap();
+                assert_eq!(stack.next_path(), "[0]");
+
+                stack.swap_top(State::ExpectingNextListItem);
+                stack.begin_list_item();
+                assert_eq!(stack.next_path(), "[1]");
+
+                stack.push(State::ExpectingFirstDictFieldOrEnd).unwrap();
+                assert_eq!(stack.next_path(), "[0]");
+
+                stack.set_last_key(b"a".to_vec());
+                stack.swap_top(State::ExpectingDictFieldValue);
+                assert_eq!(stack.next_path(), "[0].a");
+
+                stack.swap_top(State::ExpectingDictFieldKeyOrEnd);
+                assert_eq!(stack.next_path(), "[0]");
+            }
+
+            #[test]
+            fn quote_the_keys_that_cannot_be_written_as_plain_path_segments() {
+                let mut stack = Stack::default();
+
+                stack.push(State::ExpectingFirstDictFieldOrEnd).unwrap();
+                stack.set_last_key(b"a.b".to_vec());
+
+                stack.push(State::ExpectingFirstDictFieldOrEnd).unwrap();
+                stack.set_last_key(b"\xFF".to_vec());
+
+                assert_eq!(stack.path(), "[\"a.b\"][\"\u{fffd}\"]");
+            }
+
             mod be_displayed_with_single_letter_abbreviations_for_states {
 
                 use crate::parsers::stack::{Stack, State};
@@ -230,7 +507,7 @@ mod tests {
                 fn after_pushing_one_more_state() {
                     let mut stack = Stack::default();
 
-                    stack.push(State::ExpectingDictFieldKeyOrEnd);
+                    stack.push(State::ExpectingDictFieldKeyOrEnd).unwrap();
 
                     assert_eq!(format!("{stack}"), "[I, F]");
                 }
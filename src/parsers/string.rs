@@ -1,94 +1,658 @@
 //! Bencoded string parser.
 //!
 //! It reads bencoded bytes from the input and writes JSON bytes to the output.
+//!
+//! Strings are written while they are read, in chunks of [`STRING_CHUNK_SIZE`]
+//! bytes, so the memory used does not depend on the size of the strings.
+//!
+//! UTF-8 is validated incrementally. If a string is not valid UTF-8, it's
+//! written with the selected [`StringEncoding`]. With the default
+//! [`StringEncoding::HexWrapped`] encoding, that's only possible when the first
+//! invalid byte is found in the first chunk. Otherwise, the text before the
+//! first invalid byte has already been written, and the rest of the string is
+//! written in hexadecimal inside the same JSON string:
+//! `"<valid UTF-8 text><hex>fffe</hex>"`. Other encodings can't be mixed with
+//! text, so they keep valid UTF-8 strings in memory until they end or an
+//! invalid byte is found.
+//!
+//! Valid UTF-8 strings that look like the hex-wrapped format, for example
+//! `<hex>fffe</hex>`, are written in that format too (`"<hex>3c6865783e…</hex>"`),
+//! so they are not confused with strings that are not valid UTF-8 when the
+//! JSON is converted back into bencode. Strings longer than a chunk are
+//! written as text, and only their end is written in that format when it
+//! looks like it: `"<text><hex>3c6865783e…</hex>"`.
 use std::io::{self, Read};
+use std::mem;
 
 use crate::rw::{byte_reader::ByteReader, writer::Writer};
 
-/* todo: Optimize UTF-8 conversion. Try to convert to string partially and stop
-    converting if we reach a point when input is not valid UTF-8 anymore. This
-    way we don't consume more memory and we can print the bytes directly to the
-    output from that point on.
-*/
-
 use core::str;
+use std::fmt::Write;
+
+use base64::prelude::{Engine, BASE64_STANDARD, BASE64_URL_SAFE_NO_PAD};
+
+use super::{
+    config::ParserConfig,
+    error::{Error, ReadContext, WriteContext},
+};
+
+/// The maximum number of bytes of a string kept in memory while parsing it.
+pub const STRING_CHUNK_SIZE: usize = 64 * 1024;
 
-use super::error::{Error, ReadContext, WriteContext};
+/// The beginning of a string in the [`StringEncoding::HexWrapped`] encoding.
+pub(crate) const HEX_WRAPPED_OPENING: &[u8] = b"<hex>";
+
+/// The end of a string in the [`StringEncoding::HexWrapped`] encoding.
+pub(crate) const HEX_WRAPPED_CLOSING: &[u8] = b"</hex>";
 
 /// It parses a string bencoded value.
 ///
 /// # Errors
 ///
-/// Will return an error if it can't read from the input or write to the
-/// output.
-///
-/// # Panics
+/// Will return an error if:
 ///
-/// Will panic if we reach the end of the input without completing the string.
+/// - It can't read from the input or write to the output.
+/// - The input ends before the end of the string.
+/// - The string is not a valid bencoded string.
 pub fn parse<R: Read, W: Writer>(reader: &mut ByteReader<R>, writer: &mut W) -> Result<(), Error> {
-    let mut string_parser = StringParser::default();
-    string_parser.parse(reader, writer)
+    let mut remaining = parse_length(reader, writer, &ParserConfig::default())?;
+
+    let mut chunk = vec![0; remaining.min(STRING_CHUNK_SIZE)];
+    let mut encoder = JsonStringEncoder::new(StringEncoding::default());
+
+    while remaining > 0 {
+        let chunk_len = remaining.min(chunk.len());
+
+        read_chunk(reader, writer, &mut chunk[..chunk_len])?;
+        encoder.write(&chunk[..chunk_len], writer)?;
+
+        remaining -= chunk_len;
+    }
+
+    encoder.finish(writer)
 }
 
-/// Strings bencode format have two parts: `length:value`.
+/// It parses a string bencoded value returning its raw bytes.
 ///
-/// - Length is a sequence of bytes (only digits 0..9).
-/// - Value is an arbitrary sequence of bytes (not only valid UTF-8).
-#[derive(Default, Debug)]
-#[allow(clippy::module_name_repetitions)]
-struct StringParser {
-    /// The final parsed string.
-    parsed_value: String,
+/// The writer is only used to include the write context in errors.
+///
+/// Leading zeros in the length (`03:abc`) are only allowed in `lenient` mode,
+/// for legacy input.
+///
+/// # Errors
+///
+/// Will return an error if:
+///
+/// - It can't read from the input.
+/// - The string is not a valid bencoded string.
+/// - The string is longer than the maximum string length in the config.
+pub fn parse_bytes<R: Read, W: Writer>(
+    reader: &mut ByteReader<R>,
+    writer: &W,
+    config: &ParserConfig,
+) -> Result<Vec<u8>, Error> {
+    let length = parse_length(reader, writer, config)?;
+
+    let mut value = Value::new(length);
+
+    value.parse(reader, writer)?;
+
+    Ok(value.bytes)
 }
 
-impl StringParser {
-    fn parse<R: Read, W: Writer>(
+/// It parses the length of a string bencoded value, including the `:`
+/// separator. The value is not read.
+///
+/// # Errors
+///
+/// Will return an error if:
+///
+/// - It can't read from the input.
+/// - The length is not a valid bencoded string length.
+/// - The string is longer than the maximum string length in the config.
+pub fn parse_length<R: Read, W: Writer>(
+    reader: &mut ByteReader<R>,
+    writer: &W,
+    config: &ParserConfig,
+) -> Result<usize, Error> {
+    let mut length = Length::new(config.lenient);
+
+    length.parse(reader, writer)?;
+
+    // Check the limit before reading the value, so the bytes are not read
+    if let Some(max_string_len) = config.max_string_len {
+        if length.number > max_string_len {
+            return Err(Error::MaxStringLengthExceeded(
+                max_string_len,
+                ReadContext {
+                    byte: None,
+                    pos: reader.input_byte_counter(),
+                    latest_bytes: reader.captured_bytes(),
+                    path: String::new(),
+                    next_bytes: reader.next_bytes(),
+                    token_start: None,
+                },
+                WriteContext {
+                    byte: None,
+                    pos: writer.output_byte_counter(),
+                    latest_bytes: writer.captured_bytes(),
+                },
+            ));
+        }
+    }
+
+    Ok(length.number)
+}
+
+/// It reads the next bytes of a string bencoded value, filling the whole
+/// chunk.
+///
+/// # Errors
+///
+/// Will return an error if it can't read from the input or the input ends
+/// before filling the chunk.
+pub fn read_chunk<R: Read, W: Writer>(
+    reader: &mut ByteReader<R>,
+    writer: &W,
+    chunk: &mut [u8],
+) -> Result<(), Error> {
+    for byte in chunk.iter_mut() {
+        *byte = Value::next_byte(reader, writer)?;
+    }
+
+    Ok(())
+}
+
+/// How byte strings that are not valid UTF-8 are written in the JSON output.
+///
+/// Valid UTF-8 strings are always written as JSON strings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StringEncoding {
+    /// Lowercase hexadecimal inside a wrapper: `"<hex>fffe</hex>"`.
+    #[default]
+    HexWrapped,
+
+    /// Plain lowercase hexadecimal: `"fffe"`.
+    Hex,
+
+    /// Standard base64 with padding: `"//4="`.
+    Base64,
+
+    /// URL-safe base64 without padding: `"__4"`.
+    Base64Url,
+
+    /// A JSON object with the lowercase hexadecimal: `{"$bytes":"fffe"}`.
+    ///
+    /// JSON object keys must be strings, so dictionary keys are written with
+    /// the [`StringEncoding::HexWrapped`] encoding.
+    BytesObject,
+
+    /// Each byte as the Unicode code point with the same value, as if the
+    /// string was latin-1. Bytes that are not printable ASCII chars are
+    /// escaped: `"\u00ff\u00fe"`.
+    Latin1,
+}
+
+impl StringEncoding {
+    /// It returns the encoding used for dictionary keys, which must be JSON
+    /// strings.
+    #[must_use]
+    pub fn for_dict_key(self) -> Self {
+        match self {
+            Self::BytesObject => Self::HexWrapped,
+            encoding => encoding,
+        }
+    }
+
+    fn opening(self) -> &'static str {
+        match self {
+            Self::HexWrapped => "\"<hex>",
+            Self::BytesObject => r#"{"$bytes":""#,
+            Self::Hex | Self::Base64 | Self::Base64Url | Self::Latin1 => "\"",
+        }
+    }
+
+    fn closing(self) -> &'static str {
+        match self {
+            Self::HexWrapped => "</hex>\"",
+            Self::BytesObject => "\"}",
+            Self::Hex | Self::Base64 | Self::Base64Url | Self::Latin1 => "\"",
+        }
+    }
+}
+
+/// It writes the raw bytes of a string as a JSON value.
+///
+/// If the string contains non UTF-8 bytes it writes them with the given
+/// encoding, for example, in the format '<hex>fafb</hex>'.
+///
+/// # Errors
+///
+/// Will return an error if it can't write to the output.
+pub fn write_json<W: Writer>(
+    bytes: &[u8],
+    encoding: StringEncoding,
+    writer: &mut W,
+) -> Result<(), Error> {
+    let mut encoder = JsonStringEncoder::new(encoding);
+
+    encoder.write(bytes, writer)?;
+    encoder.finish(writer)
+}
+
+/// It writes a string as a JSON value while its bytes are read, in chunks.
+///
+/// ```rust
+/// use torrust_bencode2json::parsers::string::{JsonStringEncoder, StringEncoding};
+/// use torrust_bencode2json::rw::string_writer::StringWriter;
+///
+/// let mut output = String::new();
+/// let mut writer = StringWriter::new(&mut output);
+///
+/// let mut encoder = JsonStringEncoder::new(StringEncoding::default());
+///
+/// encoder.write(b"\xFF", &mut writer).unwrap();
+/// encoder.write(b"\xFE", &mut writer).unwrap();
+/// encoder.finish(&mut writer).unwrap();
+///
+/// assert_eq!(output, r#""<hex>fffe</hex>""#);
+/// ```
+#[derive(Debug)]
+pub struct JsonStringEncoder {
+    encoding: StringEncoding,
+    state: EncoderState,
+}
+
+#[derive(Debug)]
+enum EncoderState {
+    /// Nothing has been written yet. The string is valid UTF-8 so far, except
+    /// for the bytes of a char that is not complete yet. With the
+    /// [`StringEncoding::HexWrapped`] encoding, it's also shorter than a
+    /// chunk.
+    Buffering {
+        buffer: Vec<u8>,
+
+        /// The number of bytes at the beginning of the buffer that are
+        /// valid UTF-8.
+        valid_len: usize,
+    },
+
+    /// The string is being written as text, only with the
+    /// [`StringEncoding::HexWrapped`] encoding. It keeps the bytes of a char
+    /// that is not complete yet, and the end of the text while it looks like
+    /// the hex-wrapped format.
+    Text {
+        incomplete_char: Vec<u8>,
+        tail: HexWrappedTail,
+    },
+
+    /// The string is not valid UTF-8 and it's being written with the
+    /// selected encoding. It keeps the bytes that do not complete a base64
+    /// group yet.
+    Encoded(Vec<u8>),
+
+    /// The string was being written as text and the rest is being written in
+    /// hexadecimal.
+    TextThenHex,
+}
+
+impl JsonStringEncoder {
+    #[must_use]
+    pub fn new(encoding: StringEncoding) -> Self {
+        Self {
+            encoding,
+            state: EncoderState::Buffering {
+                buffer: Vec::new(),
+                valid_len: 0,
+            },
+        }
+    }
+
+    /// It writes the next bytes of the string.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if it can't write to the output.
+    pub fn write<W: Writer>(&mut self, bytes: &[u8], writer: &mut W) -> Result<(), Error> {
+        match &mut self.state {
+            EncoderState::Buffering { buffer, valid_len } => {
+                let (mut buffer, valid_len) = (mem::take(buffer), *valid_len);
+                buffer.extend_from_slice(bytes);
+                self.write_buffered(buffer, valid_len, writer)
+            }
+            EncoderState::Text {
+                incomplete_char,
+                tail,
+            } => {
+                let (mut text, tail) = (mem::take(incomplete_char), mem::take(tail));
+                text.extend_from_slice(bytes);
+                self.write_text(&text, tail, writer)
+            }
+            EncoderState::Encoded(pending) => {
+                let mut pending = mem::take(pending);
+                pending.extend_from_slice(bytes);
+                self.write_encoded(pending, writer)
+            }
+            EncoderState::TextThenHex => {
+                writer.write_str(&hex::encode(bytes))?;
+                Ok(())
+            }
+        }
+    }
+
+    /// It writes the end of the string.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if it can't write to the output.
+    pub fn finish<W: Writer>(self, writer: &mut W) -> Result<(), Error> {
+        match self.state {
+            EncoderState::Buffering { buffer, .. } => {
+                if self.encoding == StringEncoding::HexWrapped && is_hex_wrapped(&buffer) {
+                    // Text that looks like the hex format -> print it encoded
+                    // too, so it's not decoded as raw bytes
+                    writer.write_str(self.encoding.opening())?;
+                    writer.write_str(&encode(&buffer, self.encoding))?;
+                    writer.write_str(self.encoding.closing())?;
+                } else if let Ok(string) = str::from_utf8(&buffer) {
+                    // String only contains valid UTF-8 chars -> print it as it's
+                    writer.write_str(&json_string(string))?;
+                } else {
+                    // String contains non valid UTF-8 chars -> print it encoded
+                    writer.write_str(self.encoding.opening())?;
+                    writer.write_str(&encode(&buffer, self.encoding))?;
+                    writer.write_str(self.encoding.closing())?;
+                }
+            }
+            EncoderState::Text {
+                incomplete_char,
+                tail,
+            } => {
+                if !incomplete_char.is_empty() {
+                    writer.write_str(tail.as_str())?;
+                    writer.write_str(&bytes_to_hex(&incomplete_char))?;
+                } else if tail.is_hex_wrapped() {
+                    // Text that ends like the hex format -> print the end
+                    // encoded too, so it's not decoded as raw bytes
+                    writer.write_str(&bytes_to_hex(&tail.bytes))?;
+                } else {
+                    writer.write_str(tail.as_str())?;
+                }
+                writer.write_byte(b'"')?;
+            }
+            EncoderState::Encoded(pending) => {
+                writer.write_str(&encode(&pending, self.encoding))?;
+                writer.write_str(self.encoding.closing())?;
+            }
+            EncoderState::TextThenHex => {
+                writer.write_str("</hex>\"")?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// It decides how to write the string once it's known whether it's valid
+    /// UTF-8. Only the new bytes after the first `valid_len` bytes are
+    /// validated.
+    ///
+    /// The [`StringEncoding::HexWrapped`] encoding begins writing the string
+    /// as text after the first chunk, because the rest can be written in
+    /// hexadecimal inside the same JSON string. Other encodings keep the
+    /// string in memory until it ends or an invalid byte is found.
+    fn write_buffered<W: Writer>(
         &mut self,
-        reader: &mut ByteReader<R>,
+        buffer: Vec<u8>,
+        valid_len: usize,
         writer: &mut W,
     ) -> Result<(), Error> {
-        let mut length = Length::default();
+        let (valid_len, is_invalid) = match str::from_utf8(&buffer[valid_len..]) {
+            Ok(_) => (buffer.len(), false),
+            Err(err) => (valid_len + err.valid_up_to(), err.error_len().is_some()),
+        };
 
-        length.parse(reader, writer)?;
+        if self.encoding == StringEncoding::HexWrapped && valid_len >= STRING_CHUNK_SIZE {
+            writer.write_byte(b'"')?;
+            return self.write_text(&buffer, HexWrappedTail::default(), writer);
+        }
 
-        let mut value = Value::new(length.number);
+        if is_invalid {
+            // String contains non valid UTF-8 chars -> print it encoded
+            writer.write_str(self.encoding.opening())?;
+            return self.write_encoded(buffer, writer);
+        }
 
-        value.parse(reader, writer)?;
+        self.state = EncoderState::Buffering { buffer, valid_len };
 
-        self.parsed_value = value.utf8();
+        Ok(())
+    }
 
-        writer.write_str(&self.json())?;
+    /// It writes the valid UTF-8 text and switches to hexadecimal when it
+    /// finds an invalid byte. The end of the text is kept while it looks like
+    /// the hex-wrapped format.
+    fn write_text<W: Writer>(
+        &mut self,
+        bytes: &[u8],
+        mut tail: HexWrappedTail,
+        writer: &mut W,
+    ) -> Result<(), Error> {
+        let (valid_up_to, is_invalid) = match str::from_utf8(bytes) {
+            Ok(_) => (bytes.len(), false),
+            Err(err) => (err.valid_up_to(), err.error_len().is_some()),
+        };
+
+        let mut text = Vec::with_capacity(valid_up_to);
+        tail.push(&bytes[..valid_up_to], &mut text);
+
+        let text = str::from_utf8(&text).expect("the text should be valid UTF-8");
+        let json = json_string(text);
+
+        // Remove the double quotes, because the string has not ended yet
+        writer.write_str(&json[1..json.len() - 1])?;
+
+        if is_invalid {
+            // The tail is not the end of the text
+            writer.write_str(tail.as_str())?;
+            writer.write_str("<hex>")?;
+            writer.write_str(&hex::encode(&bytes[valid_up_to..]))?;
+            self.state = EncoderState::TextThenHex;
+        } else {
+            self.state = EncoderState::Text {
+                incomplete_char: bytes[valid_up_to..].to_vec(),
+                tail,
+            };
+        }
 
         Ok(())
     }
 
-    /// It returns the final parsed value as string.
-    ///
-    /// If the string contains non UTF-8 bytes it returns the hexadecimal list
-    /// of bytes in in the format '<hex>fa fb</hex>'  
-    fn parsed_value(&self) -> String {
-        self.parsed_value.clone()
+    /// It writes the bytes with the selected encoding, except the bytes that
+    /// do not complete a base64 group.
+    fn write_encoded<W: Writer>(
+        &mut self,
+        mut bytes: Vec<u8>,
+        writer: &mut W,
+    ) -> Result<(), Error> {
+        let complete_len = match self.encoding {
+            StringEncoding::Base64 | StringEncoding::Base64Url => bytes.len() - bytes.len() % 3,
+            _ => bytes.len(),
+        };
+
+        let pending = bytes.split_off(complete_len);
+
+        writer.write_str(&encode(&bytes, self.encoding))?;
+
+        self.state = EncoderState::Encoded(pending);
+
+        Ok(())
     }
+}
 
-    /// It serializes the parsed value into JSON.
-    #[must_use]
-    fn json(&self) -> String {
-        serde_json::to_string(&self.parsed_value()).unwrap()
+/// It checks whether the bytes are in the hex-wrapped format: an even number
+/// of lowercase hexadecimal digits inside `<hex>` and `</hex>`.
+pub(crate) fn is_hex_wrapped(bytes: &[u8]) -> bool {
+    let Some(hex) = bytes
+        .strip_prefix(HEX_WRAPPED_OPENING)
+        .and_then(|rest| rest.strip_suffix(HEX_WRAPPED_CLOSING))
+    else {
+        return false;
+    };
+
+    hex.len().checked_rem(2) == Some(0) && hex.iter().copied().all(is_lowercase_hex_digit)
+}
+
+fn is_lowercase_hex_digit(byte: u8) -> bool {
+    matches!(byte, b'0'..=b'9' | b'a'..=b'f')
+}
+
+/// The end of a text while it looks like the hex-wrapped format: the
+/// beginning of `<hex>`, or `<hex>` followed by hexadecimal digits and the
+/// beginning of `</hex>`.
+///
+/// It's kept in memory until it's known how to write it. If the text ends
+/// with it, it's written in the hex-wrapped format. Otherwise, it's written
+/// as text.
+#[derive(Debug, Default)]
+struct HexWrappedTail {
+    bytes: Vec<u8>,
+
+    /// The number of hexadecimal digits after `<hex>`.
+    digits: usize,
+
+    /// The number of bytes of `</hex>` matched after the digits.
+    closing_len: usize,
+}
+
+impl HexWrappedTail {
+    /// It adds the next bytes of the text to the tail. The bytes that can't
+    /// be part of the hex-wrapped format anymore are added to `text`, so they
+    /// can be written.
+    fn push(&mut self, bytes: &[u8], text: &mut Vec<u8>) {
+        for &byte in bytes {
+            while !self.accept(byte) {
+                if self.bytes.is_empty() {
+                    text.push(byte);
+                    break;
+                }
+                self.release(text);
+            }
+        }
+    }
+
+    /// It adds the byte to the tail if the tail still looks like the format.
+    fn accept(&mut self, byte: u8) -> bool {
+        let len = self.bytes.len();
+
+        let is_accepted = if len < HEX_WRAPPED_OPENING.len() {
+            byte == HEX_WRAPPED_OPENING[len]
+        } else if self.closing_len == 0 && is_lowercase_hex_digit(byte) {
+            self.digits += 1;
+            true
+        } else if self.closing_len < HEX_WRAPPED_CLOSING.len()
+            && byte == HEX_WRAPPED_CLOSING[self.closing_len]
+        {
+            self.closing_len += 1;
+            true
+        } else {
+            false
+        };
+
+        if is_accepted {
+            self.bytes.push(byte);
+        }
+
+        is_accepted
+    }
+
+    /// It moves the tail into the text, when the tail does not look like the
+    /// format anymore. The `<` at the beginning of `</hex>` is kept, because
+    /// it could also be the beginning of a new `<hex>`.
+    fn release(&mut self, text: &mut Vec<u8>) {
+        let keep_len = usize::from(self.closing_len == 1);
+
+        text.extend_from_slice(&self.bytes[..self.bytes.len() - keep_len]);
+
+        *self = Self::default();
+
+        if keep_len == 1 {
+            self.bytes.push(HEX_WRAPPED_OPENING[0]);
+        }
+    }
+
+    fn is_hex_wrapped(&self) -> bool {
+        self.closing_len == HEX_WRAPPED_CLOSING.len() && self.digits.checked_rem(2) == Some(0)
+    }
+
+    fn as_str(&self) -> &str {
+        str::from_utf8(&self.bytes).expect("the tail should only contain ASCII chars")
     }
 }
 
+/// It encodes the bytes without the opening and closing of the encoding.
+fn encode(bytes: &[u8], encoding: StringEncoding) -> String {
+    match encoding {
+        StringEncoding::HexWrapped | StringEncoding::Hex | StringEncoding::BytesObject => {
+            hex::encode(bytes)
+        }
+        StringEncoding::Base64 => BASE64_STANDARD.encode(bytes),
+        StringEncoding::Base64Url => BASE64_URL_SAFE_NO_PAD.encode(bytes),
+        StringEncoding::Latin1 => latin1(bytes),
+    }
+}
+
+fn json_string(string: &str) -> String {
+    serde_json::to_string(string).expect("a string should always be valid JSON")
+}
+
+/// It writes each byte as the Unicode code point with the same value.
+/// Printable ASCII chars are written as they are.
+fn latin1(bytes: &[u8]) -> String {
+    let mut json = String::with_capacity(bytes.len() * 6);
+
+    for &byte in bytes {
+        match byte {
+            b'"' => json.push_str("\\\""),
+            b'\\' => json.push_str("\\\\"),
+            0x20..=0x7E => json.push(byte as char),
+            _ => {
+                write!(json, "\\u{byte:04x}").expect("writing to a string should not fail");
+            }
+        }
+    }
+
+    json
+}
+
+fn bytes_to_hex(data: &[u8]) -> String {
+    format!("<hex>{}</hex>", hex::encode(data))
+}
+
+/// Strings bencode format have two parts: `length:value`.
+///
+/// - Length is a sequence of bytes (only digits 0..9).
+/// - Value is an arbitrary sequence of bytes (not only valid UTF-8).
 #[derive(Default, Debug)]
 struct Length {
-    /// A list of parsed bytes. It's only for debugging.
+    /// A list of parsed bytes.
     bytes: Vec<u8>,
 
     /// The parsed length at the current read digit.
     number: usize,
+
+    /// It allows leading zeros.
+    lenient: bool,
 }
 
 impl Length {
     const END_OF_STRING_LENGTH_BYTE: u8 = b':';
 
+    fn new(lenient: bool) -> Self {
+        Self {
+            lenient,
+            ..Default::default()
+        }
+    }
+
     fn parse<R: Read, W: Writer>(
         &mut self,
         reader: &mut ByteReader<R>,
@@ -125,6 +689,9 @@ impl Length {
                             byte: None,
                             pos: reader.input_byte_counter(),
                             latest_bytes: reader.captured_bytes(),
+                            path: String::new(),
+                            next_bytes: reader.next_bytes(),
+                            token_start: None,
                         },
                         WriteContext {
                             byte: None,
@@ -142,31 +709,51 @@ impl Length {
     ///
     /// # Errors
     ///
-    /// Will return an error if the byte is not a digit (0..9).
+    /// Will return an error if:
+    ///
+    /// - The byte is not a digit (0..9).
+    /// - The length has leading zeros and it's not lenient.
+    /// - The length does not fit into a `usize`.
     fn add_byte<R: Read, W: Writer>(
         &mut self,
         byte: u8,
         reader: &mut ByteReader<R>,
         writer: &W,
     ) -> Result<(), Error> {
+        let read_context = || ReadContext {
+            byte: Some(byte),
+            pos: reader.input_byte_counter(),
+            latest_bytes: reader.captured_bytes(),
+            path: String::new(),
+            next_bytes: reader.next_bytes(),
+            token_start: None,
+        };
+
+        let write_context = || WriteContext {
+            byte: Some(byte),
+            pos: writer.output_byte_counter(),
+            latest_bytes: writer.captured_bytes(),
+        };
+
         if !byte.is_ascii_digit() {
             return Err(Error::InvalidStringLengthByte(
-                ReadContext {
-                    byte: Some(byte),
-                    pos: reader.input_byte_counter(),
-                    latest_bytes: reader.captured_bytes(),
-                },
-                WriteContext {
-                    byte: Some(byte),
-                    pos: writer.output_byte_counter(),
-                    latest_bytes: writer.captured_bytes(),
-                },
+                read_context(),
+                write_context(),
+            ));
+        }
+
+        if !self.lenient && self.bytes == [b'0'] {
+            return Err(Error::LeadingZerosInStringLength(
+                read_context(),
+                write_context(),
             ));
         }
 
         self.bytes.push(byte);
 
-        self.add_digit_to_length(Self::byte_to_digit(byte));
+        if !self.add_digit_to_length(Self::byte_to_digit(byte)) {
+            return Err(Error::StringLengthOverflow(read_context(), write_context()));
+        }
 
         Ok(())
     }
@@ -176,9 +763,20 @@ impl Length {
         (byte - b'0') as usize
     }
 
-    /// It adds the new digit to the number.
-    fn add_digit_to_length(&mut self, digit: usize) {
-        self.number = (self.number * 10) + digit;
+    /// It adds the new digit to the number. It returns `false` if the number
+    /// overflows.
+    fn add_digit_to_length(&mut self, digit: usize) -> bool {
+        match self
+            .number
+            .checked_mul(10)
+            .and_then(|number| number.checked_add(digit))
+        {
+            Some(number) => {
+                self.number = number;
+                true
+            }
+            None => false,
+        }
     }
 }
 
@@ -225,6 +823,9 @@ impl Value {
                             byte: None,
                             pos: reader.input_byte_counter(),
                             latest_bytes: reader.captured_bytes(),
+                            path: String::new(),
+                            next_bytes: reader.next_bytes(),
+                            token_start: None,
                         },
                         WriteContext {
                             byte: None,
@@ -242,33 +843,16 @@ impl Value {
         self.bytes.push(byte);
         self.bytes_counter += 1;
     }
-
-    fn utf8(&self) -> String {
-        match str::from_utf8(&self.bytes) {
-            Ok(string) => {
-                // String only contains valid UTF-8 chars -> print it as it's
-                string.to_owned()
-            }
-            Err(_) => {
-                // String contains non valid UTF-8 chars -> print it as hex bytes
-                Self::bytes_to_hex(&self.bytes)
-            }
-        }
-    }
-
-    fn bytes_to_hex(data: &[u8]) -> String {
-        format!("<hex>{}</hex>", hex::encode(data))
-    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
-        parsers::error::Error,
+        parsers::{config::ParserConfig, error::Error},
         rw::{byte_reader::ByteReader, string_writer::StringWriter},
     };
 
-    use super::parse;
+    use super::{parse, parse_bytes};
 
     fn bencode_to_json_unchecked(input_buffer: &[u8]) -> String {
         let mut output = String::new();
@@ -313,8 +897,34 @@ mod tests {
     }
 
     #[test]
-    fn length_can_contain_leading_zeros() {
-        assert_eq!(bencode_to_json_unchecked(b"00:"), r#""""#.to_string());
+    fn length_cannot_contain_leading_zeros() {
+        assert!(matches!(
+            try_bencode_to_json(b"03:abc"),
+            Err(Error::LeadingZerosInStringLength { .. })
+        ));
+    }
+
+    #[test]
+    fn length_can_contain_leading_zeros_in_lenient_mode() {
+        let mut reader = ByteReader::new(&b"00:"[..]);
+
+        let mut output = String::new();
+        let writer = StringWriter::new(&mut output);
+
+        assert_eq!(
+            parse_bytes(
+                &mut reader,
+                &writer,
+                &ParserConfig::default().with_lenient_mode()
+            )
+            .unwrap(),
+            b""
+        );
+    }
+
+    #[test]
+    fn length_can_be_zero() {
+        assert_eq!(bencode_to_json_unchecked(b"0:"), r#""""#.to_string());
     }
 
     #[test]
@@ -418,6 +1028,340 @@ mod tests {
         }
     }
 
+    mod it_should_write_non_utf8_strings_with_the_selected_encoding {
+        use crate::{
+            parsers::string::{write_json, StringEncoding},
+            rw::string_writer::StringWriter,
+        };
+
+        fn write(bytes: &[u8], encoding: StringEncoding) -> String {
+            let mut output = String::new();
+
+            write_json(bytes, encoding, &mut StringWriter::new(&mut output)).unwrap();
+
+            output
+        }
+
+        #[test]
+        fn hex_wrapped_by_default() {
+            assert_eq!(
+                write(b"\xFF\xFE", StringEncoding::default()),
+                r#""<hex>fffe</hex>""#
+            );
+        }
+
+        #[test]
+        fn hex() {
+            assert_eq!(write(b"\xFF\xFE", StringEncoding::Hex), r#""fffe""#);
+        }
+
+        #[test]
+        fn base64() {
+            assert_eq!(write(b"\xFF\xFE", StringEncoding::Base64), r#""//4=""#);
+        }
+
+        #[test]
+        fn base64url() {
+            assert_eq!(write(b"\xFF\xFE", StringEncoding::Base64Url), r#""__4""#);
+        }
+
+        #[test]
+        fn bytes_object() {
+            assert_eq!(
+                write(b"\xFF\xFE", StringEncoding::BytesObject),
+                r#"{"$bytes":"fffe"}"#
+            );
+        }
+
+        #[test]
+        fn latin1() {
+            assert_eq!(
+                write(b"a\"\\\n\xFF", StringEncoding::Latin1),
+                r#""a\"\\\u000a\u00ff""#
+            );
+        }
+
+        #[test]
+        fn but_not_utf8_strings() {
+            assert_eq!(write(b"spam", StringEncoding::Base64), r#""spam""#);
+        }
+
+        #[test]
+        fn and_utf8_strings_that_look_like_the_hex_wrapped_encoding() {
+            assert_eq!(
+                write(b"<hex>ab</hex>", StringEncoding::HexWrapped),
+                r#""<hex>3c6865783e61623c2f6865783e</hex>""#
+            );
+        }
+
+        #[test]
+        fn but_not_utf8_strings_that_only_look_partially_like_the_hex_wrapped_encoding() {
+            for text in [
+                "<hex>AB</hex>",
+                "<hex>abc</hex>",
+                "a<hex>ab</hex>",
+                "<hex>ab",
+            ] {
+                assert_eq!(
+                    write(text.as_bytes(), StringEncoding::HexWrapped),
+                    format!(r#""{text}""#)
+                );
+            }
+        }
+    }
+
+    mod it_should_write_strings_in_chunks {
+        use crate::{
+            parsers::string::{
+                encode, write_json, JsonStringEncoder, StringEncoding, STRING_CHUNK_SIZE,
+            },
+            rw::string_writer::StringWriter,
+        };
+
+        fn write_in_chunks(bytes: &[u8], chunk_size: usize, encoding: StringEncoding) -> String {
+            let mut output = String::new();
+            let mut writer = StringWriter::new(&mut output);
+
+            let mut encoder = JsonStringEncoder::new(encoding);
+
+            for chunk in bytes.chunks(chunk_size) {
+                encoder.write(chunk, &mut writer).unwrap();
+            }
+
+            encoder.finish(&mut writer).unwrap();
+
+            output
+        }
+
+        #[test]
+        fn splitting_a_multibyte_char_between_two_chunks() {
+            assert_eq!(
+                write_in_chunks("¡Hola!".as_bytes(), 1, StringEncoding::default()),
+                r#""¡Hola!""#
+            );
+        }
+
+        #[test]
+        fn with_the_same_output_as_the_whole_string_when_it_is_not_utf8() {
+            let bytes = b"\xFF\xFE\xFD\xFC\xFB";
+
+            for encoding in [StringEncoding::HexWrapped, StringEncoding::Base64] {
+                let mut whole = String::new();
+                write_json(bytes, encoding, &mut StringWriter::new(&mut whole)).unwrap();
+
+                assert_eq!(write_in_chunks(bytes, 2, encoding), whole);
+            }
+        }
+
+        #[test]
+        fn with_the_selected_encoding_when_the_first_invalid_byte_is_in_the_first_chunk() {
+            let mut bytes = b"a".repeat(STRING_CHUNK_SIZE - 1);
+            bytes.push(0xFF);
+            bytes.extend_from_slice(b"bc");
+
+            assert_eq!(
+                write_in_chunks(&bytes, 1024, StringEncoding::default()),
+                format!(r#""<hex>{}</hex>""#, hex::encode(&bytes))
+            );
+        }
+
+        #[test]
+        fn switching_to_hex_when_the_first_invalid_byte_is_after_the_first_chunk() {
+            let text = "a".repeat(STRING_CHUNK_SIZE);
+
+            let mut bytes = text.as_bytes().to_vec();
+            bytes.extend_from_slice(b"\xFF\xFEbc");
+
+            let expected = format!(r#""{text}<hex>fffe6263</hex>""#);
+
+            // The output does not depend on the chunk size
+            for chunk_size in [1000, STRING_CHUNK_SIZE, bytes.len()] {
+                assert_eq!(
+                    write_in_chunks(&bytes, chunk_size, StringEncoding::HexWrapped),
+                    expected
+                );
+            }
+        }
+
+        #[test]
+        fn with_the_selected_encoding_when_the_first_invalid_byte_is_after_the_first_chunk_with_other_encodings(
+        ) {
+            // 70,000 bytes, with the first invalid byte in the second chunk
+            let mut bytes = b"a".repeat(STRING_CHUNK_SIZE);
+            bytes.push(0xFF);
+            bytes.extend_from_slice(&b"b".repeat(70_000 - STRING_CHUNK_SIZE - 1));
+
+            for encoding in [
+                StringEncoding::Hex,
+                StringEncoding::Base64,
+                StringEncoding::Base64Url,
+                StringEncoding::BytesObject,
+                StringEncoding::Latin1,
+            ] {
+                let expected = format!(
+                    "{}{}{}",
+                    encoding.opening(),
+                    encode(&bytes, encoding),
+                    encoding.closing()
+                );
+
+                for chunk_size in [1000, STRING_CHUNK_SIZE, bytes.len()] {
+                    assert_eq!(write_in_chunks(&bytes, chunk_size, encoding), expected);
+                }
+            }
+        }
+
+        #[test]
+        fn as_text_when_a_string_longer_than_a_chunk_is_valid_utf8_with_other_encodings() {
+            let text = "a".repeat(70_000);
+
+            assert_eq!(
+                write_in_chunks(text.as_bytes(), 1000, StringEncoding::Base64),
+                format!(r#""{text}""#)
+            );
+        }
+
+        #[test]
+        fn with_the_selected_encoding_when_the_string_ends_with_an_incomplete_char_after_the_first_chunk_with_other_encodings(
+        ) {
+            let mut bytes = b"a".repeat(STRING_CHUNK_SIZE);
+            bytes.push(0xC2);
+
+            assert_eq!(
+                write_in_chunks(&bytes, 1000, StringEncoding::Base64),
+                format!(r#""{}""#, encode(&bytes, StringEncoding::Base64))
+            );
+        }
+
+        #[test]
+        fn with_the_end_in_hex_when_the_text_after_the_first_chunk_ends_in_the_hex_wrapped_encoding(
+        ) {
+            let text = "a".repeat(STRING_CHUNK_SIZE);
+
+            let mut bytes = text.as_bytes().to_vec();
+            bytes.extend_from_slice(b"<he<hex>ab</hex>");
+
+            let expected = format!(r#""{text}<he<hex>{}</hex>""#, hex::encode("<hex>ab</hex>"));
+
+            // The end of the text is split between chunks
+            for chunk_size in [1000, STRING_CHUNK_SIZE + 10, bytes.len()] {
+                assert_eq!(
+                    write_in_chunks(&bytes, chunk_size, StringEncoding::HexWrapped),
+                    expected
+                );
+            }
+        }
+
+        #[test]
+        fn with_the_end_in_hex_when_a_text_of_70000_bytes_ends_in_the_hex_wrapped_encoding() {
+            let text = "a".repeat(70_000 - "<hex>ff</hex>".len());
+
+            let bytes = format!("{text}<hex>ff</hex>");
+
+            assert_eq!(
+                write_in_chunks(
+                    bytes.as_bytes(),
+                    STRING_CHUNK_SIZE,
+                    StringEncoding::HexWrapped
+                ),
+                format!(r#""{text}<hex>{}</hex>""#, hex::encode("<hex>ff</hex>"))
+            );
+        }
+
+        #[test]
+        fn with_the_end_in_hex_when_it_begins_in_the_first_chunk() {
+            let text = format!("{}<hex>ab", "a".repeat(STRING_CHUNK_SIZE - 10));
+            let end = format!("<hex>{}</hex>", "cd".repeat(10));
+
+            let bytes = format!("{text}{end}");
+
+            assert_eq!(
+                write_in_chunks(bytes.as_bytes(), 1000, StringEncoding::HexWrapped),
+                format!(r#""{text}<hex>{}</hex>""#, hex::encode(end))
+            );
+        }
+
+        #[test]
+        fn writing_text_after_the_first_chunk_that_only_contains_the_hex_wrapped_encoding() {
+            let text = format!("{}<hex>ab</hex>.", "a".repeat(STRING_CHUNK_SIZE));
+
+            assert_eq!(
+                write_in_chunks(text.as_bytes(), 1000, StringEncoding::HexWrapped),
+                format!(r#""{text}""#)
+            );
+        }
+
+        #[test]
+        fn switching_to_hex_when_the_string_ends_with_an_incomplete_char_after_the_first_chunk() {
+            let text = "a".repeat(STRING_CHUNK_SIZE);
+
+            let mut bytes = text.as_bytes().to_vec();
+            bytes.push(0xC2);
+
+            assert_eq!(
+                write_in_chunks(&bytes, 1000, StringEncoding::default()),
+                format!(r#""{text}<hex>c2</hex>""#)
+            );
+        }
+    }
+
+    mod it_should_allow_parsing_the_raw_bytes {
+        use crate::{
+            parsers::{config::ParserConfig, error::Error, string::parse_bytes},
+            rw::{byte_reader::ByteReader, string_writer::StringWriter},
+        };
+
+        #[test]
+        fn of_a_non_utf8_string() {
+            let mut reader = ByteReader::new(&b"4:\xFF\xFE\xFD\xFC"[..]);
+
+            let mut output = String::new();
+            let writer = StringWriter::new(&mut output);
+
+            assert_eq!(
+                parse_bytes(&mut reader, &writer, &ParserConfig::default()).unwrap(),
+                b"\xFF\xFE\xFD\xFC".to_vec()
+            );
+        }
+
+        #[test]
+        fn of_a_string_with_the_maximum_length() {
+            let mut reader = ByteReader::new(&b"4:spam"[..]);
+
+            let mut output = String::new();
+            let writer = StringWriter::new(&mut output);
+
+            assert_eq!(
+                parse_bytes(
+                    &mut reader,
+                    &writer,
+                    &ParserConfig::default().with_max_string_len(4)
+                )
+                .unwrap(),
+                b"spam".to_vec()
+            );
+        }
+
+        #[test]
+        fn failing_before_reading_a_string_longer_than_the_maximum_length() {
+            let mut reader = ByteReader::new(&b"4:spam"[..]);
+
+            let mut output = String::new();
+            let writer = StringWriter::new(&mut output);
+
+            let result = parse_bytes(
+                &mut reader,
+                &writer,
+                &ParserConfig::default().with_max_string_len(3),
+            );
+
+            assert!(matches!(
+                result,
+                Err(Error::MaxStringLengthExceeded(3, read_context, _)) if read_context.pos == 2
+            ));
+        }
+    }
+
     mod it_should_fail_parsing_when {
         use std::io::{self, Read};
 
@@ -429,6 +1373,13 @@ mod tests {
             rw::{byte_reader::ByteReader, string_writer::StringWriter},
         };
 
+        #[test]
+        fn the_length_does_not_fit_into_a_usize() {
+            let result = try_bencode_to_json(format!("{}0:", usize::MAX).as_bytes());
+
+            assert!(matches!(result, Err(Error::StringLengthOverflow { .. })));
+        }
+
         #[test]
         fn it_reaches_the_end_of_the_input_parsing_the_string_length() {
             let incomplete_string_length = b"4";
@@ -0,0 +1,1213 @@
+//! Pull-based tokenizer for bencoded input.
+//!
+//! It reads bencoded bytes from the input and returns one token at a time, so
+//! you can build your own consumers (indexers, validators, converters, ...)
+//! without writing JSON.
+//!
+//! The structure of the bencoded data is validated with the same [`Stack`]
+//! states the [`BencodeParser`](super::BencodeParser) uses. In fact, the parser
+//! is only one consumer of the tokenizer that writes the tokens as JSON.
+//!
+//! ```rust
+//! use torrust_bencode2json::parsers::tokenizer::{Token, Tokenizer};
+//!
+//! let tokens = Tokenizer::new(&b"li42e4:spame"[..])
+//!     .map(|event| event.unwrap().token)
+//!     .collect::<Vec<_>>();
+//!
+//! assert_eq!(
+//!     tokens,
+//!     vec![
+//!         Token::ListStart,
+//!         Token::Integer(b"42".to_vec()),
+//!         Token::Bytes(b"spam".to_vec()),
+//!         Token::End,
+//!     ]
+//! );
+//! ```
+use std::{
+    io::{self, BufReader, Read},
+    iter::FusedIterator,
+};
+
+use super::{
+    config::ParserConfig,
+    error::{self, ReadContext, WriteContext},
+    integer,
+    stack::{Stack, State},
+    string, BencodeType, BENCODE_BEGIN_DICT, BENCODE_BEGIN_INTEGER, BENCODE_BEGIN_LIST,
+    BENCODE_END_LIST_OR_DICT,
+};
+use crate::rw::{
+    byte_reader::{ByteReader, MaxInputBytesExceeded},
+    null_writer::NullWriter,
+    writer::Writer,
+};
+
+/// A bencoded token.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Token {
+    /// The beginning of a list: `l`.
+    ListStart,
+
+    /// The beginning of a dictionary: `d`.
+    DictStart,
+
+    /// The end of the current list or dictionary: `e`.
+    End,
+
+    /// An integer. It contains the digits, including the sign if the integer
+    /// is negative. Integers are not limited to 64 bits.
+    Integer(Vec<u8>),
+
+    /// A byte string. It contains the raw bytes, which are not always valid
+    /// UTF-8.
+    Bytes(Vec<u8>),
+}
+
+/// A token with its position in the input.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Event {
+    pub token: Token,
+
+    /// The offset of the first byte of the token in the input.
+    pub start: u64,
+
+    /// The offset of the byte after the last byte of the token in the input.
+    pub end: u64,
+}
+
+pub struct Tokenizer<R: Read> {
+    byte_reader: ByteReader<R>,
+    stack: Stack,
+    config: ParserConfig,
+
+    /// The number of tokens returned so far.
+    token_counter: u64,
+
+    /// The number of bytes of the current string value that have not been
+    /// read yet.
+    pending_string_value_bytes: usize,
+
+    /// The position of the first byte of the latest token.
+    token_start: u64,
+
+    /// The iterator has returned an error, so it does not return more tokens.
+    failed: bool,
+}
+
+impl<R: Read> Tokenizer<R> {
+    pub fn new(reader: R) -> Self {
+        Self::with_config(reader, ParserConfig::default())
+    }
+
+    /// It creates a tokenizer with the given options.
+    #[must_use]
+    pub fn with_config(reader: R, config: ParserConfig) -> Self {
+        let mut byte_reader = ByteReader::new(reader);
+
+        if let Some(max_input_bytes) = config.max_input_bytes {
+            byte_reader = byte_reader.with_max_input_bytes(max_input_bytes);
+        }
+
+        let stack = match config.max_depth {
+            Some(max_depth) => Stack::with_max_depth(max_depth),
+            None => Stack::default(),
+        };
+
+        Self {
+            byte_reader,
+            stack,
+            config,
+            token_counter: 0,
+            pending_string_value_bytes: 0,
+            token_start: 0,
+            failed: false,
+        }
+    }
+
+    /// It enables the strict mode, which rejects input that is not canonical
+    /// as required by [BEP 3](https://www.bittorrent.org/beps/bep_0003.html).
+    ///
+    /// Dictionary keys must be unique and sorted by their raw bytes.
+    #[must_use]
+    pub fn strict(mut self) -> Self {
+        self.config = self.config.with_strict_mode();
+        self
+    }
+
+    /// It enables the lenient mode, which accepts some non-canonical values
+    /// found in legacy input:
+    ///
+    /// - The negative zero: `i-0e`.
+    /// - Leading zeros in string lengths: `03:abc`.
+    #[must_use]
+    pub fn lenient(mut self) -> Self {
+        self.config = self.config.with_lenient_mode();
+        self
+    }
+
+    /// It returns the current parsing state, which is the state before the
+    /// next token.
+    ///
+    /// Consumers can use it to know where the next token is. For example,
+    /// whether it's a dictionary key or a dictionary value.
+    #[must_use]
+    pub fn state(&self) -> State {
+        self.stack.peek()
+    }
+
+    /// It returns the number of nested lists and dictionaries the next token
+    /// is in.
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.stack.depth()
+    }
+
+    /// It returns the path to the current value, with the latest dictionary
+    /// key and list index at each level, for example,
+    /// `info.files[127].path[2]`.
+    #[must_use]
+    pub fn path(&self) -> String {
+        self.stack.path()
+    }
+
+    /// It returns the latest dictionary key parsed at each level of the stack.
+    /// The first level is the initial state, which never has a key.
+    ///
+    /// Consumers can use it to know which field the next token belongs to.
+    #[must_use]
+    pub fn last_keys(&self) -> Vec<Option<&[u8]>> {
+        self.stack.last_keys()
+    }
+
+    /// It returns the input reader, positioned right after the last token.
+    ///
+    /// It should only be called between tokens. Otherwise, the byte that was
+    /// peeked to recognize the next token is lost.
+    #[must_use]
+    pub fn into_inner(self) -> BufReader<R> {
+        self.byte_reader.into_inner()
+    }
+
+    /// It returns the number of bytes that have been read from the input.
+    #[must_use]
+    pub fn input_byte_counter(&self) -> u64 {
+        self.byte_reader.input_byte_counter()
+    }
+
+    /// It returns a copy of the latest bytes that have been read from the
+    /// input.
+    #[must_use]
+    pub fn captured_bytes(&self) -> Vec<u8> {
+        self.byte_reader.captured_bytes()
+    }
+
+    /// It returns the position of the first byte of the latest token in the
+    /// input, or of the next token after [`Tokenizer::has_next_token`].
+    #[must_use]
+    pub fn token_start(&self) -> u64 {
+        self.token_start
+    }
+
+    /// It returns a copy of the next bytes of the input, up to 16, which are
+    /// already buffered but have not been read yet.
+    #[must_use]
+    pub fn next_bytes(&self) -> Vec<u8> {
+        self.byte_reader.next_bytes()
+    }
+
+    /// It reads the next token from the input. It returns `None` when the
+    /// input ends after a complete bencoded value, or when the input is empty.
+    ///
+    /// Line breaks (`\n`, `\r\n` or `\r`) at the beginning, the end, or between
+    /// values are ignored.
+    ///
+    /// The writer is only used to include the write context in errors. The
+    /// tokenizer should not be used after returning an error.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if:
+    ///
+    /// - It can't read from the input.
+    /// - The input is invalid Bencode.
+    /// - The input exceeds one of the limits in the config.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if a string value started with
+    /// [`Tokenizer::begin_string_value`] has not been completely read.
+    pub fn next_event<W: Writer>(&mut self, writer: &W) -> Result<Option<Event>, error::Error> {
+        assert!(
+            self.pending_string_value_bytes == 0,
+            "the string value has not been completely read"
+        );
+
+        let result = self.read_event(writer);
+
+        self.map_error(result, writer)
+    }
+
+    /// It begins reading the next token if it's a string value, which is a
+    /// string that is not a dictionary key, and returns its length. The bytes
+    /// of the string must be read with [`Tokenizer::read_string_value_chunk`].
+    ///
+    /// It returns `None` when the next token is not a string value. In that
+    /// case nothing is consumed, except line breaks, and the token can be read
+    /// with [`Tokenizer::next_event`].
+    ///
+    /// It allows consumers to process big strings without keeping them in
+    /// memory.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if:
+    ///
+    /// - It can't read from the input.
+    /// - The string length is invalid Bencode.
+    /// - The input exceeds one of the limits in the config.
+    pub fn begin_string_value<W: Writer>(
+        &mut self,
+        writer: &W,
+    ) -> Result<Option<usize>, error::Error> {
+        let result = self.read_string_value_length(writer);
+
+        self.map_error(result, writer)
+    }
+
+    /// It reads the next bytes of the string value started with
+    /// [`Tokenizer::begin_string_value`]. It returns the number of bytes
+    /// written into the chunk, which is `0` when the whole string has been
+    /// read.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if:
+    ///
+    /// - It can't read from the input.
+    /// - The input ends before the end of the string.
+    /// - The input exceeds one of the limits in the config.
+    pub fn read_string_value_chunk<W: Writer>(
+        &mut self,
+        chunk: &mut [u8],
+        writer: &W,
+    ) -> Result<usize, error::Error> {
+        let chunk_len = chunk.len().min(self.pending_string_value_bytes);
+
+        let result = string::read_chunk(&mut self.byte_reader, writer, &mut chunk[..chunk_len]);
+        let result = self.add_path(result);
+
+        self.map_error(result, writer)?;
+
+        self.pending_string_value_bytes -= chunk_len;
+
+        Ok(chunk_len)
+    }
+
+    /// It returns true if the input contains more tokens. Line breaks before
+    /// the next token are consumed, but the token is not. The position of the
+    /// next token is returned by [`Tokenizer::token_start`].
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if:
+    ///
+    /// - It can't read from the input.
+    /// - The input exceeds the maximum number of bytes in the config.
+    pub fn has_next_token<W: Writer>(&mut self, writer: &W) -> Result<bool, error::Error> {
+        let result = self.skip_line_breaks(writer);
+
+        self.map_error(result, writer)
+    }
+
+    fn skip_line_breaks<W: Writer>(&mut self, writer: &W) -> Result<bool, error::Error> {
+        loop {
+            match self.peek_byte()? {
+                Some(byte) if is_line_break(byte) => self.read_peeked_byte(byte, writer)?,
+                Some(_) => {
+                    // The peeked byte is already counted as read
+                    self.token_start = self.byte_reader.input_byte_counter() - 1;
+                    return Ok(true);
+                }
+                None => return Ok(false),
+            }
+        }
+    }
+
+    /// It replaces the I/O error returned when the input exceeds the maximum
+    /// number of bytes with the dedicated error.
+    fn map_error<T, W: Writer>(
+        &self,
+        result: Result<T, error::Error>,
+        writer: &W,
+    ) -> Result<T, error::Error> {
+        match result {
+            Err(error::Error::Io(err)) if MaxInputBytesExceeded::is_cause_of(&err) => {
+                Err(error::Error::MaxInputBytesExceeded(
+                    self.config.max_input_bytes.unwrap_or_default(),
+                    self.read_context(),
+                    Self::write_context(writer),
+                ))
+            }
+            result => result,
+        }
+    }
+
+    /// It adds the path to the errors returned by the integer and string
+    /// parsers, which don't know where the value is.
+    fn add_path<T>(&self, result: Result<T, error::Error>) -> Result<T, error::Error> {
+        result.map_err(|err| err.with_path(|| self.stack.path()))
+    }
+
+    fn read_string_value_length<W: Writer>(
+        &mut self,
+        writer: &W,
+    ) -> Result<Option<usize>, error::Error> {
+        loop {
+            let Some(peeked_byte) = self.peek_byte()? else {
+                return Ok(None);
+            };
+
+            match peeked_byte {
+                byte if is_line_break(byte) => {
+                    // Ignore line breaks at the beginning, the end, or between values
+                    self.read_peeked_byte(peeked_byte, writer)?;
+                }
+                b'0'..=b'9' if !self.is_expecting_dict_key() => {
+                    // The peeked byte is already counted as read
+                    self.token_start = self.byte_reader.input_byte_counter() - 1;
+
+                    self.count_token(writer)?;
+                    self.begin_bencoded_value(BencodeType::String, writer)?;
+
+                    let result = string::parse_length(&mut self.byte_reader, writer, &self.config);
+                    let length = self.add_path(result)?;
+
+                    self.pending_string_value_bytes = length;
+
+                    return Ok(Some(length));
+                }
+                _ => return Ok(None),
+            }
+        }
+    }
+
+    fn read_event<W: Writer>(&mut self, writer: &W) -> Result<Option<Event>, error::Error> {
+        loop {
+            let Some(peeked_byte) = self.peek_byte()? else {
+                self.check_bad_end_stack_state(writer)?;
+                return Ok(None);
+            };
+
+            if is_line_break(peeked_byte) {
+                // Ignore line breaks at the beginning, the end, or between values
+                self.read_peeked_byte(peeked_byte, writer)?;
+                continue;
+            }
+
+            // The peeked byte is already counted as read
+            let start = self.byte_reader.input_byte_counter() - 1;
+
+            self.token_start = start;
+
+            self.count_token(writer)?;
+
+            let token = match peeked_byte {
+                BENCODE_BEGIN_INTEGER => {
+                    self.begin_bencoded_value(BencodeType::Integer, writer)?;
+                    let result = integer::parse_digits(&mut self.byte_reader, writer, &self.config);
+                    Token::Integer(self.add_path(result)?)
+                }
+                b'0'..=b'9' => {
+                    let is_dict_key = self.is_expecting_dict_key();
+
+                    self.begin_bencoded_value(BencodeType::String, writer)?;
+
+                    let result = string::parse_bytes(&mut self.byte_reader, writer, &self.config);
+                    let bytes = self.add_path(result)?;
+
+                    if is_dict_key {
+                        self.add_dict_key(&bytes, writer)?;
+                    }
+
+                    Token::Bytes(bytes)
+                }
+                BENCODE_BEGIN_LIST => {
+                    self.read_peeked_byte(peeked_byte, writer)?;
+                    self.begin_bencoded_value(BencodeType::List, writer)?;
+                    self.push(State::ExpectingFirstListItemOrEnd, writer)?;
+                    Token::ListStart
+                }
+                BENCODE_BEGIN_DICT => {
+                    self.read_peeked_byte(peeked_byte, writer)?;
+                    self.begin_bencoded_value(BencodeType::Dict, writer)?;
+                    self.push(State::ExpectingFirstDictFieldOrEnd, writer)?;
+                    Token::DictStart
+                }
+                BENCODE_END_LIST_OR_DICT => {
+                    self.read_peeked_byte(peeked_byte, writer)?;
+                    self.end_list_or_dict(writer)?;
+                    Token::End
+                }
+                _ => {
+                    return Err(error::Error::UnrecognizedFirstBencodeValueByte(
+                        ReadContext {
+                            byte: Some(peeked_byte),
+                            path: self.stack.next_path(),
+                            ..self.read_context()
+                        },
+                        WriteContext {
+                            byte: Some(peeked_byte),
+                            ..Self::write_context(writer)
+                        },
+                    ));
+                }
+            };
+
+            return Ok(Some(Event {
+                token,
+                start,
+                end: self.byte_reader.input_byte_counter(),
+            }));
+        }
+    }
+
+    fn is_expecting_dict_key(&self) -> bool {
+        matches!(
+            self.stack.peek(),
+            State::ExpectingFirstDictFieldOrEnd | State::ExpectingDictFieldKeyOrEnd
+        )
+    }
+
+    /// It increases the number of tokens.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the maximum number of tokens is exceeded.
+    fn count_token<W: Writer>(&mut self, writer: &W) -> Result<(), error::Error> {
+        if let Some(max_tokens) = self.config.max_tokens {
+            if self.token_counter >= max_tokens {
+                return Err(error::Error::MaxTokensExceeded(
+                    max_tokens,
+                    self.next_token_read_context(),
+                    Self::write_context(writer),
+                ));
+            }
+        }
+
+        self.token_counter += 1;
+
+        Ok(())
+    }
+
+    /// It pushes the state for a new list or dictionary.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the maximum depth is exceeded.
+    fn push<W: Writer>(&mut self, state: State, writer: &W) -> Result<(), error::Error> {
+        self.stack.push(state).map_err(|_| {
+            error::Error::MaxDepthExceeded(
+                self.config.max_depth.unwrap_or_default(),
+                self.token_read_context(),
+                Self::write_context(writer),
+            )
+        })
+    }
+
+    /// It reads the next byte from the input consuming it.
+    ///
+    /// # Errors
+    ///
+    /// Will return and errors if:
+    ///
+    /// - It can't read from the input.
+    /// - The byte read is not the expected one (the previously peeked byte).
+    fn read_peeked_byte<W: Writer>(
+        &mut self,
+        peeked_byte: u8,
+        writer: &W,
+    ) -> Result<(), error::Error> {
+        let byte = self.byte_reader.read_byte()?;
+
+        if byte == peeked_byte {
+            return Ok(());
+        }
+
+        Err(error::Error::ReadByteAfterPeekingDoesMatchPeekedByte(
+            ReadContext {
+                byte: Some(byte),
+                ..self.read_context()
+            },
+            WriteContext {
+                byte: Some(byte),
+                ..Self::write_context(writer)
+            },
+        ))
+    }
+
+    /// It peeks the next byte from the input without consuming it. It returns
+    /// `None` if the input has ended.
+    ///
+    /// # Errors
+    ///
+    /// Will return and errors if it can't read from the input.
+    fn peek_byte(&mut self) -> Result<Option<u8>, error::Error> {
+        match self.byte_reader.peek_byte() {
+            Ok(byte) => Ok(Some(byte)),
+            Err(err) => {
+                if err.kind() == io::ErrorKind::UnexpectedEof {
+                    return Ok(None);
+                }
+                Err(err.into())
+            }
+        }
+    }
+
+    /// It updates the stack state.
+    ///
+    /// Called when the first byte of a bencoded value (integer, string, list
+    /// or dict) is received.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if a dictionary key is not a string.
+    fn begin_bencoded_value<W: Writer>(
+        &mut self,
+        bencode_type: BencodeType,
+        writer: &W,
+    ) -> Result<(), error::Error> {
+        match self.stack.peek() {
+            State::Initial => {}
+            State::ExpectingNextListItem => {
+                self.stack.begin_list_item();
+            }
+            State::ExpectingFirstListItemOrEnd => {
+                self.stack.swap_top(State::ExpectingNextListItem);
+                self.stack.begin_list_item();
+            }
+            State::ExpectingFirstDictFieldOrEnd | State::ExpectingDictFieldKeyOrEnd => {
+                if bencode_type != BencodeType::String {
+                    return Err(error::Error::ExpectedStringForDictKeyGot(
+                        bencode_type,
+                        self.next_token_read_context(),
+                        Self::write_context(writer),
+                    ));
+                }
+
+                self.stack.swap_top(State::ExpectingDictFieldValue);
+            }
+            State::ExpectingDictFieldValue => {
+                self.stack.swap_top(State::ExpectingDictFieldKeyOrEnd);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// It keeps the dictionary key to validate the next one.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error in strict mode if the key is not greater than the
+    /// previous key in the same dictionary.
+    fn add_dict_key<W: Writer>(&mut self, key: &[u8], writer: &W) -> Result<(), error::Error> {
+        if self.config.strict {
+            if let Some(previous_key) = self.stack.last_key() {
+                if key <= previous_key {
+                    let read_context = self.token_read_context();
+
+                    let write_context = Self::write_context(writer);
+
+                    if key == previous_key {
+                        return Err(error::Error::DuplicateDictKey(read_context, write_context));
+                    }
+
+                    return Err(error::Error::UnsortedDictKeys(read_context, write_context));
+                }
+            }
+        }
+
+        self.stack.set_last_key(key.to_vec());
+
+        Ok(())
+    }
+
+    /// It updates the stack state.
+    ///
+    /// Called when the end of list or dictionary byte is received. End of
+    /// integers or strings are processed while parsing them.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the end of bencoded value (list or dictionary)
+    /// was not expected.
+    fn end_list_or_dict<W: Writer>(&mut self, writer: &W) -> Result<(), error::Error> {
+        match self.stack.peek() {
+            State::ExpectingFirstListItemOrEnd
+            | State::ExpectingNextListItem
+            | State::ExpectingFirstDictFieldOrEnd
+            | State::ExpectingDictFieldKeyOrEnd => {
+                self.stack.pop();
+                Ok(())
+            }
+            State::ExpectingDictFieldValue => Err(error::Error::PrematureEndOfDict(
+                self.token_read_context(),
+                Self::write_context(writer),
+            )),
+            State::Initial => Err(error::Error::NoMatchingStartForListOrDictEnd(
+                self.token_read_context(),
+                Self::write_context(writer),
+            )),
+        }
+    }
+
+    fn read_context(&self) -> ReadContext {
+        ReadContext {
+            byte: None,
+            pos: self.byte_reader.input_byte_counter(),
+            latest_bytes: self.byte_reader.captured_bytes(),
+            path: self.stack.path(),
+            next_bytes: self.byte_reader.next_bytes(),
+            token_start: None,
+        }
+    }
+
+    /// The read context for errors about the whole latest token.
+    fn token_read_context(&self) -> ReadContext {
+        ReadContext {
+            token_start: Some(self.token_start),
+            ..self.read_context()
+        }
+    }
+
+    /// The read context for errors about the latest token, before the stack
+    /// has moved to the value it begins.
+    fn next_token_read_context(&self) -> ReadContext {
+        ReadContext {
+            path: self.stack.next_path(),
+            ..self.token_read_context()
+        }
+    }
+
+    fn write_context<W: Writer>(writer: &W) -> WriteContext {
+        WriteContext {
+            byte: None,
+            pos: writer.output_byte_counter(),
+            latest_bytes: writer.captured_bytes(),
+        }
+    }
+
+    /// It checks if the stack state is correct at the end of the parsing.
+    ///
+    /// That could happen, for example, when bencode values are not finished.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the stack state is not correct.
+    fn check_bad_end_stack_state<W: Writer>(&self, writer: &W) -> Result<(), error::Error> {
+        let read_context = self.read_context();
+
+        let write_context = Self::write_context(writer);
+
+        match self.stack.peek() {
+            State::Initial => Ok(()),
+            State::ExpectingFirstListItemOrEnd => Err(
+                error::Error::UnexpectedEndOfInputExpectingFirstListItemOrEnd(
+                    read_context,
+                    write_context,
+                ),
+            ),
+            State::ExpectingNextListItem => {
+                Err(error::Error::UnexpectedEndOfInputExpectingNextListItem(
+                    read_context,
+                    write_context,
+                ))
+            }
+            State::ExpectingFirstDictFieldOrEnd => Err(
+                error::Error::UnexpectedEndOfInputExpectingFirstDictFieldOrEnd(
+                    read_context,
+                    write_context,
+                ),
+            ),
+            State::ExpectingDictFieldValue => {
+                Err(error::Error::UnexpectedEndOfInputExpectingDictFieldValue(
+                    read_context,
+                    write_context,
+                ))
+            }
+            State::ExpectingDictFieldKeyOrEnd => Err(
+                error::Error::UnexpectedEndOfInputExpectingDictFieldKeyOrEnd(
+                    read_context,
+                    write_context,
+                ),
+            ),
+        }
+    }
+}
+
+/// It returns true if the byte is part of a line break: `\n`, `\r\n` or `\r`.
+fn is_line_break(byte: u8) -> bool {
+    byte == b'\n' || byte == b'\r'
+}
+
+/// It iterates over the tokens until the input ends or there is an error.
+/// The error is the last item.
+///
+/// Errors do not include the write context, because nothing is written.
+impl<R: Read> Iterator for Tokenizer<R> {
+    type Item = Result<Event, error::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        let result = self.next_event(&NullWriter::new());
+
+        self.failed = result.is_err();
+
+        result.transpose()
+    }
+}
+
+impl<R: Read> FusedIterator for Tokenizer<R> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::parsers::{
+        error::Error,
+        tokenizer::{Event, Token, Tokenizer},
+    };
+
+    fn tokens(input: &[u8]) -> Vec<Token> {
+        Tokenizer::new(input)
+            .map(|event| event.unwrap().token)
+            .collect()
+    }
+
+    mod it_should_return {
+        use crate::parsers::tokenizer::{tests::tokens, Token};
+
+        #[test]
+        fn no_tokens_for_an_empty_input() {
+            assert!(tokens(b"").is_empty());
+        }
+
+        #[test]
+        fn an_integer_with_its_digits() {
+            assert_eq!(tokens(b"i-42e"), vec![Token::Integer(b"-42".to_vec())]);
+        }
+
+        #[test]
+        fn an_integer_greater_than_i64_max() {
+            assert_eq!(
+                tokens(b"i9223372036854775808e"),
+                vec![Token::Integer(b"9223372036854775808".to_vec())]
+            );
+        }
+
+        #[test]
+        fn a_string_with_its_raw_bytes() {
+            assert_eq!(
+                tokens(b"2:\xFF\xFE"),
+                vec![Token::Bytes(b"\xFF\xFE".to_vec())]
+            );
+        }
+
+        #[test]
+        fn the_beginning_and_the_end_of_a_list() {
+            assert_eq!(
+                tokens(b"li1ee"),
+                vec![Token::ListStart, Token::Integer(b"1".to_vec()), Token::End]
+            );
+        }
+
+        #[test]
+        fn the_beginning_and_the_end_of_a_dictionary() {
+            assert_eq!(
+                tokens(b"d3:foo3:bare"),
+                vec![
+                    Token::DictStart,
+                    Token::Bytes(b"foo".to_vec()),
+                    Token::Bytes(b"bar".to_vec()),
+                    Token::End
+                ]
+            );
+        }
+
+        #[test]
+        fn nested_tokens_ignoring_line_breaks() {
+            assert_eq!(
+                tokens(b"\nd1:ald1:bi1eeee\n"),
+                vec![
+                    Token::DictStart,
+                    Token::Bytes(b"a".to_vec()),
+                    Token::ListStart,
+                    Token::DictStart,
+                    Token::Bytes(b"b".to_vec()),
+                    Token::Integer(b"1".to_vec()),
+                    Token::End,
+                    Token::End,
+                    Token::End
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn it_should_return_the_byte_offsets_of_each_token() {
+        let events = Tokenizer::new(&b"\nl4:spami-1ee"[..])
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(
+            events,
+            vec![
+                Event {
+                    token: Token::ListStart,
+                    start: 1,
+                    end: 2
+                },
+                Event {
+                    token: Token::Bytes(b"spam".to_vec()),
+                    start: 2,
+                    end: 8
+                },
+                Event {
+                    token: Token::Integer(b"-1".to_vec()),
+                    start: 8,
+                    end: 12
+                },
+                Event {
+                    token: Token::End,
+                    start: 12,
+                    end: 13
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn it_should_return_the_state_before_the_next_token() {
+        use crate::parsers::stack::State;
+
+        let mut tokenizer = Tokenizer::new(&b"d1:ai1ee"[..]);
+
+        assert_eq!(tokenizer.state(), State::Initial);
+
+        tokenizer.next();
+        assert_eq!(tokenizer.state(), State::ExpectingFirstDictFieldOrEnd);
+
+        tokenizer.next();
+        assert_eq!(tokenizer.state(), State::ExpectingDictFieldValue);
+
+        tokenizer.next();
+        assert_eq!(tokenizer.state(), State::ExpectingDictFieldKeyOrEnd);
+
+        tokenizer.next();
+        assert_eq!(tokenizer.state(), State::Initial);
+    }
+
+    #[test]
+    fn it_should_allow_reading_string_values_in_chunks() {
+        use crate::rw::null_writer::NullWriter;
+
+        let writer = NullWriter::new();
+        let mut tokenizer = Tokenizer::new(&b"d3:key5:valuee"[..]);
+        let mut chunk = [0; 2];
+
+        tokenizer.next_event(&writer).unwrap();
+
+        // Dictionary keys are not string values
+        assert_eq!(tokenizer.begin_string_value(&writer).unwrap(), None);
+        assert_eq!(
+            tokenizer.next_event(&writer).unwrap().unwrap().token,
+            Token::Bytes(b"key".to_vec())
+        );
+
+        assert_eq!(tokenizer.begin_string_value(&writer).unwrap(), Some(5));
+
+        let mut value = vec![];
+        loop {
+            let chunk_len = tokenizer
+                .read_string_value_chunk(&mut chunk, &writer)
+                .unwrap();
+            if chunk_len == 0 {
+                break;
+            }
+            value.extend_from_slice(&chunk[..chunk_len]);
+        }
+        assert_eq!(value, b"value");
+
+        assert_eq!(
+            tokenizer.next_event(&writer).unwrap().unwrap().token,
+            Token::End
+        );
+    }
+
+    mod it_should_fail {
+        use crate::parsers::{
+            error::Error,
+            tokenizer::{tests::first_error, Tokenizer},
+            BencodeType,
+        };
+
+        #[test]
+        fn when_a_dictionary_key_is_not_a_string() {
+            assert!(matches!(
+                first_error(b"di1ei2ee"),
+                Error::ExpectedStringForDictKeyGot(BencodeType::Integer, _, _)
+            ));
+        }
+
+        #[test]
+        fn when_a_dictionary_field_does_not_have_a_value() {
+            assert!(matches!(
+                first_error(b"d3:fooe"),
+                Error::PrematureEndOfDict { .. }
+            ));
+        }
+
+        #[test]
+        fn when_the_end_of_a_list_or_dictionary_does_not_have_a_start() {
+            assert!(matches!(
+                first_error(b"e"),
+                Error::NoMatchingStartForListOrDictEnd { .. }
+            ));
+        }
+
+        #[test]
+        fn when_the_input_ends_before_the_value_is_finished() {
+            assert!(matches!(
+                first_error(b"li1e"),
+                Error::UnexpectedEndOfInputExpectingNextListItem { .. }
+            ));
+        }
+
+        #[test]
+        fn when_it_cannot_recognized_the_first_byte_of_a_token() {
+            assert!(matches!(
+                first_error(b"x"),
+                Error::UnrecognizedFirstBencodeValueByte { .. }
+            ));
+        }
+
+        #[test]
+        fn only_once_and_then_stop_returning_tokens() {
+            let events = Tokenizer::new(&b"li1ex"[..]).collect::<Vec<_>>();
+
+            assert_eq!(events.len(), 3);
+            assert!(matches!(
+                events[2],
+                Err(Error::UnrecognizedFirstBencodeValueByte { .. })
+            ));
+        }
+
+        #[test]
+        fn including_the_path_to_the_value_being_parsed() {
+            let error = first_error(b"d4:infod5:filesld6:lengthi1eed6:lengthi1xeeee");
+
+            assert_eq!(error.read_context().unwrap().path, "info.files[1].length");
+        }
+
+        #[test]
+        fn including_the_index_of_the_next_list_item_when_its_first_byte_is_unrecognized() {
+            assert_eq!(
+                first_error(b"li1ei2exe").read_context().unwrap().path,
+                "[2]"
+            );
+            assert_eq!(first_error(b"lxe").read_context().unwrap().path, "[0]");
+            assert_eq!(
+                first_error(b"d4:infod5:filesli1ex3:abcee")
+                    .read_context()
+                    .unwrap()
+                    .path,
+                "info.files[1]"
+            );
+        }
+
+        #[test]
+        fn including_the_path_of_the_dictionary_when_the_first_byte_of_a_key_is_unrecognized() {
+            assert_eq!(first_error(b"d1:ai1exe").read_context().unwrap().path, "");
+            assert_eq!(
+                first_error(b"d1:ad1:bi1exee").read_context().unwrap().path,
+                "a"
+            );
+        }
+
+        #[test]
+        fn including_the_path_of_the_dictionary_when_a_key_is_not_a_string() {
+            assert_eq!(
+                first_error(b"d1:ad1:bi1ei2ei3eee")
+                    .read_context()
+                    .unwrap()
+                    .path,
+                "a"
+            );
+        }
+
+        #[test]
+        fn including_the_path_when_the_string_is_not_finished() {
+            let error = first_error(b"ll4:spamel3:egg");
+
+            assert_eq!(error.read_context().unwrap().path, "[1][0]");
+        }
+
+        #[test]
+        fn including_the_write_context_of_the_consumer() {
+            use crate::rw::{string_writer::StringWriter, writer::Writer};
+
+            let mut output = String::new();
+            let mut writer = StringWriter::new(&mut output);
+            writer.write_str("[").unwrap();
+
+            let mut tokenizer = Tokenizer::new(&b"x"[..]);
+
+            let Err(Error::UnrecognizedFirstBencodeValueByte(_, write_context)) =
+                tokenizer.next_event(&writer)
+            else {
+                panic!("expected an unrecognized first byte error");
+            };
+
+            assert_eq!(write_context.pos, 1);
+        }
+    }
+
+    mod in_strict_mode {
+        use crate::parsers::{error::Error, tokenizer::Tokenizer};
+
+        fn first_error(input: &[u8]) -> Option<Error> {
+            Tokenizer::new(input).strict().find_map(Result::err)
+        }
+
+        #[test]
+        fn it_should_allow_sorted_dictionary_keys() {
+            assert!(first_error(b"d1:ai1e1:bi2e2:bai3ee").is_none());
+        }
+
+        #[test]
+        fn it_should_compare_keys_by_their_raw_bytes() {
+            assert!(first_error(b"d1:Bi1e1:ai2e1:\xFFi3ee").is_none());
+        }
+
+        #[test]
+        fn it_should_only_compare_keys_in_the_same_dictionary() {
+            assert!(first_error(b"d1:bd1:ai1ee1:cd1:ai1eee").is_none());
+        }
+
+        #[test]
+        fn it_should_fail_when_dictionary_keys_are_not_sorted() {
+            assert!(matches!(
+                first_error(b"d1:bi1e1:ai2ee"),
+                Some(Error::UnsortedDictKeys { .. })
+            ));
+        }
+
+        #[test]
+        fn it_should_point_to_the_first_byte_of_the_unsorted_key() {
+            let error = first_error(b"d1:bi1e1:ai2ee").unwrap();
+
+            assert_eq!(error.read_context().unwrap().offset(), 7);
+        }
+
+        #[test]
+        fn it_should_fail_when_a_dictionary_key_is_duplicated() {
+            assert!(matches!(
+                first_error(b"d1:ai1e1:ai2ee"),
+                Some(Error::DuplicateDictKey { .. })
+            ));
+        }
+
+        #[test]
+        fn it_should_allow_unsorted_and_duplicate_keys_when_it_is_disabled() {
+            assert!(Tokenizer::new(&b"d1:bi1e1:ai2e1:ai3ee"[..]).all(|event| event.is_ok()));
+        }
+    }
+
+    mod with_limits {
+        use crate::parsers::{config::ParserConfig, error::Error, tokenizer::Tokenizer};
+
+        fn first_error(input: &[u8], config: ParserConfig) -> Option<Error> {
+            Tokenizer::with_config(input, config).find_map(Result::err)
+        }
+
+        #[test]
+        fn it_should_allow_input_within_all_the_limits() {
+            let config = ParserConfig::default()
+                .with_max_depth(2)
+                .with_max_string_len(4)
+                .with_max_integer_digits(1)
+                .with_max_input_bytes(13)
+                .with_max_tokens(6);
+
+            assert!(first_error(b"ld4:spami1eee", config).is_none());
+        }
+
+        #[test]
+        fn it_should_fail_when_lists_and_dictionaries_are_nested_too_deep() {
+            assert!(matches!(
+                first_error(b"llleee", ParserConfig::default().with_max_depth(2)),
+                Some(Error::MaxDepthExceeded(2, read_context, _)) if read_context.pos == 3
+            ));
+        }
+
+        #[test]
+        fn it_should_fail_when_a_string_is_too_long() {
+            assert!(matches!(
+                first_error(b"l5:spamse", ParserConfig::default().with_max_string_len(4)),
+                Some(Error::MaxStringLengthExceeded(4, _, _))
+            ));
+        }
+
+        #[test]
+        fn it_should_not_read_the_bytes_of_a_huge_string() {
+            assert!(matches!(
+                first_error(
+                    b"99999999999999:",
+                    ParserConfig::default().with_max_string_len(1024)
+                ),
+                Some(Error::MaxStringLengthExceeded(1024, _, _))
+            ));
+        }
+
+        #[test]
+        fn it_should_fail_when_an_integer_has_too_many_digits() {
+            assert!(matches!(
+                first_error(
+                    b"li1ei-123e",
+                    ParserConfig::default().with_max_integer_digits(2)
+                ),
+                Some(Error::MaxIntegerDigitsExceeded(2, _, _))
+            ));
+        }
+
+        #[test]
+        fn it_should_fail_when_the_input_is_too_big() {
+            assert!(matches!(
+                first_error(b"li1ei2ee", ParserConfig::default().with_max_input_bytes(4)),
+                Some(Error::MaxInputBytesExceeded(4, read_context, _)) if read_context.pos == 4
+            ));
+        }
+
+        #[test]
+        fn it_should_fail_when_there_are_too_many_tokens() {
+            assert!(matches!(
+                first_error(b"li1ei2ee", ParserConfig::default().with_max_tokens(3)),
+                Some(Error::MaxTokensExceeded(3, _, _))
+            ));
+        }
+
+        #[test]
+        fn it_should_not_count_line_breaks_as_tokens() {
+            assert!(first_error(b"\ni1e\n", ParserConfig::default().with_max_tokens(1)).is_none());
+        }
+    }
+
+    fn first_error(input: &[u8]) -> Error {
+        Tokenizer::new(input)
+            .find_map(Result::err)
+            .expect("the input should be invalid")
+    }
+}
@@ -0,0 +1,279 @@
+//! YAML emission for the [`BencodeParser`](super::BencodeParser).
+//!
+//! The parser writes the same tokens as in JSON, and the emitter only writes
+//! the delimiters and indentation, which depend on the state of the
+//! [`Stack`](super::stack::Stack) before each token:
+//!
+//! ```yaml
+//! announce: "udp://tracker.example.com:6969"
+//! info:
+//!   files:
+//!     - length: 10
+//!       path: ["a", "b.txt"]
+//!   name: "example"
+//! url-list: []
+//! ```
+//!
+//! Lists and dictionaries are written in block style. Strings are written as
+//! double-quoted scalars, like in JSON, so strings that are not valid UTF-8
+//! are written with the selected [`StringEncoding`](super::string::StringEncoding).
+//! Dictionary keys are written as plain scalars when they do not need quotes.
+use super::stack::State;
+use crate::rw::{error::Error, writer::Writer};
+
+/// The number of spaces for each nesting level.
+const INDENT_WIDTH: usize = 2;
+
+/// Keys that are not strings when they are plain scalars, for example,
+/// booleans in YAML 1.1.
+const RESERVED_PLAIN_KEYS: [&str; 11] = [
+    "y", "n", "yes", "no", "on", "off", "true", "false", "null", "~", "",
+];
+
+/// Where a list or dictionary is in the document.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Placement {
+    /// It's the top-level value.
+    TopLevel,
+
+    /// It's an item of a list, after the item indicator: `- `.
+    ListItem,
+
+    /// It's the value of a dictionary field, after the key.
+    DictFieldValue,
+}
+
+impl Placement {
+    /// The first entry is written in the same line when the list or
+    /// dictionary is at the beginning of the document or after `- `.
+    fn has_inline_first_entry(self) -> bool {
+        self != Self::DictFieldValue
+    }
+}
+
+/// It writes the YAML delimiters and indentation between tokens.
+#[derive(Debug, Default)]
+pub(crate) struct YamlEmitter {
+    /// The top-level values are the items of a sequence.
+    top_level_sequence: bool,
+
+    /// The placement of each list or dictionary not closed yet.
+    placements: Vec<Placement>,
+}
+
+impl YamlEmitter {
+    /// It creates an emitter. When `top_level_sequence` is true, each
+    /// top-level value is written as an item of a sequence.
+    pub(crate) fn new(top_level_sequence: bool) -> Self {
+        Self {
+            top_level_sequence,
+            placements: Vec::new(),
+        }
+    }
+
+    /// It writes the delimiters before a dictionary key.
+    ///
+    /// The depth is the number of lists and dictionaries the key is in.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if it can't write to the output.
+    pub(crate) fn begin_key<W: Writer>(
+        &self,
+        state: &State,
+        depth: usize,
+        writer: &mut W,
+    ) -> Result<(), Error> {
+        self.begin_entry(state, depth, writer)
+    }
+
+    /// It writes the delimiters before a value: an integer, string, list or
+    /// dictionary.
+    ///
+    /// The depth is the number of lists and dictionaries the value is in.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if it can't write to the output.
+    pub(crate) fn begin_value<W: Writer>(
+        &mut self,
+        state: &State,
+        depth: usize,
+        is_list_or_dict: bool,
+        writer: &mut W,
+    ) -> Result<(), Error> {
+        let placement = match state {
+            State::Initial => {
+                if self.top_level_sequence {
+                    writer.write_str("- ")?;
+                    Placement::ListItem
+                } else {
+                    Placement::TopLevel
+                }
+            }
+            State::ExpectingFirstListItemOrEnd | State::ExpectingNextListItem => {
+                self.begin_entry(state, depth, writer)?;
+                writer.write_str("- ")?;
+                Placement::ListItem
+            }
+            State::ExpectingDictFieldValue => {
+                writer.write_byte(b':')?;
+                Placement::DictFieldValue
+            }
+            State::ExpectingFirstDictFieldOrEnd | State::ExpectingDictFieldKeyOrEnd => {
+                panic!("unexpected value in state {state}, expecting a dictionary key")
+            }
+        };
+
+        if is_list_or_dict {
+            self.placements.push(placement);
+        } else if placement == Placement::DictFieldValue {
+            writer.write_byte(b' ')?;
+        }
+
+        Ok(())
+    }
+
+    /// It writes the end of a list or dictionary, which is only visible when
+    /// it's empty: `[]` or `{}`.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if it can't write to the output.
+    pub(crate) fn end_list_or_dict<W: Writer>(
+        &mut self,
+        state: &State,
+        writer: &mut W,
+    ) -> Result<(), Error> {
+        let placement = self.placements.pop();
+
+        let empty = match state {
+            State::ExpectingFirstListItemOrEnd => "[]",
+            State::ExpectingFirstDictFieldOrEnd => "{}",
+            _ => return Ok(()),
+        };
+
+        if placement == Some(Placement::DictFieldValue) {
+            writer.write_byte(b' ')?;
+        }
+
+        writer.write_str(empty)
+    }
+
+    /// It writes an extra dictionary field, which is not in the input, after
+    /// the current field.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if it can't write to the output.
+    pub(crate) fn write_extra_field<W: Writer>(
+        &self,
+        key: &str,
+        json_value: &str,
+        depth: usize,
+        writer: &mut W,
+    ) -> Result<(), Error> {
+        self.write_line_break(depth, writer)?;
+        writer.write_str(key)?;
+        writer.write_str(": ")?;
+        writer.write_str(json_value)
+    }
+
+    /// It writes the line break and indentation before an entry of a list or
+    /// dictionary, except for the first entry when it's in the same line.
+    fn begin_entry<W: Writer>(
+        &self,
+        state: &State,
+        depth: usize,
+        writer: &mut W,
+    ) -> Result<(), Error> {
+        let is_first_entry = matches!(
+            state,
+            State::ExpectingFirstListItemOrEnd | State::ExpectingFirstDictFieldOrEnd
+        );
+
+        let is_inline = self
+            .placements
+            .last()
+            .is_some_and(|placement| placement.has_inline_first_entry());
+
+        if is_first_entry && is_inline {
+            return Ok(());
+        }
+
+        self.write_line_break(depth, writer)
+    }
+
+    fn write_line_break<W: Writer>(&self, depth: usize, writer: &mut W) -> Result<(), Error> {
+        let level = depth.saturating_sub(1) + usize::from(self.top_level_sequence);
+
+        writer.write_byte(b'\n')?;
+        writer.write_str(&" ".repeat(level * INDENT_WIDTH))
+    }
+}
+
+/// It returns true if the dictionary key can be written as a plain scalar,
+/// without quotes.
+///
+/// Only simple ASCII keys, like `announce-list` or `creation date`, are
+/// written without quotes. Keys that would be read as other types, like
+/// `true`, or that contain YAML indicators are written as double-quoted
+/// scalars.
+pub(crate) fn is_plain_key(key: &[u8]) -> bool {
+    let Ok(key) = std::str::from_utf8(key) else {
+        return false;
+    };
+
+    let starts_with_letter = key
+        .bytes()
+        .next()
+        .is_some_and(|byte| byte.is_ascii_alphabetic() || byte == b'_');
+
+    starts_with_letter
+        && !key.ends_with(' ')
+        && key
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || b" _-./()".contains(&byte))
+        && !RESERVED_PLAIN_KEYS
+            .iter()
+            .any(|reserved| key.eq_ignore_ascii_case(reserved))
+}
+
+#[cfg(test)]
+mod tests {
+
+    mod plain_keys {
+        use crate::parsers::yaml::is_plain_key;
+
+        #[test]
+        fn should_be_simple_ascii_keys() {
+            assert!(is_plain_key(b"announce"));
+            assert!(is_plain_key(b"announce-list"));
+            assert!(is_plain_key(b"creation date"));
+            assert!(is_plain_key(b"_private"));
+            assert!(is_plain_key(b"creation date (ISO 8601)"));
+        }
+
+        #[test]
+        fn should_not_be_keys_that_would_be_read_as_other_types() {
+            assert!(!is_plain_key(b""));
+            assert!(!is_plain_key(b"true"));
+            assert!(!is_plain_key(b"No"));
+            assert!(!is_plain_key(b"y"));
+            assert!(!is_plain_key(b"null"));
+            assert!(!is_plain_key(b"42"));
+            assert!(!is_plain_key(b".inf"));
+        }
+
+        #[test]
+        fn should_not_be_keys_with_yaml_indicators_or_non_ascii_chars() {
+            assert!(!is_plain_key(b"a: b"));
+            assert!(!is_plain_key(b"a #b"));
+            assert!(!is_plain_key(b"-a"));
+            assert!(!is_plain_key(b"[a]"));
+            assert!(!is_plain_key(b"a "));
+            assert!(!is_plain_key("ñandú".as_bytes()));
+            assert!(!is_plain_key(b"\xFF"));
+        }
+    }
+}
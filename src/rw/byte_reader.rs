@@ -1,6 +1,7 @@
 //! A reader that reads bytes from an input.
 //!
 //! The input is any type that implements the `std::io::Read` trait.
+use std::fmt;
 use std::io::BufReader;
 use std::io::Error;
 use std::io::Read;
@@ -8,6 +9,10 @@ use std::io::Read;
 use ringbuffer::AllocRingBuffer;
 use ringbuffer::RingBuffer;
 
+/// The maximum number of bytes after the current position returned by
+/// [`ByteReader::next_bytes`].
+const NEXT_BYTES_LIMIT: usize = 16;
+
 /// A reader that reads bytes from an input.
 ///
 /// It's wrapper of a basic reader with extra functionality.
@@ -26,6 +31,30 @@ pub struct ByteReader<R: Read> {
 
     /// A buffer to capture the latest bytes read from the input.
     captured_bytes: AllocRingBuffer<u8>,
+
+    /// The maximum number of bytes that can be read from the input.
+    max_input_bytes: Option<u64>,
+}
+
+/// The payload of the I/O error returned when the reader reaches the maximum
+/// number of bytes that can be read from the input.
+#[derive(Debug)]
+pub struct MaxInputBytesExceeded;
+
+impl fmt::Display for MaxInputBytesExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "maximum number of input bytes exceeded")
+    }
+}
+
+impl std::error::Error for MaxInputBytesExceeded {}
+
+impl MaxInputBytesExceeded {
+    /// It returns true if the I/O error was caused by the input limit.
+    #[must_use]
+    pub fn is_cause_of(err: &Error) -> bool {
+        matches!(err.get_ref(), Some(inner) if inner.is::<Self>())
+    }
 }
 
 impl<R: Read> ByteReader<R> {
@@ -36,14 +65,24 @@ impl<R: Read> ByteReader<R> {
             peeked_byte: None,
             last_byte: None,
             captured_bytes: AllocRingBuffer::new(1024),
+            max_input_bytes: None,
         }
     }
 
+    /// It limits the number of bytes that can be read from the input.
+    #[must_use]
+    pub fn with_max_input_bytes(mut self, max_input_bytes: u64) -> Self {
+        self.max_input_bytes = Some(max_input_bytes);
+        self
+    }
+
     /// It reads one byte from the input.
     ///
     /// # Errors
     ///
-    /// Will return an error if it can't read the byte from the input.
+    /// Will return an error if it can't read the byte from the input or the
+    /// maximum number of input bytes has been reached. In the latter case the
+    /// error payload is [`MaxInputBytesExceeded`].
     pub fn read_byte(&mut self) -> Result<u8, Error> {
         if let Some(byte) = self.peeked_byte.take() {
             return Ok(byte);
@@ -53,6 +92,15 @@ impl<R: Read> ByteReader<R> {
 
         self.reader.read_exact(&mut byte)?;
 
+        // The limit is checked after reading, so reaching the end of the input
+        // is not reported as exceeding the limit.
+        if self
+            .max_input_bytes
+            .is_some_and(|max_input_bytes| self.input_byte_counter >= max_input_bytes)
+        {
+            return Err(Error::other(MaxInputBytesExceeded));
+        }
+
         self.input_byte_counter += 1;
 
         let byte = byte[0];
@@ -90,10 +138,31 @@ impl<R: Read> ByteReader<R> {
         self.captured_bytes.to_vec()
     }
 
+    /// Returns a copy of the next bytes of the input, up to 16, without
+    /// reading them.
+    ///
+    /// Only the bytes already in the buffer are returned, so it never blocks
+    /// waiting for more input. It can return fewer bytes than the bytes left
+    /// in the input.
+    pub fn next_bytes(&self) -> Vec<u8> {
+        let buffer = self.reader.buffer();
+
+        buffer[..buffer.len().min(NEXT_BYTES_LIMIT)].to_vec()
+    }
+
     /// Returns the last byte that was read from the input.
     pub fn last_byte(&self) -> Option<u8> {
         self.last_byte
     }
+
+    /// It returns the buffered reader, positioned right after the last byte
+    /// read from the input.
+    ///
+    /// The peeked byte, if any, is not included. It should only be called
+    /// when there is no peeked byte.
+    pub fn into_inner(self) -> BufReader<R> {
+        self.reader
+    }
 }
 
 #[cfg(test)]
@@ -123,6 +192,33 @@ mod tests {
             assert!(byte_reader.read_byte().is_err());
         }
 
+        #[test]
+        fn it_should_fail_when_it_reaches_the_maximum_number_of_bytes_to_read() {
+            use crate::rw::byte_reader::MaxInputBytesExceeded;
+
+            let input = vec![b'l', b'e'];
+
+            let mut byte_reader = ByteReader::new(input.as_slice()).with_max_input_bytes(1);
+
+            assert_eq!(byte_reader.read_byte().unwrap(), b'l');
+            assert!(MaxInputBytesExceeded::is_cause_of(
+                &byte_reader.read_byte().unwrap_err()
+            ));
+        }
+
+        #[test]
+        fn it_should_reach_the_end_of_the_input_when_the_input_has_the_maximum_number_of_bytes() {
+            let input = vec![b'l'];
+
+            let mut byte_reader = ByteReader::new(input.as_slice()).with_max_input_bytes(1);
+
+            assert_eq!(byte_reader.read_byte().unwrap(), b'l');
+            assert_eq!(
+                byte_reader.read_byte().unwrap_err().kind(),
+                std::io::ErrorKind::UnexpectedEof
+            );
+        }
+
         #[test]
         fn it_should_increase_the_input_byte_counter_by_one_when_reading_a_new_byte() {
             let input = vec![b'l'];
@@ -234,5 +330,27 @@ mod tests {
 
             assert_eq!(byte_reader.captured_bytes(), part2);
         }
+
+        #[test]
+        fn it_should_return_the_next_bytes_without_reading_them() {
+            let input = b"0123456789abcdefghij";
+
+            let mut byte_reader = ByteReader::new(&input[..]);
+
+            byte_reader.read_byte().unwrap();
+
+            assert_eq!(byte_reader.next_bytes(), b"123456789abcdefg");
+            assert_eq!(byte_reader.input_byte_counter(), 1);
+            assert_eq!(byte_reader.read_byte().unwrap(), b'1');
+        }
+
+        #[test]
+        fn it_should_not_return_the_peeked_byte_as_a_next_byte() {
+            let mut byte_reader = ByteReader::new(&b"le"[..]);
+
+            byte_reader.peek_byte().unwrap();
+
+            assert_eq!(byte_reader.next_bytes(), b"e");
+        }
     }
 }
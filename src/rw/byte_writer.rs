@@ -10,6 +10,9 @@ use ringbuffer::RingBuffer;
 
 use super::{error::Error, writer::Writer};
 
+/// The maximum number of latest bytes captured for the error contexts.
+pub const CAPTURED_BYTES_CAPACITY: usize = 1024;
+
 /// A writer that writes to an output implementing `std::io::Write`.
 ///
 /// It's wrapper of a basic writer with extra functionality.
@@ -33,7 +36,7 @@ impl<W: Write> ByteWriter<W> {
             output_byte_counter: 0,
             writer: BufWriter::new(writer),
             last_byte: None,
-            captured_bytes: AllocRingBuffer::new(1024),
+            captured_bytes: AllocRingBuffer::new(CAPTURED_BYTES_CAPACITY),
         }
     }
 
@@ -76,6 +79,20 @@ impl<W: Write> Writer for ByteWriter<W> {
         Ok(())
     }
 
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        self.writer.write_all(bytes)?;
+
+        self.output_byte_counter += bytes.len() as u64;
+
+        if let Some(&last_byte) = bytes.last() {
+            self.last_byte = Some(last_byte);
+        }
+
+        self.captured_bytes.extend(bytes.iter().copied());
+
+        Ok(())
+    }
+
     fn output_byte_counter(&self) -> u64 {
         self.output_byte_counter
     }
@@ -128,6 +145,21 @@ mod tests {
             assert_eq!(output, vec![b'l']);
         }
 
+        #[test]
+        fn it_should_write_raw_bytes_to_the_output() {
+            let mut output = Vec::new();
+
+            let mut byte_writer = ByteWriter::new(&mut output);
+
+            byte_writer.write_bytes(&[0xFF, 0x00]).unwrap();
+
+            assert_eq!(byte_writer.output_byte_counter(), 2);
+
+            drop(byte_writer);
+
+            assert_eq!(output, vec![0xFF, 0x00]);
+        }
+
         #[test]
         fn it_should_increase_the_output_byte_counter_by_the_string_len_after_writing_a_string() {
             let mut output = Vec::new();
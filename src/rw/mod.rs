@@ -2,5 +2,7 @@
 pub mod byte_reader;
 pub mod byte_writer;
 pub mod error;
+pub mod null_writer;
+pub mod pretty_writer;
 pub mod string_writer;
 pub mod writer;
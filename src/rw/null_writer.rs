@@ -0,0 +1,69 @@
+//! A writer that discards everything written to it.
+//!
+//! It's used when the input is parsed without generating any output, for
+//! example when building an in-memory value. Errors include the write context
+//! anyway, so it keeps the counter of discarded bytes.
+use super::{error::Error, writer::Writer};
+
+/// A writer that discards all the output.
+#[derive(Default)]
+pub struct NullWriter {
+    /// Number of bytes discarded.
+    output_byte_counter: u64,
+}
+
+impl NullWriter {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Writer for NullWriter {
+    fn write_byte(&mut self, _byte: u8) -> Result<(), Error> {
+        self.output_byte_counter += 1;
+
+        Ok(())
+    }
+
+    fn write_str(&mut self, value: &str) -> Result<(), Error> {
+        self.output_byte_counter += value.len() as u64;
+
+        Ok(())
+    }
+
+    fn output_byte_counter(&self) -> u64 {
+        self.output_byte_counter
+    }
+
+    fn captured_bytes(&self) -> Vec<u8> {
+        vec![]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    mod for_writing {
+        use crate::rw::{null_writer::NullWriter, writer::Writer};
+
+        #[test]
+        fn it_should_count_the_discarded_bytes() {
+            let mut null_writer = NullWriter::new();
+
+            null_writer.write_byte(b'l').unwrap();
+            null_writer.write_str("le").unwrap();
+
+            assert_eq!(null_writer.output_byte_counter(), 3);
+        }
+
+        #[test]
+        fn it_should_not_capture_the_discarded_bytes() {
+            let mut null_writer = NullWriter::new();
+
+            null_writer.write_byte(b'l').unwrap();
+
+            assert!(null_writer.captured_bytes().is_empty());
+        }
+    }
+}
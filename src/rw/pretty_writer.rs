@@ -0,0 +1,494 @@
+//! A writer that pretty-prints the JSON written to it.
+//!
+//! It wraps another [`Writer`] and adds line breaks and indentation while the
+//! JSON is written, so the document is never kept in memory. The only bytes
+//! kept are the ones of a short array of scalar values that could still be
+//! written in one line, like `[1, 2, 3]`. They are bounded by the maximum
+//! width of compact arrays.
+//!
+//! ```rust
+//! use torrust_bencode2json::rw::{
+//!     pretty_writer::{Indent, PrettyOptions, PrettyWriter},
+//!     string_writer::StringWriter,
+//!     writer::Writer,
+//! };
+//!
+//! let mut output = String::new();
+//!
+//! let mut writer = PrettyWriter::new(
+//!     StringWriter::new(&mut output),
+//!     PrettyOptions::default().with_indent(Indent::Spaces(4)),
+//! );
+//!
+//! writer.write_str(r#"{"a":[1,2],"b":{}}"#).unwrap();
+//!
+//! drop(writer);
+//!
+//! assert_eq!(output, "{\n    \"a\": [1, 2],\n    \"b\": {}\n}");
+//! ```
+use super::{error::Error, writer::Writer};
+
+/// The default maximum width of arrays written in one line.
+pub const DEFAULT_COMPACT_ARRAY_WIDTH: usize = 60;
+
+/// The indentation added for each nesting level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Indent {
+    /// The given number of spaces.
+    Spaces(usize),
+
+    /// The given number of tabs.
+    Tabs(usize),
+}
+
+/// Options for the [`PrettyWriter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrettyOptions {
+    /// The indentation for each nesting level.
+    pub(crate) indent: Indent,
+
+    /// The maximum width, in bytes, of arrays written in one line. `None`
+    /// when all arrays are written with one item per line.
+    pub(crate) compact_array_width: Option<usize>,
+}
+
+impl Default for PrettyOptions {
+    fn default() -> Self {
+        Self {
+            indent: Indent::Spaces(2),
+            compact_array_width: Some(DEFAULT_COMPACT_ARRAY_WIDTH),
+        }
+    }
+}
+
+impl PrettyOptions {
+    /// It sets the indentation for each nesting level. By default, it's two
+    /// spaces.
+    #[must_use]
+    pub fn with_indent(mut self, indent: Indent) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// It sets the maximum width of arrays written in one line. Only arrays
+    /// that do not contain other arrays or objects are written in one line.
+    #[must_use]
+    pub fn with_compact_array_width(mut self, width: usize) -> Self {
+        self.compact_array_width = Some(width);
+        self
+    }
+
+    /// It writes all the arrays with one item per line.
+    #[must_use]
+    pub fn without_compact_arrays(mut self) -> Self {
+        self.compact_array_width = None;
+        self
+    }
+}
+
+/// The position inside a JSON string.
+#[derive(Debug, Default)]
+struct StringState {
+    in_string: bool,
+    escaped: bool,
+}
+
+impl StringState {
+    /// It updates the state with the next byte inside a string.
+    fn update(&mut self, byte: u8) {
+        if self.escaped {
+            self.escaped = false;
+        } else if byte == b'\\' {
+            self.escaped = true;
+        } else if byte == b'"' {
+            self.in_string = false;
+        }
+    }
+}
+
+/// An array that could still be written in one line.
+#[derive(Debug, Default)]
+struct CompactArray {
+    /// The JSON between the brackets, without spaces.
+    json: String,
+
+    /// The positions of the items separators in the JSON.
+    separators: Vec<usize>,
+}
+
+impl CompactArray {
+    /// The width of the array once written, with brackets and a space after
+    /// each separator.
+    fn width(&self) -> usize {
+        self.json.len() + self.separators.len() + 2
+    }
+}
+
+/// A writer that pretty-prints the compact JSON written to it.
+///
+/// The JSON must not contain whitespaces outside strings. Line breaks at the
+/// top level are kept, so every document in a stream of JSON documents is
+/// pretty-printed.
+pub struct PrettyWriter<W: Writer> {
+    writer: W,
+    options: PrettyOptions,
+
+    /// Number of arrays and objects opened and not closed yet.
+    depth: usize,
+
+    /// An array or object has just been opened, so the line break before its
+    /// first item is pending. It's not written for empty ones: `[]`.
+    after_open: bool,
+
+    string: StringState,
+
+    compact_array: Option<CompactArray>,
+}
+
+impl<W: Writer> PrettyWriter<W> {
+    pub fn new(writer: W, options: PrettyOptions) -> Self {
+        Self {
+            writer,
+            options,
+            depth: 0,
+            after_open: false,
+            string: StringState::default(),
+            compact_array: None,
+        }
+    }
+
+    /// It writes one byte of the compact JSON.
+    fn write_json_byte(&mut self, byte: u8) -> Result<(), Error> {
+        if self.compact_array.is_some() {
+            return self.write_compact_array_byte(byte);
+        }
+
+        if self.string.in_string {
+            self.string.update(byte);
+            return self.writer.write_byte(byte);
+        }
+
+        match byte {
+            b'[' if self.options.compact_array_width.is_some() => {
+                self.begin_value()?;
+                self.compact_array = Some(CompactArray::default());
+                Ok(())
+            }
+            b'[' | b'{' => self.open(byte),
+            b']' | b'}' => self.close(byte),
+            b',' => {
+                self.writer.write_byte(byte)?;
+                self.write_line_break()
+            }
+            b':' => self.writer.write_str(": "),
+            b'\n' if self.depth == 0 => self.writer.write_byte(byte),
+            _ => {
+                self.begin_value()?;
+                self.string.in_string = byte == b'"';
+                self.writer.write_byte(byte)
+            }
+        }
+    }
+
+    /// It adds one byte to the array that could be written in one line. The
+    /// array is written with one item per line when it contains arrays or
+    /// objects, or when it's too wide.
+    fn write_compact_array_byte(&mut self, byte: u8) -> Result<(), Error> {
+        let Some(array) = self.compact_array.as_mut() else {
+            return self.write_json_byte(byte);
+        };
+
+        if self.string.in_string {
+            self.string.update(byte);
+        } else {
+            match byte {
+                b']' => {
+                    if let Some(array) = self.compact_array.take() {
+                        self.write_compact_array(&array)?;
+                    }
+                    return Ok(());
+                }
+                b'[' | b'{' => {
+                    self.expand_compact_array()?;
+                    return self.write_json_byte(byte);
+                }
+                b',' => array.separators.push(array.json.len()),
+                b'"' => self.string.in_string = true,
+                _ => {}
+            }
+        }
+
+        array.json.push(char::from(byte));
+
+        self.check_compact_array_width()
+    }
+
+    /// It writes a string without ASCII chars, which is always part of a JSON
+    /// string.
+    fn write_non_ascii_str(&mut self, value: &str) -> Result<(), Error> {
+        if self.compact_array.is_some() {
+            self.write_compact_array_str(value)
+        } else {
+            self.writer.write_str(value)
+        }
+    }
+
+    /// It adds a string to the array that could be written in one line.
+    fn write_compact_array_str(&mut self, value: &str) -> Result<(), Error> {
+        if let Some(array) = self.compact_array.as_mut() {
+            array.json.push_str(value);
+        }
+
+        self.check_compact_array_width()
+    }
+
+    /// It writes the array with one item per line if it's too wide to be
+    /// written in one line.
+    fn check_compact_array_width(&mut self) -> Result<(), Error> {
+        let max_width = self.options.compact_array_width.unwrap_or_default();
+
+        if self
+            .compact_array
+            .as_ref()
+            .is_some_and(|array| array.width() > max_width)
+        {
+            self.expand_compact_array()?;
+        }
+
+        Ok(())
+    }
+
+    fn write_compact_array(&mut self, array: &CompactArray) -> Result<(), Error> {
+        self.writer.write_byte(b'[')?;
+
+        let mut start = 0;
+
+        for &separator in &array.separators {
+            self.writer.write_str(&array.json[start..separator])?;
+            self.writer.write_str(", ")?;
+            start = separator + 1;
+        }
+
+        self.writer.write_str(&array.json[start..])?;
+        self.writer.write_byte(b']')
+    }
+
+    /// It writes the beginning of the array, kept so far to be written in one
+    /// line, with one item per line.
+    fn expand_compact_array(&mut self) -> Result<(), Error> {
+        let Some(array) = self.compact_array.take() else {
+            return Ok(());
+        };
+
+        // The JSON is written again from the beginning of the array, which is
+        // outside any string.
+        self.string = StringState::default();
+
+        self.open(b'[')?;
+        self.write_str(&array.json)
+    }
+
+    fn open(&mut self, byte: u8) -> Result<(), Error> {
+        self.begin_value()?;
+        self.writer.write_byte(byte)?;
+        self.depth += 1;
+        self.after_open = true;
+        Ok(())
+    }
+
+    fn close(&mut self, byte: u8) -> Result<(), Error> {
+        self.depth = self.depth.saturating_sub(1);
+
+        if self.after_open {
+            self.after_open = false;
+        } else {
+            self.write_line_break()?;
+        }
+
+        self.writer.write_byte(byte)
+    }
+
+    /// It writes the line break after the beginning of an array or object
+    /// before its first item.
+    fn begin_value(&mut self) -> Result<(), Error> {
+        if self.after_open {
+            self.after_open = false;
+            self.write_line_break()?;
+        }
+
+        Ok(())
+    }
+
+    fn write_line_break(&mut self) -> Result<(), Error> {
+        self.writer.write_byte(b'\n')?;
+
+        let (unit, width) = match self.options.indent {
+            Indent::Spaces(width) => (" ", width),
+            Indent::Tabs(width) => ("\t", width),
+        };
+
+        self.writer.write_str(&unit.repeat(width * self.depth))
+    }
+}
+
+impl<W: Writer> Writer for PrettyWriter<W> {
+    fn write_byte(&mut self, byte: u8) -> Result<(), Error> {
+        self.write_json_byte(byte)
+    }
+
+    fn write_str(&mut self, value: &str) -> Result<(), Error> {
+        let mut start = 0;
+
+        for (pos, byte) in value.bytes().enumerate() {
+            if !byte.is_ascii() {
+                continue;
+            }
+
+            if start < pos {
+                self.write_non_ascii_str(&value[start..pos])?;
+            }
+
+            self.write_json_byte(byte)?;
+
+            start = pos + 1;
+        }
+
+        if start < value.len() {
+            self.write_non_ascii_str(&value[start..])?;
+        }
+
+        Ok(())
+    }
+
+    fn output_byte_counter(&self) -> u64 {
+        self.writer.output_byte_counter()
+    }
+
+    fn captured_bytes(&self) -> Vec<u8> {
+        self.writer.captured_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    mod it_should {
+        use crate::rw::{
+            pretty_writer::{Indent, PrettyOptions, PrettyWriter},
+            string_writer::StringWriter,
+            writer::Writer,
+        };
+
+        fn pretty(json: &str, options: PrettyOptions) -> String {
+            let mut output = String::new();
+
+            let mut writer = PrettyWriter::new(StringWriter::new(&mut output), options);
+
+            writer.write_str(json).unwrap();
+
+            drop(writer);
+
+            output
+        }
+
+        #[test]
+        fn write_one_field_per_line_with_two_spaces_by_default() {
+            assert_eq!(
+                pretty(r#"{"a":1,"b":{"c":"d"}}"#, PrettyOptions::default()),
+                "{\n  \"a\": 1,\n  \"b\": {\n    \"c\": \"d\"\n  }\n}"
+            );
+        }
+
+        #[test]
+        fn allow_changing_the_indentation_width() {
+            assert_eq!(
+                pretty(
+                    r#"{"a":1}"#,
+                    PrettyOptions::default().with_indent(Indent::Spaces(4))
+                ),
+                "{\n    \"a\": 1\n}"
+            );
+        }
+
+        #[test]
+        fn allow_indenting_with_tabs() {
+            assert_eq!(
+                pretty(
+                    r#"{"a":{"b":1}}"#,
+                    PrettyOptions::default().with_indent(Indent::Tabs(1))
+                ),
+                "{\n\t\"a\": {\n\t\t\"b\": 1\n\t}\n}"
+            );
+        }
+
+        #[test]
+        fn write_empty_arrays_and_objects_in_one_line() {
+            assert_eq!(
+                pretty("[{},[]]", PrettyOptions::default()),
+                "[\n  {},\n  []\n]"
+            );
+        }
+
+        #[test]
+        fn write_short_arrays_of_scalar_values_in_one_line() {
+            assert_eq!(
+                pretty(r#"{"a":[1,"b",2]}"#, PrettyOptions::default()),
+                "{\n  \"a\": [1, \"b\", 2]\n}"
+            );
+        }
+
+        #[test]
+        fn write_arrays_containing_arrays_or_objects_with_one_item_per_line() {
+            assert_eq!(
+                pretty("[1,[2,3]]", PrettyOptions::default()),
+                "[\n  1,\n  [2, 3]\n]"
+            );
+        }
+
+        #[test]
+        fn write_arrays_wider_than_the_limit_with_one_item_per_line() {
+            let options = PrettyOptions::default().with_compact_array_width(6);
+
+            assert_eq!(pretty("[1,2]", options), "[1, 2]");
+            assert_eq!(pretty("[1,2,3]", options), "[\n  1,\n  2,\n  3\n]");
+        }
+
+        #[test]
+        fn allow_writing_all_arrays_with_one_item_per_line() {
+            assert_eq!(
+                pretty("[1,2]", PrettyOptions::default().without_compact_arrays()),
+                "[\n  1,\n  2\n]"
+            );
+        }
+
+        #[test]
+        fn not_change_the_strings() {
+            assert_eq!(
+                pretty(
+                    r#"{"a,[b]:{c}":["\"[,]\\","ñandú"]}"#,
+                    PrettyOptions::default()
+                ),
+                "{\n  \"a,[b]:{c}\": [\"\\\"[,]\\\\\", \"ñandú\"]\n}"
+            );
+        }
+
+        #[test]
+        fn not_change_the_strings_in_arrays_wider_than_the_limit() {
+            assert_eq!(
+                pretty(
+                    r#"["ñ,[","\"]"]"#,
+                    PrettyOptions::default().with_compact_array_width(4)
+                ),
+                "[\n  \"ñ,[\",\n  \"\\\"]\"\n]"
+            );
+        }
+
+        #[test]
+        fn keep_the_line_breaks_between_top_level_values() {
+            assert_eq!(
+                pretty("{\"a\":1}\n[]\n", PrettyOptions::default()),
+                "{\n  \"a\": 1\n}\n[]\n"
+            );
+        }
+    }
+}
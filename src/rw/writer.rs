@@ -12,6 +12,9 @@
    It could be refactored to be more restrictive. However, in the future we also
    want to print Bencoded strings as bytes streams, without trying to convert
    them into UTF-8 strings.
+
+   The binary output formats (CBOR and MessagePack) use `write_bytes`, which
+   writes any byte. They can only be written with a byte-oriented writer.
 */
 
 use super::error::Error;
@@ -31,6 +34,20 @@ pub trait Writer {
     /// Will return an error if it can't write the string.
     fn write_str(&mut self, value: &str) -> Result<(), Error>;
 
+    /// It writes raw bytes to the output. They don't have to be UTF-8, so
+    /// only writers for byte outputs can write them unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if it can't write the bytes.
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        for &byte in bytes {
+            self.write_byte(byte)?;
+        }
+
+        Ok(())
+    }
+
     /// It return the number of bytes that have been written to the output.
     fn output_byte_counter(&self) -> u64;
 
@@ -0,0 +1,43 @@
+//! Serialization errors.
+use std::fmt::Display;
+
+use thiserror::Error;
+
+use crate::{parsers::error::WriteContext, rw};
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("R/W error: {0}")]
+    Rw(#[from] rw::error::Error),
+
+    /// An error generated by the serialized type.
+    #[error("{0}")]
+    Custom(String),
+
+    #[error("Type without bencode equivalent: {0}, only integers, strings, lists and dictionaries are allowed; {1}")]
+    UnsupportedType(&'static str, WriteContext),
+
+    #[error("Dictionary keys must be strings or bytes; {0}")]
+    ExpectedStringForDictKey(WriteContext),
+
+    #[error("Duplicate dictionary key: {0}; {1}")]
+    DuplicateDictKey(String, WriteContext),
+}
+
+impl Error {
+    /// It returns the write context of the error, if it has one.
+    pub(crate) fn write_context_mut(&mut self) -> Option<&mut WriteContext> {
+        match self {
+            Error::Rw(_) | Error::Custom(_) => None,
+            Error::UnsupportedType(_, write_context)
+            | Error::ExpectedStringForDictKey(write_context)
+            | Error::DuplicateDictKey(_, write_context) => Some(write_context),
+        }
+    }
+}
+
+impl serde::ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::Custom(msg.to_string())
+    }
+}
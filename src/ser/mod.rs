@@ -0,0 +1,1106 @@
+//! Serde serializer for bencoded output.
+//!
+//! It serializes your own types into canonical bencode, as required by
+//! [BEP 3](https://www.bittorrent.org/beps/bep_0003.html):
+//!
+//! - Dictionary keys are sorted by their raw bytes.
+//! - Integers do not have leading zeros or a negative zero.
+//!
+//! Bencode only has four types, so some serde types are mapped:
+//!
+//! - Strings and chars are byte strings. Use `serde_bytes` for fields
+//!   containing raw bytes, otherwise they are serialized as lists of integers.
+//! - Booleans are the integers `0` and `1`.
+//! - Dictionary fields with a `None` value are not written. `None` values
+//!   anywhere else, like list items, can't be serialized, because they would
+//!   be lost.
+//! - Unit enum variants are strings. Other variants are dictionaries with
+//!   one field, where the key is the variant name.
+//!
+//! Floats, units and dictionary keys that are not strings can't be
+//! serialized.
+pub mod error;
+
+use std::{fmt::Display, io::Write as IoWrite};
+
+use serde::{
+    ser::{
+        self, Impossible, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant,
+        SerializeTuple, SerializeTupleStruct, SerializeTupleVariant,
+    },
+    Serialize,
+};
+
+use crate::{
+    parsers::{
+        error::WriteContext, BENCODE_BEGIN_DICT, BENCODE_BEGIN_INTEGER, BENCODE_BEGIN_LIST,
+        BENCODE_END_INTEGER, BENCODE_END_LIST_OR_DICT, BENCODE_END_STRING_LENGTH,
+    },
+    rw::{
+        self,
+        byte_writer::{ByteWriter, CAPTURED_BYTES_CAPACITY},
+        writer::Writer,
+    },
+};
+
+use error::Error;
+
+/// It serializes a value into bencoded bytes.
+///
+/// # Errors
+///
+/// Will return an error if the value can't be represented in bencode.
+pub fn to_vec<T: ?Sized + Serialize>(value: &T) -> Result<Vec<u8>, Error> {
+    let mut output = Vec::new();
+    to_writer(&mut output, value)?;
+    Ok(output)
+}
+
+/// It serializes a value into bencoded bytes written to the output.
+///
+/// # Errors
+///
+/// Will return an error if the value can't be represented in bencode or it
+/// can't write to the output.
+pub fn to_writer<W: IoWrite, T: ?Sized + Serialize>(writer: W, value: &T) -> Result<(), Error> {
+    let mut serializer = Serializer::new(ByteWriter::new(writer));
+    value.serialize(&mut serializer)
+}
+
+pub struct Serializer<W: Writer> {
+    writer: W,
+
+    /// The output position where a `None` value is skipped, which is the
+    /// beginning of a dictionary field value.
+    none_allowed_at: Option<u64>,
+}
+
+impl<W: Writer> Serializer<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            none_allowed_at: None,
+        }
+    }
+
+    /// It returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn write_integer<T: Display>(&mut self, integer: T) -> Result<(), Error> {
+        self.writer.write_byte(BENCODE_BEGIN_INTEGER)?;
+        self.writer.write_str(&integer.to_string())?;
+        self.writer.write_byte(BENCODE_END_INTEGER)?;
+        Ok(())
+    }
+
+    fn write_string(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        self.writer.write_str(&bytes.len().to_string())?;
+        self.writer.write_byte(BENCODE_END_STRING_LENGTH)?;
+        self.write_raw(bytes)
+    }
+
+    fn write_raw(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        self.writer.write_bytes(bytes)?;
+        Ok(())
+    }
+
+    fn unsupported_type(&self, name: &'static str) -> Error {
+        Error::UnsupportedType(name, self.write_context())
+    }
+
+    fn write_context(&self) -> WriteContext {
+        WriteContext {
+            byte: None,
+            pos: self.writer.output_byte_counter(),
+            latest_bytes: self.writer.captured_bytes(),
+        }
+    }
+}
+
+impl<'a, W: Writer> ser::Serializer for &'a mut Serializer<W> {
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = ListSerializer<'a, W>;
+    type SerializeTuple = ListSerializer<'a, W>;
+    type SerializeTupleStruct = ListSerializer<'a, W>;
+    type SerializeTupleVariant = ListSerializer<'a, W>;
+    type SerializeMap = DictSerializer<'a, W>;
+    type SerializeStruct = DictSerializer<'a, W>;
+    type SerializeStructVariant = DictSerializer<'a, W>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), Error> {
+        self.write_integer(u8::from(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), Error> {
+        self.write_integer(v)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), Error> {
+        self.write_integer(v)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), Error> {
+        self.write_integer(v)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), Error> {
+        self.write_integer(v)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<(), Error> {
+        self.write_integer(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), Error> {
+        self.write_integer(v)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), Error> {
+        self.write_integer(v)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), Error> {
+        self.write_integer(v)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), Error> {
+        self.write_integer(v)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<(), Error> {
+        self.write_integer(v)
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<(), Error> {
+        Err(self.unsupported_type("f32"))
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<(), Error> {
+        Err(self.unsupported_type("f64"))
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), Error> {
+        self.write_string(v.encode_utf8(&mut [0; 4]).as_bytes())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), Error> {
+        self.write_string(v.as_bytes())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), Error> {
+        self.write_string(v)
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        if self.none_allowed_at == Some(self.writer.output_byte_counter()) {
+            return Ok(());
+        }
+
+        Err(self.unsupported_type("none"))
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        Err(self.unsupported_type("unit"))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Error> {
+        Err(self.unsupported_type("unit struct"))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), Error> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.writer.write_byte(BENCODE_BEGIN_DICT)?;
+        self.write_string(variant.as_bytes())?;
+        value.serialize(&mut *self)?;
+        self.writer.write_byte(BENCODE_END_LIST_OR_DICT)?;
+        Ok(())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        ListSerializer::begin(self, None)
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        ListSerializer::begin(self, Some(variant))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        DictSerializer::begin(self, None)
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        DictSerializer::begin(self, Some(variant))
+    }
+}
+
+/// It serializes a list. Items are written as soon as they are serialized.
+pub struct ListSerializer<'a, W: Writer> {
+    ser: &'a mut Serializer<W>,
+
+    /// The list is the value of an enum variant, wrapped in a dictionary
+    /// with the variant name as the only key.
+    in_variant: bool,
+}
+
+impl<'a, W: Writer> ListSerializer<'a, W> {
+    fn begin(ser: &'a mut Serializer<W>, variant: Option<&'static str>) -> Result<Self, Error> {
+        if let Some(variant) = variant {
+            ser.writer.write_byte(BENCODE_BEGIN_DICT)?;
+            ser.write_string(variant.as_bytes())?;
+        }
+
+        ser.writer.write_byte(BENCODE_BEGIN_LIST)?;
+
+        Ok(Self {
+            ser,
+            in_variant: variant.is_some(),
+        })
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.ser.writer.write_byte(BENCODE_END_LIST_OR_DICT)?;
+
+        if self.in_variant {
+            self.ser.writer.write_byte(BENCODE_END_LIST_OR_DICT)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<W: Writer> SerializeSeq for ListSerializer<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        ListSerializer::end(self)
+    }
+}
+
+impl<W: Writer> SerializeTuple for ListSerializer<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        ListSerializer::end(self)
+    }
+}
+
+impl<W: Writer> SerializeTupleStruct for ListSerializer<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        ListSerializer::end(self)
+    }
+}
+
+impl<W: Writer> SerializeTupleVariant for ListSerializer<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        ListSerializer::end(self)
+    }
+}
+
+/// It serializes a dictionary.
+///
+/// Fields are kept in memory until the end of the dictionary, because they
+/// have to be written sorted by key.
+pub struct DictSerializer<'a, W: Writer> {
+    ser: &'a mut Serializer<W>,
+
+    /// The dictionary is the value of an enum variant, wrapped in another
+    /// dictionary with the variant name as the only key.
+    in_variant: bool,
+
+    /// The serialized fields: the raw key and the bencoded value.
+    fields: Vec<(Vec<u8>, Vec<u8>)>,
+
+    /// The key of the field whose value has not been serialized yet.
+    key: Option<Vec<u8>>,
+}
+
+impl<'a, W: Writer> DictSerializer<'a, W> {
+    fn begin(ser: &'a mut Serializer<W>, variant: Option<&'static str>) -> Result<Self, Error> {
+        if let Some(variant) = variant {
+            ser.writer.write_byte(BENCODE_BEGIN_DICT)?;
+            ser.write_string(variant.as_bytes())?;
+        }
+
+        Ok(Self {
+            ser,
+            in_variant: variant.is_some(),
+            fields: Vec::new(),
+            key: None,
+        })
+    }
+
+    fn add_field<T: ?Sized + Serialize>(&mut self, key: Vec<u8>, value: &T) -> Result<(), Error> {
+        let writer = FieldWriter::after(&self.ser.writer);
+
+        let mut ser = Serializer {
+            none_allowed_at: Some(writer.output_byte_counter()),
+            writer,
+        };
+
+        value
+            .serialize(&mut ser)
+            .map_err(|err| self.prepend_captured_bytes(err))?;
+
+        // `None` values do not write anything. The field is skipped.
+        if !ser.writer.output.is_empty() {
+            self.fields.push((key, ser.writer.output));
+        }
+
+        Ok(())
+    }
+
+    /// The field writer only captures the bytes of the field. It adds the
+    /// latest bytes of the parent writer to the write context of the errors
+    /// inside the field value, so they are the same as if the value had been
+    /// written right after the parent output.
+    fn prepend_captured_bytes(&self, mut err: Error) -> Error {
+        if let Some(write_context) = err.write_context_mut() {
+            let mut latest_bytes = self.ser.writer.captured_bytes();
+            latest_bytes.append(&mut write_context.latest_bytes);
+
+            let excess = latest_bytes.len().saturating_sub(CAPTURED_BYTES_CAPACITY);
+            latest_bytes.drain(..excess);
+
+            write_context.latest_bytes = latest_bytes;
+        }
+
+        err
+    }
+
+    fn end(mut self) -> Result<(), Error> {
+        self.fields.sort_by(|a, b| a.0.cmp(&b.0));
+
+        if let Some(pair) = self.fields.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+            return Err(Error::DuplicateDictKey(
+                String::from_utf8_lossy(&pair[0].0).to_string(),
+                self.ser.write_context(),
+            ));
+        }
+
+        self.ser.writer.write_byte(BENCODE_BEGIN_DICT)?;
+
+        for (key, value) in &self.fields {
+            self.ser.write_string(key)?;
+            self.ser.write_raw(value)?;
+        }
+
+        self.ser.writer.write_byte(BENCODE_END_LIST_OR_DICT)?;
+
+        if self.in_variant {
+            self.ser.writer.write_byte(BENCODE_END_LIST_OR_DICT)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// It keeps the bencoded value of a dictionary field in memory until the
+/// fields are sorted.
+///
+/// It counts the bytes as if they were written right after the output of the
+/// parent writer, so errors inside the value have the output position of the
+/// whole output. Only the latest bytes of the field are captured. The ones of
+/// the parent writer are added by the [`DictSerializer`] when there is an
+/// error.
+struct FieldWriter {
+    output: Vec<u8>,
+
+    /// The number of bytes written by the parent writer.
+    start_pos: u64,
+}
+
+impl FieldWriter {
+    fn after<W: Writer>(parent: &W) -> Self {
+        Self {
+            output: Vec::new(),
+            start_pos: parent.output_byte_counter(),
+        }
+    }
+}
+
+impl Writer for FieldWriter {
+    fn write_byte(&mut self, byte: u8) -> Result<(), rw::error::Error> {
+        self.output.push(byte);
+        Ok(())
+    }
+
+    fn write_str(&mut self, value: &str) -> Result<(), rw::error::Error> {
+        self.write_bytes(value.as_bytes())
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), rw::error::Error> {
+        self.output.extend_from_slice(bytes);
+        Ok(())
+    }
+
+    fn output_byte_counter(&self) -> u64 {
+        self.start_pos + self.output.len() as u64
+    }
+
+    fn captured_bytes(&self) -> Vec<u8> {
+        let start = self.output.len().saturating_sub(CAPTURED_BYTES_CAPACITY);
+        self.output[start..].to_vec()
+    }
+}
+
+impl<W: Writer> SerializeMap for DictSerializer<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Error> {
+        self.key = Some(key.serialize(KeySerializer { ser: &*self.ser })?);
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self
+            .key
+            .take()
+            .expect("the key should be serialized before the value");
+        self.add_field(key, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        DictSerializer::end(self)
+    }
+}
+
+impl<W: Writer> SerializeStruct for DictSerializer<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.add_field(key.as_bytes().to_vec(), value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        DictSerializer::end(self)
+    }
+}
+
+impl<W: Writer> SerializeStructVariant for DictSerializer<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.add_field(key.as_bytes().to_vec(), value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        DictSerializer::end(self)
+    }
+}
+
+/// It serializes dictionary keys into their raw bytes. Only strings and
+/// bytes are allowed.
+struct KeySerializer<'a, W: Writer> {
+    /// The serializer of the dictionary. It's only used for the error
+    /// context.
+    ser: &'a Serializer<W>,
+}
+
+impl<W: Writer> KeySerializer<'_, W> {
+    fn expected_string(&self) -> Error {
+        Error::ExpectedStringForDictKey(self.ser.write_context())
+    }
+}
+
+impl<W: Writer> ser::Serializer for KeySerializer<'_, W> {
+    type Ok = Vec<u8>;
+    type Error = Error;
+
+    type SerializeSeq = Impossible<Vec<u8>, Error>;
+    type SerializeTuple = Impossible<Vec<u8>, Error>;
+    type SerializeTupleStruct = Impossible<Vec<u8>, Error>;
+    type SerializeTupleVariant = Impossible<Vec<u8>, Error>;
+    type SerializeMap = Impossible<Vec<u8>, Error>;
+    type SerializeStruct = Impossible<Vec<u8>, Error>;
+    type SerializeStructVariant = Impossible<Vec<u8>, Error>;
+
+    fn serialize_str(self, v: &str) -> Result<Vec<u8>, Error> {
+        Ok(v.as_bytes().to_vec())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Vec<u8>, Error> {
+        Ok(v.to_vec())
+    }
+
+    fn serialize_char(self, v: char) -> Result<Vec<u8>, Error> {
+        Ok(v.encode_utf8(&mut [0; 4]).as_bytes().to_vec())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Vec<u8>, Error> {
+        Ok(variant.as_bytes().to_vec())
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Vec<u8>, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_bool(self, _v: bool) -> Result<Vec<u8>, Error> {
+        Err(self.expected_string())
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<Vec<u8>, Error> {
+        Err(self.expected_string())
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<Vec<u8>, Error> {
+        Err(self.expected_string())
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<Vec<u8>, Error> {
+        Err(self.expected_string())
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<Vec<u8>, Error> {
+        Err(self.expected_string())
+    }
+
+    fn serialize_u8(self, _v: u8) -> Result<Vec<u8>, Error> {
+        Err(self.expected_string())
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<Vec<u8>, Error> {
+        Err(self.expected_string())
+    }
+
+    fn serialize_u32(self, _v: u32) -> Result<Vec<u8>, Error> {
+        Err(self.expected_string())
+    }
+
+    fn serialize_u64(self, _v: u64) -> Result<Vec<u8>, Error> {
+        Err(self.expected_string())
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<Vec<u8>, Error> {
+        Err(self.expected_string())
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<Vec<u8>, Error> {
+        Err(self.expected_string())
+    }
+
+    fn serialize_none(self) -> Result<Vec<u8>, Error> {
+        Err(self.expected_string())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, _value: &T) -> Result<Vec<u8>, Error> {
+        Err(self.expected_string())
+    }
+
+    fn serialize_unit(self) -> Result<Vec<u8>, Error> {
+        Err(self.expected_string())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Vec<u8>, Error> {
+        Err(self.expected_string())
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Vec<u8>, Error> {
+        Err(self.expected_string())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Err(self.expected_string())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Error> {
+        Err(self.expected_string())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        Err(self.expected_string())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(self.expected_string())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Err(self.expected_string())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Error> {
+        Err(self.expected_string())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(self.expected_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct File {
+        path: Vec<String>,
+        length: u64,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Info {
+        name: String,
+        #[serde(rename = "piece length")]
+        piece_length: u64,
+        #[serde(with = "serde_bytes")]
+        pieces: Vec<u8>,
+        private: Option<bool>,
+        files: Option<Vec<File>>,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Torrent {
+        info: Info,
+        announce: String,
+    }
+
+    mod it_should_serialize {
+        use std::collections::HashMap;
+
+        use crate::ser::to_vec;
+
+        #[test]
+        fn an_integer() {
+            assert_eq!(to_vec(&-42).unwrap(), b"i-42e");
+            assert_eq!(to_vec(&0u8).unwrap(), b"i0e");
+            assert_eq!(to_vec(&u64::MAX).unwrap(), b"i18446744073709551615e");
+        }
+
+        #[test]
+        fn a_boolean_as_the_integers_zero_and_one() {
+            assert_eq!(to_vec(&false).unwrap(), b"i0e");
+            assert_eq!(to_vec(&true).unwrap(), b"i1e");
+        }
+
+        #[test]
+        fn a_string() {
+            assert_eq!(to_vec("spam").unwrap(), b"4:spam");
+            assert_eq!(to_vec(&'\u{f1}').unwrap(), b"2:\xC3\xB1");
+        }
+
+        #[test]
+        fn bytes_as_a_string() {
+            assert_eq!(
+                to_vec(&serde_bytes::Bytes::new(b"\xFF\xFE")).unwrap(),
+                b"2:\xFF\xFE"
+            );
+        }
+
+        #[test]
+        fn a_list() {
+            assert_eq!(to_vec(&vec!["alice", "bob"]).unwrap(), b"l5:alice3:bobe");
+            assert_eq!(to_vec(&("alice", 42)).unwrap(), b"l5:alicei42ee");
+        }
+
+        #[test]
+        fn a_map_with_the_keys_sorted_by_their_raw_bytes() {
+            let mut map = HashMap::new();
+            map.insert("b", 2);
+            map.insert("a", 1);
+            map.insert("B", 3);
+
+            assert_eq!(to_vec(&map).unwrap(), b"d1:Bi3e1:ai1e1:bi2ee");
+        }
+
+        #[test]
+        fn a_struct_with_the_fields_sorted_by_their_raw_bytes_and_without_none_values() {
+            use crate::ser::tests::{File, Info, Torrent};
+
+            let torrent = Torrent {
+                info: Info {
+                    name: "foo".to_string(),
+                    piece_length: 16384,
+                    pieces: b"\xFF\xFE".to_vec(),
+                    private: None,
+                    files: Some(vec![File {
+                        path: vec!["a".to_string(), "b".to_string()],
+                        length: 10,
+                    }]),
+                },
+                announce: "udp://foo".to_string(),
+            };
+
+            assert_eq!(
+                to_vec(&torrent).unwrap(),
+                b"d8:announce9:udp://foo4:infod5:filesld6:lengthi10e4:pathl1:a1:beee4:name3:foo12:piece lengthi16384e6:pieces2:\xFF\xFEee"
+            );
+        }
+
+        #[test]
+        fn a_map_without_none_values() {
+            let mut map = HashMap::new();
+            map.insert("a", Some(1));
+            map.insert("b", None);
+
+            assert_eq!(to_vec(&map).unwrap(), b"d1:ai1ee");
+        }
+
+        #[test]
+        fn a_struct_with_flattened_fields() {
+            use std::collections::BTreeMap;
+
+            #[derive(serde::Serialize)]
+            struct Response {
+                interval: i64,
+                #[serde(flatten)]
+                extra: BTreeMap<String, i64>,
+            }
+
+            let mut extra = BTreeMap::new();
+            extra.insert("complete".to_string(), 5);
+
+            assert_eq!(
+                to_vec(&Response {
+                    interval: 1800,
+                    extra
+                })
+                .unwrap(),
+                b"d8:completei5e8:intervali1800ee"
+            );
+        }
+
+        #[test]
+        fn into_a_writer() {
+            let mut output = Vec::new();
+
+            crate::ser::to_writer(&mut output, "spam").unwrap();
+
+            assert_eq!(output, b"4:spam");
+        }
+    }
+
+    mod it_should_serialize_enums {
+        use serde::Serialize;
+
+        use crate::ser::to_vec;
+
+        #[derive(Serialize)]
+        #[serde(rename_all = "lowercase")]
+        enum Message {
+            Ping,
+            Error(i64),
+            Point(i64, i64),
+            Announce { port: u16, info_hash: String },
+        }
+
+        #[test]
+        fn with_unit_variants_as_strings() {
+            assert_eq!(to_vec(&Message::Ping).unwrap(), b"4:ping");
+        }
+
+        #[test]
+        fn with_newtype_variants_as_dictionaries() {
+            assert_eq!(to_vec(&Message::Error(201)).unwrap(), b"d5:errori201ee");
+        }
+
+        #[test]
+        fn with_tuple_variants_as_dictionaries() {
+            assert_eq!(to_vec(&Message::Point(1, 2)).unwrap(), b"d5:pointli1ei2eee");
+        }
+
+        #[test]
+        fn with_struct_variants_as_dictionaries_with_sorted_keys() {
+            assert_eq!(
+                to_vec(&Message::Announce {
+                    port: 6881,
+                    info_hash: "abc".to_string()
+                })
+                .unwrap(),
+                b"d8:announced9:info_hash3:abc4:porti6881eee"
+            );
+        }
+    }
+
+    #[test]
+    fn it_should_round_trip_with_the_deserializer() {
+        use crate::{de::from_slice, ser::to_vec};
+
+        let input = b"d8:announce9:udp://foo4:infod4:name3:foo12:piece lengthi16384e6:pieces2:\xFF\xFE7:privatei1eee";
+
+        let torrent = from_slice::<Torrent>(input).unwrap();
+
+        assert_eq!(to_vec(&torrent).unwrap(), input);
+    }
+
+    mod it_should_fail {
+        use std::collections::BTreeMap;
+
+        use crate::ser::{error::Error, to_vec};
+
+        #[test]
+        fn when_serializing_a_float() {
+            assert!(matches!(
+                to_vec(&1.5),
+                Err(Error::UnsupportedType("f64", _))
+            ));
+        }
+
+        #[test]
+        fn when_serializing_a_unit() {
+            assert!(matches!(
+                to_vec(&()),
+                Err(Error::UnsupportedType("unit", _))
+            ));
+        }
+
+        #[test]
+        fn when_serializing_a_none_list_item() {
+            assert!(matches!(
+                to_vec(&vec![Some(1), None, Some(2)]),
+                Err(Error::UnsupportedType("none", _))
+            ));
+        }
+
+        #[test]
+        fn when_serializing_a_none_tuple_element() {
+            assert!(matches!(
+                to_vec(&(None::<i64>, 7)),
+                Err(Error::UnsupportedType("none", _))
+            ));
+        }
+
+        #[test]
+        fn when_serializing_a_top_level_none() {
+            assert!(matches!(
+                to_vec(&None::<i64>),
+                Err(Error::UnsupportedType("none", _))
+            ));
+        }
+
+        #[test]
+        fn when_serializing_a_none_list_item_in_a_dictionary_field() {
+            let mut map = BTreeMap::new();
+            map.insert("list", vec![None, Some(1)]);
+
+            assert!(matches!(
+                to_vec(&map),
+                Err(Error::UnsupportedType("none", _))
+            ));
+        }
+
+        #[test]
+        fn when_a_dictionary_key_is_not_a_string() {
+            let mut map = BTreeMap::new();
+            map.insert(1, 2);
+
+            assert!(matches!(
+                to_vec(&map),
+                Err(Error::ExpectedStringForDictKey { .. })
+            ));
+        }
+
+        #[test]
+        fn when_a_dictionary_has_duplicate_keys() {
+            #[derive(serde::Serialize)]
+            struct Response {
+                interval: i64,
+                #[serde(flatten)]
+                extra: BTreeMap<String, i64>,
+            }
+
+            let mut extra = BTreeMap::new();
+            extra.insert("interval".to_string(), 5);
+
+            assert!(matches!(
+                to_vec(&Response {
+                    interval: 1800,
+                    extra
+                }),
+                Err(Error::DuplicateDictKey { .. })
+            ));
+        }
+
+        #[test]
+        fn including_the_write_context() {
+            let result = to_vec(&("spam", 1.5));
+
+            let Err(Error::UnsupportedType(_, write_context)) = result else {
+                panic!("expected an unsupported type error, got: {result:?}");
+            };
+
+            assert_eq!(write_context.pos, 7);
+            assert_eq!(write_context.latest_bytes, b"l4:spam");
+        }
+
+        #[test]
+        fn including_the_write_context_inside_a_dictionary_field() {
+            let mut map = BTreeMap::new();
+            map.insert("a", ("x", 1.5));
+
+            let result = to_vec(&("spam", map));
+
+            let Err(Error::UnsupportedType(_, write_context)) = result else {
+                panic!("expected an unsupported type error, got: {result:?}");
+            };
+
+            assert_eq!(write_context.pos, 11);
+            assert_eq!(write_context.latest_bytes, b"l4:spaml1:x");
+        }
+
+        #[test]
+        fn including_the_latest_bytes_of_nested_dictionary_fields() {
+            let mut inner = BTreeMap::new();
+            inner.insert("b", ("x".repeat(2000), 1.5));
+            let mut outer = BTreeMap::new();
+            outer.insert("a", inner);
+
+            let result = to_vec(&("spam", outer));
+
+            let Err(Error::UnsupportedType(_, write_context)) = result else {
+                panic!("expected an unsupported type error, got: {result:?}");
+            };
+
+            assert_eq!(write_context.pos, 2013);
+            assert_eq!(write_context.latest_bytes.len(), 1024);
+            assert_eq!(write_context.latest_bytes, vec![b'x'; 1024]);
+        }
+
+        #[test]
+        fn including_the_parent_bytes_in_the_latest_bytes_of_nested_dictionary_fields() {
+            let mut inner = BTreeMap::new();
+            inner.insert("b", ("x", 1.5));
+            let mut outer = BTreeMap::new();
+            outer.insert("a", inner);
+
+            let result = to_vec(&("spam", outer));
+
+            let Err(Error::UnsupportedType(_, write_context)) = result else {
+                panic!("expected an unsupported type error, got: {result:?}");
+            };
+
+            assert_eq!(write_context.latest_bytes, b"l4:spaml1:x");
+        }
+    }
+}
@@ -0,0 +1,28 @@
+//! Torrent metainfo errors.
+use std::io;
+
+use thiserror::Error;
+
+use crate::parsers;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Parser error: {0}")]
+    Parser(#[from] parsers::error::Error),
+
+    #[error("The metainfo is not a dictionary")]
+    NotADictionary,
+
+    /// A required field does not exist. It contains the path of the field,
+    /// for example, `info.name`.
+    #[error("Missing field `{0}`")]
+    MissingField(String),
+
+    /// A field does not have the expected type or value. It contains the path
+    /// of the field and a description of the expected value.
+    #[error("Invalid field `{0}`, expected {1}")]
+    InvalidField(String, &'static str),
+}
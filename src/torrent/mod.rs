@@ -0,0 +1,826 @@
+//! Torrent metainfo (`.torrent` files).
+//!
+//! It parses the metainfo described in
+//! [BEP 3](https://www.bittorrent.org/beps/bep_0003.html) into typed fields and
+//! calculates the info-hash.
+//!
+//! Version 2 and hybrid torrents, described in
+//! [BEP 52](https://www.bittorrent.org/beps/bep_0052.html), are also supported.
+//! They contain the `file tree` of the torrent and they have a SHA-256
+//! info-hash.
+//!
+//! ```rust
+//! use torrust_bencode2json::torrent::Metainfo;
+//!
+//! let metainfo = Metainfo::from_slice(
+//!     b"d8:announce23:http://tracker/announce4:infod6:lengthi5e4:name8:file.txt12:piece lengthi16384e6:pieces20:aaaaaaaaaaaaaaaaaaaaee",
+//! )
+//! .unwrap();
+//!
+//! assert_eq!(metainfo.announce.as_deref(), Some("http://tracker/announce"));
+//! assert_eq!(metainfo.info.name, "file.txt");
+//! assert_eq!(
+//!     metainfo.info_hash.to_string(),
+//!     "dda4e89337cc17e6efcefe558f25d4334ad731c9"
+//! );
+//! ```
+pub mod error;
+
+use std::{collections::BTreeMap, fmt, io::Read};
+
+use sha1::{Digest, Sha1};
+use sha2::Sha256;
+
+use crate::value::Value;
+
+use error::Error;
+
+/// The metainfo of a torrent.
+///
+/// Text fields that are not valid UTF-8 are converted replacing the invalid
+/// bytes with `U+FFFD`. The info-hash is always calculated from the original
+/// bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Metainfo {
+    /// The URL of the tracker.
+    pub announce: Option<String>,
+
+    /// The tiers of tracker URLs, as described in
+    /// [BEP 12](https://www.bittorrent.org/beps/bep_0012.html).
+    pub announce_list: Option<Vec<Vec<String>>>,
+
+    /// The creation time, in seconds since the Unix epoch.
+    pub creation_date: Option<i64>,
+
+    pub comment: Option<String>,
+
+    pub info: Info,
+
+    /// The SHA-1 hash of the bencoded `info` dictionary, exactly as it is in
+    /// the input.
+    pub info_hash: InfoHash,
+
+    /// The SHA-256 hash of the bencoded `info` dictionary, exactly as it is
+    /// in the input. It's only calculated for v2 and hybrid torrents.
+    pub info_hash_v2: Option<InfoHashV2>,
+}
+
+/// The protocol versions supported by a torrent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Version {
+    V1,
+    V2,
+
+    /// A torrent that can be downloaded with both versions.
+    Hybrid,
+}
+
+/// The `info` dictionary of the metainfo.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Info {
+    /// The suggested name of the file, or the directory for torrents with
+    /// multiple files.
+    pub name: String,
+
+    /// The number of bytes in each piece.
+    pub piece_length: i64,
+
+    /// The `meta version` field. It's `2` for v2 and hybrid torrents.
+    pub meta_version: Option<i64>,
+
+    /// The concatenated SHA-1 hashes of the pieces, 20 bytes each. v2
+    /// torrents do not contain them.
+    pub pieces: Option<Vec<u8>>,
+
+    /// Whether peers can only be obtained from the trackers, as described in
+    /// [BEP 27](https://www.bittorrent.org/beps/bep_0027.html).
+    pub private: bool,
+
+    /// The v1 list of files. Torrents with a single file contain one file
+    /// whose path is the name of the torrent. v2 torrents do not contain it.
+    pub files: Vec<File>,
+
+    /// The files in the v2 `file tree`, sorted by path. Only v2 and hybrid
+    /// torrents contain it.
+    pub file_tree: Option<Vec<File>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct File {
+    /// The number of bytes of the file.
+    pub length: i64,
+
+    /// The path of the file, one item per directory and the file name last.
+    pub path: Vec<String>,
+
+    /// The root of the merkle tree of the file, in the v2 `file tree`. Empty
+    /// files do not have it.
+    pub pieces_root: Option<[u8; 32]>,
+}
+
+/// The v1 info-hash: the SHA-1 hash of the bencoded `info` dictionary.
+///
+/// It's displayed in lowercase hexadecimal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InfoHash(pub [u8; 20]);
+
+impl fmt::Display for InfoHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", hex::encode(self.0))
+    }
+}
+
+/// The v2 info-hash: the SHA-256 hash of the bencoded `info` dictionary.
+///
+/// It's displayed in lowercase hexadecimal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InfoHashV2(pub [u8; 32]);
+
+impl InfoHashV2 {
+    /// It returns the first 20 bytes of the hash, which are used where the
+    /// protocol only has room for a v1 info-hash, like in tracker requests.
+    #[must_use]
+    pub fn truncated(&self) -> InfoHash {
+        let mut truncated = [0; 20];
+        truncated.copy_from_slice(&self.0[..20]);
+        InfoHash(truncated)
+    }
+}
+
+impl fmt::Display for InfoHashV2 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", hex::encode(self.0))
+    }
+}
+
+impl Metainfo {
+    /// It parses the metainfo from the input.
+    ///
+    /// The whole input is read into memory before parsing it, because the
+    /// info-hash is calculated from the raw bytes of the `info` dictionary.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if it can't read from the input or the input is
+    /// not a valid metainfo.
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Self, Error> {
+        let mut input = Vec::new();
+
+        reader.read_to_end(&mut input)?;
+
+        Self::from_slice(&input)
+    }
+
+    /// It parses the metainfo from a slice of bytes.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the input is not valid bencode or it does not
+    /// contain the required metainfo fields.
+    pub fn from_slice(input: &[u8]) -> Result<Self, Error> {
+        let (value, info_span) = Value::from_slice_with_field_span(input, b"info")?;
+
+        let metainfo = Fields::new(&value, String::new()).ok_or(Error::NotADictionary)?;
+
+        let info = metainfo
+            .dict("info")?
+            .ok_or_else(|| Error::MissingField("info".to_string()))?;
+
+        let info_span = info_span.ok_or_else(|| Error::MissingField("info".to_string()))?;
+
+        let info = Info::from_fields(&info)?;
+
+        let info_hash_v2 = match info.version() {
+            Version::V1 => None,
+            Version::V2 | Version::Hybrid => {
+                Some(InfoHashV2(Sha256::digest(&input[info_span.clone()]).into()))
+            }
+        };
+
+        Ok(Self {
+            announce: metainfo.string("announce")?,
+            announce_list: metainfo.announce_list()?,
+            creation_date: metainfo.integer("creation date")?,
+            comment: metainfo.string("comment")?,
+            info,
+            info_hash: InfoHash(Sha1::digest(&input[info_span]).into()),
+            info_hash_v2,
+        })
+    }
+}
+
+impl Info {
+    fn from_fields(info: &Fields<'_>) -> Result<Self, Error> {
+        let name = info.required_string("name")?;
+
+        let piece_length = info.required_integer("piece length")?;
+
+        if piece_length <= 0 {
+            return Err(Error::InvalidField(
+                info.path("piece length"),
+                "a positive integer",
+            ));
+        }
+
+        let meta_version = info.integer("meta version")?;
+
+        let file_tree = match info.dict("file tree")? {
+            Some(file_tree) => {
+                let mut files = vec![];
+                file_tree.add_file_tree_files(&mut vec![], &mut files)?;
+                Some(files)
+            }
+            None if meta_version == Some(2) => {
+                return Err(Error::MissingField(info.path("file tree")));
+            }
+            None => None,
+        };
+
+        let pieces = info.bytes("pieces")?;
+
+        if let Some(pieces) = pieces {
+            if pieces.len() % 20 != 0 {
+                return Err(Error::InvalidField(
+                    info.path("pieces"),
+                    "a multiple of 20 bytes",
+                ));
+            }
+        }
+
+        // The v1 fields are only optional in v2 torrents
+        let is_v2_only = meta_version == Some(2) && pieces.is_none();
+
+        let files = match info.list("files")? {
+            Some(files) => files
+                .iter()
+                .enumerate()
+                .map(|(index, file)| {
+                    File::from_value(file, format!("{}[{index}]", info.path("files")))
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            None if is_v2_only => vec![],
+            None => vec![File {
+                length: info.required_length("length")?,
+                path: vec![name.clone()],
+                pieces_root: None,
+            }],
+        };
+
+        if pieces.is_none() && !is_v2_only {
+            return Err(Error::MissingField(info.path("pieces")));
+        }
+
+        Ok(Self {
+            name,
+            piece_length,
+            meta_version,
+            pieces: pieces.map(<[u8]>::to_vec),
+            private: info.integer("private")? == Some(1),
+            files,
+            file_tree,
+        })
+    }
+
+    /// It returns the protocol versions supported by the torrent.
+    #[must_use]
+    pub fn version(&self) -> Version {
+        match (self.meta_version, &self.pieces) {
+            (Some(2), Some(_)) => Version::Hybrid,
+            (Some(2), None) => Version::V2,
+            _ => Version::V1,
+        }
+    }
+
+    /// It returns the number of bytes of all the files.
+    ///
+    /// The v2 `file tree` is used when it exists, because the v1 list of
+    /// files in hybrid torrents also contains padding files.
+    #[must_use]
+    pub fn total_length(&self) -> i64 {
+        self.file_tree
+            .as_ref()
+            .unwrap_or(&self.files)
+            .iter()
+            .map(|file| file.length)
+            .sum()
+    }
+}
+
+impl File {
+    fn from_value(value: &Value, path: String) -> Result<Self, Error> {
+        let file =
+            Fields::new(value, path.clone()).ok_or(Error::InvalidField(path, "a dictionary"))?;
+
+        let path = file
+            .list("path")?
+            .ok_or_else(|| Error::MissingField(file.path("path")))?
+            .iter()
+            .enumerate()
+            .map(|(index, item)| {
+                item.as_bytes().map(lossy_string).ok_or_else(|| {
+                    Error::InvalidField(format!("{}[{index}]", file.path("path")), "a string")
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            length: file.required_length("length")?,
+            path,
+            pieces_root: None,
+        })
+    }
+}
+
+/// The fields of a dictionary with its path in the metainfo, used in errors.
+struct Fields<'a> {
+    fields: &'a BTreeMap<Vec<u8>, Value>,
+    path: String,
+}
+
+impl<'a> Fields<'a> {
+    fn new(value: &'a Value, path: String) -> Option<Self> {
+        value.as_dict().map(|fields| Self { fields, path })
+    }
+
+    /// It returns the path of a field in this dictionary.
+    fn path(&self, key: &str) -> String {
+        if self.path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{key}", self.path)
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<&'a Value> {
+        self.fields.get(key.as_bytes())
+    }
+
+    fn integer(&self, key: &str) -> Result<Option<i64>, Error> {
+        self.get(key)
+            .map(|value| {
+                value
+                    .as_integer()
+                    .ok_or_else(|| Error::InvalidField(self.path(key), "an integer"))
+            })
+            .transpose()
+    }
+
+    fn bytes(&self, key: &str) -> Result<Option<&'a [u8]>, Error> {
+        self.get(key)
+            .map(|value| {
+                value
+                    .as_bytes()
+                    .ok_or_else(|| Error::InvalidField(self.path(key), "a string"))
+            })
+            .transpose()
+    }
+
+    fn string(&self, key: &str) -> Result<Option<String>, Error> {
+        Ok(self.bytes(key)?.map(lossy_string))
+    }
+
+    fn list(&self, key: &str) -> Result<Option<&'a [Value]>, Error> {
+        self.get(key)
+            .map(|value| {
+                value
+                    .as_list()
+                    .ok_or_else(|| Error::InvalidField(self.path(key), "a list"))
+            })
+            .transpose()
+    }
+
+    fn dict(&self, key: &str) -> Result<Option<Fields<'a>>, Error> {
+        self.get(key)
+            .map(|value| {
+                Fields::new(value, self.path(key))
+                    .ok_or_else(|| Error::InvalidField(self.path(key), "a dictionary"))
+            })
+            .transpose()
+    }
+
+    fn required_integer(&self, key: &str) -> Result<i64, Error> {
+        self.integer(key)?
+            .ok_or_else(|| Error::MissingField(self.path(key)))
+    }
+
+    fn required_string(&self, key: &str) -> Result<String, Error> {
+        self.string(key)?
+            .ok_or_else(|| Error::MissingField(self.path(key)))
+    }
+
+    fn required_length(&self, key: &str) -> Result<i64, Error> {
+        let length = self.required_integer(key)?;
+
+        if length < 0 {
+            return Err(Error::InvalidField(
+                self.path(key),
+                "a non-negative integer",
+            ));
+        }
+
+        Ok(length)
+    }
+
+    /// It adds the files in a directory of the v2 `file tree`, walking the
+    /// subdirectories. Keys are path components, and the empty key marks a
+    /// file.
+    fn add_file_tree_files(
+        &self,
+        directory: &mut Vec<String>,
+        files: &mut Vec<File>,
+    ) -> Result<(), Error> {
+        for (key, value) in self.fields {
+            let key_path = self.path(&lossy_string(key));
+
+            let entry = Fields::new(value, key_path.clone())
+                .ok_or(Error::InvalidField(key_path, "a dictionary"))?;
+
+            if key.is_empty() {
+                if directory.is_empty() {
+                    return Err(Error::InvalidField(entry.path, "a file with a name"));
+                }
+
+                files.push(entry.file_tree_file(directory.clone())?);
+                continue;
+            }
+
+            directory.push(lossy_string(key));
+            entry.add_file_tree_files(directory, files)?;
+            directory.pop();
+        }
+
+        Ok(())
+    }
+
+    /// It parses the properties of a file in the v2 `file tree`.
+    fn file_tree_file(&self, path: Vec<String>) -> Result<File, Error> {
+        let length = self.required_length("length")?;
+
+        let pieces_root = self
+            .bytes("pieces root")?
+            .map(|pieces_root| {
+                <[u8; 32]>::try_from(pieces_root)
+                    .map_err(|_| Error::InvalidField(self.path("pieces root"), "32 bytes"))
+            })
+            .transpose()?;
+
+        if length > 0 && pieces_root.is_none() {
+            return Err(Error::MissingField(self.path("pieces root")));
+        }
+
+        Ok(File {
+            length,
+            path,
+            pieces_root,
+        })
+    }
+
+    fn announce_list(&self) -> Result<Option<Vec<Vec<String>>>, Error> {
+        let Some(tiers) = self.list("announce-list")? else {
+            return Ok(None);
+        };
+
+        tiers
+            .iter()
+            .enumerate()
+            .map(|(tier_index, tier)| {
+                let tier_path = format!("{}[{tier_index}]", self.path("announce-list"));
+
+                tier.as_list()
+                    .ok_or_else(|| Error::InvalidField(tier_path.clone(), "a list"))?
+                    .iter()
+                    .enumerate()
+                    .map(|(index, url)| {
+                        url.as_bytes().map(lossy_string).ok_or_else(|| {
+                            Error::InvalidField(format!("{tier_path}[{index}]"), "a string")
+                        })
+                    })
+                    .collect()
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Some)
+    }
+}
+
+fn lossy_string(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    const SINGLE_FILE_INFO: &[u8] =
+        b"d6:lengthi5e4:name8:file.txt12:piece lengthi16384e6:pieces20:aaaaaaaaaaaaaaaaaaaae";
+
+    fn metainfo_with_info(info: &[u8]) -> Vec<u8> {
+        let mut metainfo = b"d8:announce23:http://tracker/announce4:info".to_vec();
+        metainfo.extend_from_slice(info);
+        metainfo.push(b'e');
+        metainfo
+    }
+
+    mod it_should_parse {
+        use crate::torrent::{
+            tests::{metainfo_with_info, SINGLE_FILE_INFO},
+            File, Metainfo,
+        };
+
+        #[test]
+        fn a_torrent_with_a_single_file() {
+            let metainfo = Metainfo::from_slice(&metainfo_with_info(SINGLE_FILE_INFO)).unwrap();
+
+            assert_eq!(
+                metainfo.announce.as_deref(),
+                Some("http://tracker/announce")
+            );
+            assert_eq!(metainfo.info.name, "file.txt");
+            assert_eq!(metainfo.info.piece_length, 16384);
+            assert_eq!(metainfo.info.pieces, Some(b"a".repeat(20)));
+            assert!(!metainfo.info.private);
+            assert_eq!(
+                metainfo.info.files,
+                vec![File {
+                    length: 5,
+                    path: vec!["file.txt".to_string()],
+                    pieces_root: None,
+                }]
+            );
+        }
+
+        #[test]
+        fn a_torrent_with_multiple_files() {
+            let metainfo = Metainfo::from_slice(&metainfo_with_info(
+                b"d5:filesld6:lengthi3e4:pathl3:dir5:a.txteed6:lengthi4e4:pathl5:b.txteee4:name4:root12:piece lengthi16384e6:pieces0:7:privatei1ee",
+            ))
+            .unwrap();
+
+            assert_eq!(
+                metainfo.info.files,
+                vec![
+                    File {
+                        length: 3,
+                        path: vec!["dir".to_string(), "a.txt".to_string()],
+                        pieces_root: None,
+                    },
+                    File {
+                        length: 4,
+                        path: vec!["b.txt".to_string()],
+                        pieces_root: None,
+                    }
+                ]
+            );
+            assert_eq!(metainfo.info.total_length(), 7);
+            assert!(metainfo.info.private);
+        }
+
+        #[test]
+        fn the_optional_fields() {
+            let mut input = b"d13:announce-listll3:udp4:httpel5:otheree7:comment5:hello13:creation datei1700000000e4:info".to_vec();
+            input.extend_from_slice(SINGLE_FILE_INFO);
+            input.push(b'e');
+
+            let metainfo = Metainfo::from_slice(&input).unwrap();
+
+            assert_eq!(metainfo.announce, None);
+            assert_eq!(
+                metainfo.announce_list,
+                Some(vec![
+                    vec!["udp".to_string(), "http".to_string()],
+                    vec!["other".to_string()]
+                ])
+            );
+            assert_eq!(metainfo.comment.as_deref(), Some("hello"));
+            assert_eq!(metainfo.creation_date, Some(1_700_000_000));
+        }
+
+        #[test]
+        fn text_fields_that_are_not_valid_utf8() {
+            let metainfo = Metainfo::from_slice(&metainfo_with_info(
+                b"d6:lengthi5e4:name2:\xFFa12:piece lengthi16384e6:pieces0:e",
+            ))
+            .unwrap();
+
+            assert_eq!(metainfo.info.name, "\u{FFFD}a");
+        }
+    }
+
+    mod the_info_hash {
+        use sha1::{Digest, Sha1};
+
+        use crate::torrent::{
+            tests::{metainfo_with_info, SINGLE_FILE_INFO},
+            Metainfo,
+        };
+
+        #[test]
+        fn should_be_the_sha1_hash_of_the_info_dictionary() {
+            let metainfo = Metainfo::from_slice(&metainfo_with_info(SINGLE_FILE_INFO)).unwrap();
+
+            assert_eq!(
+                metainfo.info_hash.0,
+                <[u8; 20]>::from(Sha1::digest(SINGLE_FILE_INFO))
+            );
+        }
+
+        #[test]
+        fn should_be_displayed_in_hexadecimal() {
+            let metainfo = Metainfo::from_slice(&metainfo_with_info(SINGLE_FILE_INFO)).unwrap();
+
+            assert_eq!(
+                metainfo.info_hash.to_string(),
+                "dda4e89337cc17e6efcefe558f25d4334ad731c9"
+            );
+        }
+
+        #[test]
+        fn should_use_the_original_bytes_even_if_they_are_not_canonical() {
+            // Unsorted keys: encoding the parsed value again would sort them
+            let info = b"d4:name8:file.txt6:lengthi5e12:piece lengthi16384e6:pieces0:e";
+
+            let metainfo = Metainfo::from_slice(&metainfo_with_info(info)).unwrap();
+
+            assert_eq!(metainfo.info_hash.0, <[u8; 20]>::from(Sha1::digest(info)));
+        }
+
+        #[test]
+        fn should_use_the_last_info_field_when_it_is_duplicated() {
+            let mut input = b"d4:infod4:name5:firste4:info".to_vec();
+            input.extend_from_slice(SINGLE_FILE_INFO);
+            input.push(b'e');
+
+            let metainfo = Metainfo::from_slice(&input).unwrap();
+
+            assert_eq!(metainfo.info.name, "file.txt");
+            assert_eq!(
+                metainfo.info_hash.0,
+                <[u8; 20]>::from(Sha1::digest(SINGLE_FILE_INFO))
+            );
+        }
+    }
+
+    mod for_v2_torrents {
+        use sha2::{Digest, Sha256};
+
+        use crate::torrent::{tests::metainfo_with_info, File, Metainfo, Version};
+
+        fn v2_info(extra_fields: &[u8]) -> Vec<u8> {
+            let mut info = b"d9:file treed4:dir1d5:a.txtd0:d6:lengthi3e11:pieces root32:".to_vec();
+            info.extend_from_slice(&[0xAB; 32]);
+            info.extend_from_slice(
+                b"eee5:emptyd0:d6:lengthi0eeee12:meta versioni2e4:name4:root12:piece lengthi16384e",
+            );
+            info.extend_from_slice(extra_fields);
+            info.push(b'e');
+            info
+        }
+
+        #[test]
+        fn it_should_walk_the_file_tree() {
+            let metainfo = Metainfo::from_slice(&metainfo_with_info(&v2_info(b""))).unwrap();
+
+            assert_eq!(
+                metainfo.info.file_tree,
+                Some(vec![
+                    File {
+                        length: 3,
+                        path: vec!["dir1".to_string(), "a.txt".to_string()],
+                        pieces_root: Some([0xAB; 32]),
+                    },
+                    File {
+                        length: 0,
+                        path: vec!["empty".to_string()],
+                        pieces_root: None,
+                    }
+                ])
+            );
+            assert_eq!(metainfo.info.total_length(), 3);
+        }
+
+        #[test]
+        fn it_should_recognize_v2_only_torrents() {
+            let metainfo = Metainfo::from_slice(&metainfo_with_info(&v2_info(b""))).unwrap();
+
+            assert_eq!(metainfo.info.version(), Version::V2);
+            assert_eq!(metainfo.info.pieces, None);
+            assert!(metainfo.info.files.is_empty());
+        }
+
+        #[test]
+        fn it_should_recognize_hybrid_torrents() {
+            let metainfo = Metainfo::from_slice(&metainfo_with_info(&v2_info(
+                b"6:pieces20:aaaaaaaaaaaaaaaaaaaa",
+            )))
+            .unwrap_err();
+
+            // The v1 fields are required in hybrid torrents
+            assert!(metainfo.to_string().contains("info.length"));
+
+            let metainfo = Metainfo::from_slice(&metainfo_with_info(&v2_info(
+                b"6:lengthi3e6:pieces20:aaaaaaaaaaaaaaaaaaaa",
+            )))
+            .unwrap();
+
+            assert_eq!(metainfo.info.version(), Version::Hybrid);
+        }
+
+        #[test]
+        fn it_should_calculate_the_v2_info_hash() {
+            let info = v2_info(b"");
+
+            let metainfo = Metainfo::from_slice(&metainfo_with_info(&info)).unwrap();
+
+            let expected: [u8; 32] = Sha256::digest(&info).into();
+
+            assert_eq!(metainfo.info_hash_v2.unwrap().0, expected);
+            assert_eq!(
+                metainfo.info_hash_v2.unwrap().to_string(),
+                hex::encode(expected)
+            );
+            assert_eq!(metainfo.info_hash_v2.unwrap().truncated().0, expected[..20]);
+        }
+
+        #[test]
+        fn it_should_not_calculate_the_v2_info_hash_for_v1_torrents() {
+            let metainfo =
+                Metainfo::from_slice(&metainfo_with_info(super::SINGLE_FILE_INFO)).unwrap();
+
+            assert_eq!(metainfo.info.version(), Version::V1);
+            assert_eq!(metainfo.info_hash_v2, None);
+        }
+
+        #[test]
+        fn it_should_fail_when_the_file_tree_is_missing() {
+            assert!(matches!(
+                Metainfo::from_slice(&metainfo_with_info(b"d12:meta versioni2e4:name1:a12:piece lengthi1ee")),
+                Err(crate::torrent::error::Error::MissingField(path)) if path == "info.file tree"
+            ));
+        }
+
+        #[test]
+        fn it_should_fail_when_a_pieces_root_does_not_have_32_bytes() {
+            assert!(matches!(
+                Metainfo::from_slice(&metainfo_with_info(b"d9:file treed1:ad0:d6:lengthi1e11:pieces root1:xeee12:meta versioni2e4:name1:a12:piece lengthi1ee")),
+                Err(crate::torrent::error::Error::InvalidField(path, "32 bytes")) if path == "info.file tree.a..pieces root"
+            ));
+        }
+    }
+
+    mod it_should_fail {
+        use crate::torrent::{error::Error, tests::metainfo_with_info, Metainfo};
+
+        #[test]
+        fn when_the_input_is_not_valid_bencode() {
+            assert!(matches!(
+                Metainfo::from_slice(b"d4:info"),
+                Err(Error::Parser(_))
+            ));
+        }
+
+        #[test]
+        fn when_the_metainfo_is_not_a_dictionary() {
+            assert!(matches!(
+                Metainfo::from_slice(b"li1ee"),
+                Err(Error::NotADictionary)
+            ));
+        }
+
+        #[test]
+        fn when_the_info_dictionary_is_missing() {
+            assert!(matches!(
+                Metainfo::from_slice(b"d8:announce3:urle"),
+                Err(Error::MissingField(path)) if path == "info"
+            ));
+        }
+
+        #[test]
+        fn when_a_required_field_is_missing() {
+            assert!(matches!(
+                Metainfo::from_slice(&metainfo_with_info(b"d6:lengthi5e12:piece lengthi1e6:pieces0:e")),
+                Err(Error::MissingField(path)) if path == "info.name"
+            ));
+        }
+
+        #[test]
+        fn when_a_field_has_the_wrong_type() {
+            assert!(matches!(
+                Metainfo::from_slice(&metainfo_with_info(b"d6:lengthi5e4:name1:a12:piece length1:16:pieces0:e")),
+                Err(Error::InvalidField(path, "an integer")) if path == "info.piece length"
+            ));
+        }
+
+        #[test]
+        fn when_a_nested_field_has_the_wrong_type() {
+            assert!(matches!(
+                Metainfo::from_slice(&metainfo_with_info(b"d5:filesld6:lengthi1e4:pathli1eeee4:name1:a12:piece lengthi1e6:pieces0:e")),
+                Err(Error::InvalidField(path, "a string")) if path == "info.files[0].path[0]"
+            ));
+        }
+
+        #[test]
+        fn when_the_pieces_are_not_a_multiple_of_20_bytes() {
+            assert!(matches!(
+                Metainfo::from_slice(&metainfo_with_info(b"d6:lengthi5e4:name1:a12:piece lengthi1e6:pieces3:abce")),
+                Err(Error::InvalidField(path, _)) if path == "info.pieces"
+            ));
+        }
+    }
+}
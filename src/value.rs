@@ -0,0 +1,780 @@
+//! In-memory bencoded value.
+//!
+//! It allows inspecting bencoded data without converting it into JSON first.
+//! Byte strings are kept as raw bytes, so non UTF-8 strings are not lost.
+use core::str;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    io::{Read, Write as IoWrite},
+    ops::Range,
+};
+
+use crate::{
+    parsers::{
+        error::{Error, ReadContext, WriteContext},
+        tokenizer::{Token, Tokenizer},
+        BENCODE_BEGIN_DICT, BENCODE_BEGIN_INTEGER, BENCODE_BEGIN_LIST, BENCODE_END_INTEGER,
+        BENCODE_END_LIST_OR_DICT, BENCODE_END_STRING_LENGTH,
+    },
+    rw::{self, byte_writer::ByteWriter, null_writer::NullWriter, writer::Writer},
+};
+
+/// A bencoded value.
+///
+/// Dictionaries are sorted by the raw bytes of their keys, like the bencode
+/// specification requires. If the input contains duplicate keys, the last
+/// value wins.
+///
+/// Bencoded integers do not have a size limit. They are parsed as an `i64`
+/// when possible, and as a [`BigInteger`] otherwise.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Integer(i64),
+    BigInteger(BigInteger),
+    Bytes(Vec<u8>),
+    List(Vec<Value>),
+    Dict(BTreeMap<Vec<u8>, Value>),
+}
+
+impl Value {
+    /// It parses the first bencoded value from the input.
+    ///
+    /// Line breaks before the value are ignored, like the
+    /// [`BencodeParser`](crate::parsers::BencodeParser) does. The input after
+    /// the value is not parsed.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if it can't read from the input or the input is
+    /// not a valid bencoded value.
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self, Error> {
+        ValueParser::new(reader).parse()
+    }
+
+    /// It parses the bencoded value of a slice of bytes.
+    ///
+    /// Line breaks around the value are ignored. Unlike
+    /// [`Value::from_reader`], the whole input must be a single value.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the input is not a valid bencoded value, or it
+    /// contains more data after the value: the
+    /// [`Error::TrailingData`] error.
+    pub fn from_slice(input: &[u8]) -> Result<Self, Error> {
+        let mut parser = ValueParser::new(input);
+
+        let value = parser.parse()?;
+
+        parser.check_end_of_input()?;
+
+        Ok(value)
+    }
+
+    /// It parses the bencoded value of a slice of bytes, like
+    /// [`Value::from_slice`], and returns the byte span of the value of the
+    /// `key` field in the top-level dictionary. If the field is duplicated,
+    /// it's the span of the last one, which is the one kept in the value.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the input is not a valid bencoded value, or it
+    /// contains more data after the value.
+    pub(crate) fn from_slice_with_field_span(
+        input: &[u8],
+        key: &[u8],
+    ) -> Result<(Self, Option<Range<usize>>), Error> {
+        let mut parser = ValueParser::new(input);
+        parser.span_key = Some(key.to_vec());
+
+        let value = parser.parse()?;
+
+        parser.check_end_of_input()?;
+
+        let span = parser.span.map(|span| {
+            let start = usize::try_from(span.start).expect("the offset should fit into a usize");
+            let end = usize::try_from(span.end).expect("the offset should fit into a usize");
+            start..end
+        });
+
+        Ok((value, span))
+    }
+
+    /// It encodes the value into bencoded bytes.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if it can't write to the in-memory output, which should not
+    /// happen.
+    #[must_use]
+    pub fn encode(&self) -> Vec<u8> {
+        let mut output = Vec::new();
+
+        self.write_bytes(&mut output)
+            .expect("writing to a vector should not fail");
+
+        output
+    }
+
+    /// It writes the bencoded value to the output.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if it can't write to the output.
+    pub fn write_bytes<W: IoWrite>(&self, writer: W) -> Result<(), rw::error::Error> {
+        let mut writer = ByteWriter::new(writer);
+        self.write(&mut writer)
+    }
+
+    fn write<W: Writer>(&self, writer: &mut W) -> Result<(), rw::error::Error> {
+        match self {
+            Value::Integer(integer) => {
+                writer.write_byte(BENCODE_BEGIN_INTEGER)?;
+                writer.write_str(&integer.to_string())?;
+                writer.write_byte(BENCODE_END_INTEGER)?;
+            }
+            Value::BigInteger(integer) => {
+                writer.write_byte(BENCODE_BEGIN_INTEGER)?;
+                writer.write_str(integer.as_str())?;
+                writer.write_byte(BENCODE_END_INTEGER)?;
+            }
+            Value::Bytes(bytes) => {
+                Self::write_bytes_string(bytes, writer)?;
+            }
+            Value::List(items) => {
+                writer.write_byte(BENCODE_BEGIN_LIST)?;
+                for item in items {
+                    item.write(writer)?;
+                }
+                writer.write_byte(BENCODE_END_LIST_OR_DICT)?;
+            }
+            Value::Dict(fields) => {
+                writer.write_byte(BENCODE_BEGIN_DICT)?;
+                for (key, value) in fields {
+                    Self::write_bytes_string(key, writer)?;
+                    value.write(writer)?;
+                }
+                writer.write_byte(BENCODE_END_LIST_OR_DICT)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_bytes_string<W: Writer>(bytes: &[u8], writer: &mut W) -> Result<(), rw::error::Error> {
+        writer.write_str(&bytes.len().to_string())?;
+        writer.write_byte(BENCODE_END_STRING_LENGTH)?;
+        for byte in bytes {
+            writer.write_byte(*byte)?;
+        }
+        Ok(())
+    }
+
+    /// It returns the integer if the value is an integer that fits into an
+    /// `i64`.
+    #[must_use]
+    pub fn as_integer(&self) -> Option<i64> {
+        match self {
+            Value::Integer(integer) => Some(*integer),
+            _ => None,
+        }
+    }
+
+    /// It returns the integer if the value is an integer that does not fit
+    /// into an `i64`.
+    #[must_use]
+    pub fn as_big_integer(&self) -> Option<&BigInteger> {
+        match self {
+            Value::BigInteger(integer) => Some(integer),
+            _ => None,
+        }
+    }
+
+    /// It returns the raw bytes if the value is a byte string.
+    #[must_use]
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Value::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    /// It returns the string if the value is a byte string containing only
+    /// valid UTF-8.
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str> {
+        self.as_bytes().and_then(|bytes| str::from_utf8(bytes).ok())
+    }
+
+    /// It returns the items if the value is a list.
+    #[must_use]
+    pub fn as_list(&self) -> Option<&[Value]> {
+        match self {
+            Value::List(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// It returns the fields if the value is a dictionary.
+    #[must_use]
+    pub fn as_dict(&self) -> Option<&BTreeMap<Vec<u8>, Value>> {
+        match self {
+            Value::Dict(fields) => Some(fields),
+            _ => None,
+        }
+    }
+
+    /// It returns the value of a dictionary field. It returns `None` if the
+    /// value is not a dictionary or the field does not exist.
+    #[must_use]
+    pub fn get(&self, key: &[u8]) -> Option<&Value> {
+        self.as_dict().and_then(|fields| fields.get(key))
+    }
+}
+
+impl From<i64> for Value {
+    fn from(integer: i64) -> Self {
+        Value::Integer(integer)
+    }
+}
+
+impl From<i128> for Value {
+    fn from(integer: i128) -> Self {
+        match i64::try_from(integer) {
+            Ok(integer) => Value::Integer(integer),
+            Err(_) => Value::BigInteger(BigInteger(integer.to_string())),
+        }
+    }
+}
+
+impl From<u128> for Value {
+    fn from(integer: u128) -> Self {
+        match i64::try_from(integer) {
+            Ok(integer) => Value::Integer(integer),
+            Err(_) => Value::BigInteger(BigInteger(integer.to_string())),
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(string: &str) -> Self {
+        Value::Bytes(string.as_bytes().to_vec())
+    }
+}
+
+impl From<&[u8]> for Value {
+    fn from(bytes: &[u8]) -> Self {
+        Value::Bytes(bytes.to_vec())
+    }
+}
+
+impl From<Vec<u8>> for Value {
+    fn from(bytes: Vec<u8>) -> Self {
+        Value::Bytes(bytes)
+    }
+}
+
+impl From<Vec<Value>> for Value {
+    fn from(items: Vec<Value>) -> Self {
+        Value::List(items)
+    }
+}
+
+impl From<BTreeMap<Vec<u8>, Value>> for Value {
+    fn from(fields: BTreeMap<Vec<u8>, Value>) -> Self {
+        Value::Dict(fields)
+    }
+}
+
+/// An integer that does not fit into an `i64`.
+///
+/// It keeps the decimal digits as they are in the bencoded input, so integers
+/// of any size are not lost.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BigInteger(String);
+
+impl BigInteger {
+    /// It returns the decimal digits, with a minus sign if the integer is
+    /// negative.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    #[must_use]
+    pub fn is_negative(&self) -> bool {
+        self.0.starts_with('-')
+    }
+
+    /// It returns the integer if it fits into an `i128`.
+    #[must_use]
+    pub fn to_i128(&self) -> Option<i128> {
+        self.0.parse().ok()
+    }
+
+    /// It returns the integer if it fits into a `u128`.
+    #[must_use]
+    pub fn to_u128(&self) -> Option<u128> {
+        self.0.parse().ok()
+    }
+}
+
+impl fmt::Display for BigInteger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// A list or dictionary whose items are still being parsed.
+enum Container {
+    List(Vec<Value>),
+    Dict {
+        fields: BTreeMap<Vec<u8>, Value>,
+        /// The key of the field whose value is being parsed.
+        key: Option<Vec<u8>>,
+    },
+}
+
+/// It builds a [`Value`] from bencoded bytes.
+///
+/// It's a consumer of the [`Tokenizer`] that, instead of writing JSON, keeps
+/// the unfinished lists and dictionaries in a stack.
+struct ValueParser<R: Read> {
+    tokenizer: Tokenizer<R>,
+    containers: Vec<Container>,
+
+    /// The key of the top-level dictionary field whose span is recorded.
+    span_key: Option<Vec<u8>>,
+
+    /// The input offset where the value of the `span_key` field begins.
+    span_start: Option<u64>,
+
+    /// The span of the value of the latest `span_key` field.
+    span: Option<Range<u64>>,
+}
+
+impl<R: Read> ValueParser<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            tokenizer: Tokenizer::new(reader),
+            containers: vec![],
+            span_key: None,
+            span_start: None,
+            span: None,
+        }
+    }
+
+    fn parse(&mut self) -> Result<Value, Error> {
+        // Nothing is written, the writer is only used for the error context.
+        let writer = NullWriter::new();
+
+        loop {
+            let Some(event) = self.tokenizer.next_event(&writer)? else {
+                // The tokenizer only ends without errors at the top level
+                return Err(Error::UnexpectedEndOfInputExpectingValue(
+                    ReadContext {
+                        byte: None,
+                        pos: self.tokenizer.input_byte_counter(),
+                        latest_bytes: self.tokenizer.captured_bytes(),
+                        path: self.tokenizer.path(),
+                        next_bytes: self.tokenizer.next_bytes(),
+                        token_start: None,
+                    },
+                    WriteContext {
+                        byte: None,
+                        pos: writer.output_byte_counter(),
+                        latest_bytes: writer.captured_bytes(),
+                    },
+                ));
+            };
+
+            if self.is_expecting_span_field_value() {
+                self.span_start = Some(event.start);
+            }
+
+            let value = match event.token {
+                Token::Integer(digits) => Some(Self::digits_to_integer(digits)),
+                Token::Bytes(bytes) => Some(Value::Bytes(bytes)),
+                Token::ListStart => {
+                    self.containers.push(Container::List(vec![]));
+                    None
+                }
+                Token::DictStart => {
+                    self.containers.push(Container::Dict {
+                        fields: BTreeMap::new(),
+                        key: None,
+                    });
+                    None
+                }
+                Token::End => match self.containers.pop() {
+                    Some(Container::List(items)) => Some(Value::List(items)),
+                    Some(Container::Dict { fields, .. }) => Some(Value::Dict(fields)),
+                    None => panic!("containers should match the tokenizer states"),
+                },
+            };
+
+            if let Some(value) = value {
+                if self.is_expecting_span_field_value() {
+                    self.span = self.span_start.take().map(|start| start..event.end);
+                }
+
+                if let Some(top_level_value) = self.add_value(value) {
+                    return Ok(top_level_value);
+                }
+            }
+        }
+    }
+
+    /// It returns true when the next value finished or begun is the value of
+    /// the `span_key` field in the top-level dictionary.
+    fn is_expecting_span_field_value(&self) -> bool {
+        match (self.containers.as_slice(), &self.span_key) {
+            ([Container::Dict { key, .. }], Some(span_key)) => key.as_ref() == Some(span_key),
+            _ => false,
+        }
+    }
+
+    /// It checks there are only line breaks left in the input after the
+    /// top-level value.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if it can't read from the input or there is more
+    /// data after the value.
+    fn check_end_of_input(&mut self) -> Result<(), Error> {
+        let writer = NullWriter::new();
+
+        if !self.tokenizer.has_next_token(&writer)? {
+            return Ok(());
+        }
+
+        Err(Error::TrailingData(
+            ReadContext {
+                byte: None,
+                pos: self.tokenizer.input_byte_counter(),
+                latest_bytes: self.tokenizer.captured_bytes(),
+                path: self.tokenizer.path(),
+                next_bytes: self.tokenizer.next_bytes(),
+                token_start: Some(self.tokenizer.token_start()),
+            },
+            WriteContext {
+                byte: None,
+                pos: writer.output_byte_counter(),
+                latest_bytes: writer.captured_bytes(),
+            },
+        ))
+    }
+
+    /// It adds a finished value to the current list or dictionary. It returns
+    /// the value back when there is no parent, which means the top-level
+    /// value is finished.
+    fn add_value(&mut self, value: Value) -> Option<Value> {
+        match self.containers.last_mut() {
+            None => Some(value),
+            Some(Container::List(items)) => {
+                items.push(value);
+                None
+            }
+            Some(Container::Dict { fields, key }) => {
+                match key.take() {
+                    Some(key) => {
+                        fields.insert(key, value);
+                    }
+                    None => match value {
+                        Value::Bytes(bytes) => *key = Some(bytes),
+                        _ => panic!("dictionary keys should be checked by the tokenizer"),
+                    },
+                }
+                None
+            }
+        }
+    }
+
+    /// It converts the parsed integer digits into an `i64`, or into a
+    /// [`BigInteger`] when it does not fit.
+    fn digits_to_integer(digits: Vec<u8>) -> Value {
+        // Digits only contain ASCII chars: an optional sign and digits.
+        let digits = String::from_utf8(digits).expect("integer digits should be ASCII");
+
+        match digits.parse::<i64>() {
+            Ok(integer) => Value::Integer(integer),
+            Err(_) => Value::BigInteger(BigInteger(digits)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::value::Value;
+
+    fn dict(fields: &[(&str, Value)]) -> Value {
+        Value::Dict(
+            fields
+                .iter()
+                .map(|(key, value)| (key.as_bytes().to_vec(), value.clone()))
+                .collect::<BTreeMap<_, _>>(),
+        )
+    }
+
+    mod it_should_parse {
+        use crate::value::{tests::dict, Value};
+
+        #[test]
+        fn an_integer() {
+            assert_eq!(Value::from_slice(b"i-42e").unwrap(), Value::Integer(-42));
+        }
+
+        #[test]
+        fn an_integer_that_does_not_fit_into_an_i64_as_a_big_integer() {
+            let value = Value::from_slice(b"i-170141183460469231731687303715884105729e").unwrap();
+
+            let big_integer = value.as_big_integer().unwrap();
+
+            assert_eq!(
+                big_integer.as_str(),
+                "-170141183460469231731687303715884105729"
+            );
+            assert!(big_integer.is_negative());
+            assert_eq!(big_integer.to_i128(), None);
+            assert_eq!(value.as_integer(), None);
+        }
+
+        #[test]
+        fn a_utf8_string() {
+            assert_eq!(Value::from_slice(b"4:spam").unwrap(), Value::from("spam"));
+        }
+
+        #[test]
+        fn a_non_utf8_string_keeping_the_raw_bytes() {
+            assert_eq!(
+                Value::from_slice(b"4:\xFF\xFE\xFD\xFC").unwrap(),
+                Value::Bytes(b"\xFF\xFE\xFD\xFC".to_vec())
+            );
+        }
+
+        #[test]
+        fn a_list() {
+            assert_eq!(
+                Value::from_slice(b"li42e4:spamlee").unwrap(),
+                Value::List(vec![
+                    Value::Integer(42),
+                    Value::from("spam"),
+                    Value::List(vec![])
+                ])
+            );
+        }
+
+        #[test]
+        fn a_dictionary() {
+            assert_eq!(
+                Value::from_slice(b"d3:bar4:spam3:food3:bazi42eee").unwrap(),
+                dict(&[
+                    ("bar", Value::from("spam")),
+                    ("foo", dict(&[("baz", Value::Integer(42))]))
+                ])
+            );
+        }
+
+        #[test]
+        fn a_dictionary_with_duplicate_keys_keeping_the_last_value() {
+            assert_eq!(
+                Value::from_slice(b"d3:fooi1e3:fooi2ee").unwrap(),
+                dict(&[("foo", Value::Integer(2))])
+            );
+        }
+
+        #[test]
+        fn only_the_first_value_of_the_input_from_a_reader() {
+            assert_eq!(
+                Value::from_reader(&b"i1ei2e"[..]).unwrap(),
+                Value::Integer(1)
+            );
+        }
+
+        #[test]
+        fn the_span_of_the_last_field_value_with_a_key_in_the_top_level_dictionary() {
+            let input = b"d4:infoi1e1:xd4:infoi2ee4:infoli3eee";
+
+            let (value, span) = Value::from_slice_with_field_span(input, b"info").unwrap();
+
+            assert_eq!(
+                value.get(b"info"),
+                Some(&Value::List(vec![Value::Integer(3)]))
+            );
+            assert_eq!(&input[span.unwrap()], b"li3ee");
+        }
+
+        #[test]
+        fn a_slice_ending_with_line_breaks() {
+            assert_eq!(Value::from_slice(b"i1e\r\n").unwrap(), Value::Integer(1));
+        }
+
+        #[test]
+        fn ignoring_line_breaks() {
+            assert_eq!(
+                Value::from_slice(b"\nli1e\ni2ee").unwrap(),
+                Value::List(vec![Value::Integer(1), Value::Integer(2)])
+            );
+        }
+    }
+
+    mod it_should_encode {
+        use crate::value::{tests::dict, Value};
+
+        #[test]
+        fn an_integer() {
+            assert_eq!(Value::Integer(-42).encode(), b"i-42e");
+        }
+
+        #[test]
+        fn a_big_integer() {
+            assert_eq!(
+                Value::from(u128::MAX).encode(),
+                format!("i{}e", u128::MAX).as_bytes()
+            );
+        }
+
+        #[test]
+        fn a_non_utf8_string() {
+            assert_eq!(Value::Bytes(b"\xFF\xFE".to_vec()).encode(), b"2:\xFF\xFE");
+        }
+
+        #[test]
+        fn a_list() {
+            assert_eq!(
+                Value::List(vec![Value::Integer(42), Value::from("spam")]).encode(),
+                b"li42e4:spame"
+            );
+        }
+
+        #[test]
+        fn a_dictionary_with_the_keys_sorted() {
+            assert_eq!(
+                dict(&[("foo", Value::Integer(1)), ("bar", Value::Integer(2))]).encode(),
+                b"d3:bari2e3:fooi1ee"
+            );
+        }
+
+        #[test]
+        fn the_same_bencoded_value_that_was_parsed() {
+            let bencoded_value =
+                b"d3:bigi18446744073709551616e4:listli-1e0:e3:numi42e4:pathl3:\xFF\xFE\xFDee";
+
+            assert_eq!(
+                Value::from_slice(bencoded_value).unwrap().encode(),
+                bencoded_value
+            );
+        }
+    }
+
+    mod it_should_allow_accessing {
+        use crate::value::{tests::dict, BigInteger, Value};
+
+        #[test]
+        fn the_inner_values() {
+            assert_eq!(Value::Integer(42).as_integer(), Some(42));
+            assert_eq!(Value::from("spam").as_bytes(), Some(&b"spam"[..]));
+            assert_eq!(Value::from("spam").as_str(), Some("spam"));
+            assert_eq!(Value::List(vec![]).as_list(), Some(&[][..]));
+            assert!(dict(&[]).as_dict().is_some());
+        }
+
+        #[test]
+        fn the_integers_as_an_i64_when_they_fit() {
+            assert_eq!(Value::from(42_i128), Value::Integer(42));
+            assert_eq!(
+                Value::from(u128::from(u64::MAX))
+                    .as_big_integer()
+                    .and_then(BigInteger::to_u128),
+                Some(u128::from(u64::MAX))
+            );
+        }
+
+        #[test]
+        fn the_string_only_if_it_is_valid_utf8() {
+            assert_eq!(Value::Bytes(b"\xFF".to_vec()).as_str(), None);
+        }
+
+        #[test]
+        fn the_dictionary_fields_by_key() {
+            let value = dict(&[("foo", Value::Integer(42))]);
+
+            assert_eq!(value.get(b"foo"), Some(&Value::Integer(42)));
+            assert_eq!(value.get(b"bar"), None);
+            assert_eq!(Value::Integer(42).get(b"foo"), None);
+        }
+    }
+
+    mod it_should_fail {
+        use crate::{
+            parsers::{error::Error, BencodeType},
+            value::Value,
+        };
+
+        #[test]
+        fn when_the_input_is_empty() {
+            assert!(matches!(
+                Value::from_slice(b""),
+                Err(Error::UnexpectedEndOfInputExpectingValue { .. })
+            ));
+        }
+
+        #[test]
+        fn when_it_cannot_recognized_the_fist_byte_of_a_new_bencoded_value() {
+            assert!(matches!(
+                Value::from_slice(b"a"),
+                Err(Error::UnrecognizedFirstBencodeValueByte { .. })
+            ));
+        }
+
+        #[test]
+        fn when_a_list_is_not_finished() {
+            assert!(matches!(
+                Value::from_slice(b"li42e"),
+                Err(Error::UnexpectedEndOfInputExpectingNextListItem { .. })
+            ));
+        }
+
+        #[test]
+        fn when_a_dictionary_field_does_not_have_a_value() {
+            assert!(matches!(
+                Value::from_slice(b"d3:fooe"),
+                Err(Error::PrematureEndOfDict { .. })
+            ));
+        }
+
+        #[test]
+        fn when_a_dictionary_key_is_not_a_string() {
+            assert!(matches!(
+                Value::from_slice(b"di42ei43ee"),
+                Err(Error::ExpectedStringForDictKeyGot(
+                    BencodeType::Integer,
+                    _,
+                    _
+                ))
+            ));
+        }
+
+        #[test]
+        fn when_there_is_more_than_one_value_in_the_input() {
+            let result = Value::from_slice(b"i1ei2e");
+
+            let Err(Error::TrailingData(read_context, _)) = result else {
+                panic!("expected a trailing data error, got {result:?}");
+            };
+
+            assert_eq!(read_context.offset(), 3);
+        }
+
+        #[test]
+        fn when_it_receives_an_end_byte_without_the_matching_open_byte() {
+            assert!(matches!(
+                Value::from_slice(b"e"),
+                Err(Error::NoMatchingStartForListOrDictEnd { .. })
+            ));
+        }
+    }
+}
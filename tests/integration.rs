@@ -63,6 +63,305 @@ mod it_should {
             .stderr(predicate::str::contains("Error: Unrecognized first"));
     }
 
+    #[test]
+    fn fail_when_the_bencoded_input_is_not_canonical() {
+        let mut cmd = Command::cargo_bin("torrust-bencode2json").unwrap();
+        cmd.write_stdin("i-0e")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Error: Negative zero"));
+    }
+
+    #[test]
+    fn accept_non_canonical_values_in_lenient_mode() {
+        let mut cmd = Command::cargo_bin("torrust-bencode2json").unwrap();
+        cmd.arg("--lenient")
+            .write_stdin("l03:abci-0ee")
+            .assert()
+            .success()
+            .stdout(r#"["abc",-0]"#);
+    }
+
+    #[test]
+    fn accept_unsorted_dictionary_keys_by_default() {
+        let mut cmd = Command::cargo_bin("torrust-bencode2json").unwrap();
+        cmd.write_stdin("d3:fooi42e3:bar4:spame")
+            .assert()
+            .success()
+            .stdout(r#"{"foo":42,"bar":"spam"}"#);
+    }
+
+    #[test]
+    fn fail_when_dictionary_keys_are_unsorted_in_strict_mode() {
+        let mut cmd = Command::cargo_bin("torrust-bencode2json").unwrap();
+        cmd.arg("--strict")
+            .write_stdin("d3:fooi42e3:bar4:spame")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Error: Unsorted dictionary keys"));
+    }
+
+    #[test]
+    fn fail_when_the_strict_and_lenient_modes_are_both_selected() {
+        let mut cmd = Command::cargo_bin("torrust-bencode2json").unwrap();
+        cmd.args(["--strict", "--lenient"])
+            .write_stdin("i1e")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("cannot be used with"));
+    }
+
+    #[test]
+    fn fail_when_the_input_exceeds_the_selected_limits() {
+        for (limit, value, input, message) in [
+            (
+                "--max-input-bytes",
+                "4",
+                "li1ei2ee",
+                "Maximum number of input bytes",
+            ),
+            ("--max-depth", "2", "llleee", "Maximum depth"),
+            ("--max-string-len", "3", "4:spam", "Maximum string length"),
+            (
+                "--max-integer-digits",
+                "3",
+                "i1234e",
+                "Maximum number of integer digits",
+            ),
+            ("--max-tokens", "3", "li1ei2ee", "Maximum number of tokens"),
+        ] {
+            let mut cmd = Command::cargo_bin("torrust-bencode2json").unwrap();
+            cmd.arg(limit)
+                .arg(value)
+                .write_stdin(input)
+                .assert()
+                .failure()
+                .stderr(predicate::str::contains(message));
+        }
+    }
+
+    #[test]
+    fn accept_input_within_the_selected_limits() {
+        let mut cmd = Command::cargo_bin("torrust-bencode2json").unwrap();
+        cmd.args(["--max-input-bytes", "13", "--max-depth", "2"])
+            .args(["--max-string-len", "4", "--max-integer-digits", "1"])
+            .write_stdin("ld4:spami1eee")
+            .assert()
+            .success()
+            .stdout(r#"[{"spam":1}]"#);
+    }
+
+    #[test]
+    fn write_non_utf8_strings_with_the_selected_encoding() {
+        let mut cmd = Command::cargo_bin("torrust-bencode2json").unwrap();
+        cmd.arg("--string-encoding")
+            .arg("base64")
+            .write_stdin(b"l2:\xFF\xFEe".as_slice())
+            .assert()
+            .success()
+            .stdout(r#"["//4="]"#);
+    }
+
+    #[test]
+    fn write_torrent_hashes_in_hexadecimal_in_torrent_mode() {
+        let mut input = b"d12:piece layersd32:".to_vec();
+        input.extend_from_slice(&[0xAB; 32]);
+        input.extend_from_slice(b"32:");
+        input.extend_from_slice(&[0xCD; 32]);
+        input.extend_from_slice(b"ee");
+
+        let mut cmd = Command::cargo_bin("torrust-bencode2json").unwrap();
+        cmd.arg("--mode")
+            .arg("torrent")
+            .write_stdin(input)
+            .assert()
+            .success()
+            .stdout(format!(
+                r#"{{"piece layers":{{"{}":["{}"]}}}}"#,
+                "ab".repeat(32),
+                "cd".repeat(32)
+            ));
+    }
+
+    #[test]
+    fn decode_compact_peers_in_tracker_response_mode() {
+        let mut cmd = Command::cargo_bin("torrust-bencode2json").unwrap();
+        cmd.arg("--mode")
+            .arg("tracker-response")
+            .write_stdin(b"d8:intervali1800e5:peers6:\x01\x02\x03\x04\x1a\xe1e".as_slice())
+            .assert()
+            .success()
+            .stdout(r#"{"interval":1800,"peers":[{"ip":"1.2.3.4","port":6881}]}"#);
+    }
+
+    #[test]
+    fn fail_when_a_scrape_response_does_not_match_the_schema_in_scrape_mode() {
+        let mut cmd = Command::cargo_bin("torrust-bencode2json").unwrap();
+        cmd.arg("--mode")
+            .arg("scrape")
+            .write_stdin(b"d5:fileslee".as_slice())
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(
+                "Unexpected type for field `files`, expected Dict, got List",
+            ));
+    }
+
+    #[test]
+    fn fail_when_a_krpc_message_is_malformed_in_krpc_mode() {
+        let mut cmd = Command::cargo_bin("torrust-bencode2json").unwrap();
+        cmd.arg("--mode")
+            .arg("krpc")
+            .write_stdin(b"d1:t2:aa1:y1:qe".as_slice())
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(
+                "Malformed KRPC message, missing method name `q` in query",
+            ));
+    }
+
+    #[test]
+    fn write_each_value_in_a_line_with_the_ndjson_multi_value_format() {
+        let mut cmd = Command::cargo_bin("torrust-bencode2json").unwrap();
+        cmd.arg("--multi")
+            .arg("ndjson")
+            .write_stdin("i1eli2ee")
+            .assert()
+            .success()
+            .stdout("1\n[2]\n");
+    }
+
+    #[test]
+    fn pretty_print_the_json_with_the_selected_indentation() {
+        let mut cmd = Command::cargo_bin("torrust-bencode2json").unwrap();
+        cmd.arg("--indent")
+            .arg("4")
+            .write_stdin("d3:foold3:barli1ei2eeeee")
+            .assert()
+            .success()
+            .stdout(
+                "{\n    \"foo\": [\n        {\n            \"bar\": [1, 2]\n        }\n    ]\n}",
+            );
+    }
+
+    #[test]
+    fn write_yaml_with_the_yaml_format() {
+        let mut cmd = Command::cargo_bin("torrust-bencode2json").unwrap();
+        cmd.arg("--format")
+            .arg("yaml")
+            .write_stdin("d3:foold3:bari42eeee")
+            .assert()
+            .success()
+            .stdout("foo:\n  - bar: 42\n");
+    }
+
+    #[test]
+    fn write_cbor_with_the_cbor_format() {
+        let mut cmd = Command::cargo_bin("torrust-bencode2json").unwrap();
+        cmd.arg("--format")
+            .arg("cbor")
+            .write_stdin("li42e4:spame")
+            .assert()
+            .success()
+            .stdout(&[0x9F, 0x18, 0x2A, 0x44, b's', b'p', b'a', b'm', 0xFF][..]);
+    }
+
+    #[test]
+    fn write_integers_out_of_the_selected_range_as_strings() {
+        let mut cmd = Command::cargo_bin("torrust-bencode2json").unwrap();
+        cmd.arg("--big-integers")
+            .arg("string")
+            .arg("--integer-range")
+            .arg("json-safe")
+            .write_stdin("li42ei9007199254740992ee")
+            .assert()
+            .success()
+            .stdout(r#"[42,"9007199254740992"]"#);
+    }
+
+    #[test]
+    fn fail_when_an_integer_is_out_of_range_with_the_fail_option() {
+        let mut cmd = Command::cargo_bin("torrust-bencode2json").unwrap();
+        cmd.arg("--big-integers")
+            .arg("fail")
+            .write_stdin("i9223372036854775808e")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Integer out of range"));
+    }
+
+    #[test]
+    fn write_errors_as_json_reports_with_the_json_error_format() {
+        let mut cmd = Command::cargo_bin("torrust-bencode2json").unwrap();
+        let output = cmd
+            .arg("--error-format")
+            .arg("json")
+            .write_stdin("li1ei2x")
+            .assert()
+            .failure()
+            .get_output()
+            .stderr
+            .clone();
+
+        let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+        assert_eq!(report["kind"], "unexpected_byte_parsing_integer");
+        assert_eq!(report["found"], "0x78 (`x`)");
+        assert_eq!(report["read_context"]["pos"], 7);
+        assert_eq!(report["read_context"]["offset"], 6);
+        assert_eq!(report["read_context"]["path"], "[1]");
+        assert_eq!(report["read_context"]["latest_bytes"], "6c693165693278");
+    }
+
+    #[test]
+    fn include_the_path_to_the_value_in_the_errors() {
+        let mut cmd = Command::cargo_bin("torrust-bencode2json").unwrap();
+        cmd.write_stdin("d4:infod5:filesld6:lengthi1xeeee")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("path `info.files[0].length`"));
+    }
+
+    #[test]
+    fn point_to_the_failing_byte_in_a_hex_dump_of_the_input_by_default() {
+        let mut cmd = Command::cargo_bin("torrust-bencode2json").unwrap();
+        cmd.write_stdin("li1ei2xe")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(
+                "\
+Error: Unexpected byte parsing integer
+  --> input offset 6, path `[1]`
+  expected a digit or the end of the integer `e`, found 0x78 (`x`)
+
+00000000: 6c69 3165 6932 7865                      li1ei2xe
+                         ^^                              ^
+",
+            ));
+    }
+
+    #[test]
+    fn write_errors_in_one_line_with_the_plain_errors_option() {
+        let mut cmd = Command::cargo_bin("torrust-bencode2json").unwrap();
+        cmd.arg("--plain-errors")
+            .write_stdin("li1ei2xe")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(
+                "Error: Unexpected byte parsing integer; read context: byte `120` (char: `x`), input pos 7, path `[1]`,",
+            ))
+            .stderr(predicate::str::contains("00000000:").not());
+    }
+
+    #[test]
+    fn fail_when_there_is_more_than_one_value_by_default() {
+        let mut cmd = Command::cargo_bin("torrust-bencode2json").unwrap();
+        cmd.write_stdin("i1ei2e")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Unexpected trailing data"));
+    }
+
     #[test]
     fn fail_reading_from_non_existing_file() {
         let temp_dir = tempdir().unwrap();